@@ -1,5 +1,10 @@
 use cumulus_primitives_core::ParaId;
-use nbchain_runtime::{AccountId, AuraId, Signature, SudoConfig, EXISTENTIAL_DEPOSIT};
+use nbchain_runtime::{
+    pallet_relay_operations::RelayPalletIndices,
+    xcm_config::{relay_token_genesis_units_per_second, RELAY_TOKEN_ASSET_ID},
+    AccountId, AuraId, Balance, BlockNumber, Signature, SudoConfig, DAYS, EXISTENTIAL_DEPOSIT,
+    UNIT,
+};
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
 use sc_service::ChainType;
 use serde::{Deserialize, Serialize};
@@ -12,6 +17,15 @@ pub type ChainSpec = sc_service::GenericChainSpec<nbchain_runtime::GenesisConfig
 /// The default XCM version to set in genesis config.
 const SAFE_XCM_VERSION: u32 = xcm::prelude::XCM_VERSION;
 
+/// The relay chain of the chain specs.
+const RELAY_CHAIN: &str = "rococo-local"; // You MUST set this to the correct network!
+
+/// The parachain ids the test chain specs are run with, trusted by each other at genesis.
+const TESTNET_PARA_IDS: [u32; 2] = [1000, 2000];
+
+/// The weight a testnet sibling may use per block without paying for it, a tenth of a second.
+const SIBLING_UNPAID_EXECUTION_LIMIT: u64 = 100_000_000_000;
+
 /// Helper function to generate a crypto pair from seed
 pub fn get_public_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
     TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -53,6 +67,46 @@ where
     AccountPublic::from(get_public_from_seed::<TPublic>(seed)).into_account()
 }
 
+/// The free balance given to every endowed account.
+const ENDOWMENT: Balance = 10_000_000_000_000_000_000_000;
+
+/// A genesis allocation that is locked and released by `pallet_vesting`.
+///
+/// Nothing beyond `liquid` can be transferred before `cliff`; from then on the remaining
+/// `amount - liquid` unlocks linearly over `duration` blocks.
+#[derive(Clone)]
+pub struct LockedAllocation {
+    /// The account receiving the allocation.
+    pub who: AccountId,
+    /// Total balance credited to the account at genesis.
+    pub amount: Balance,
+    /// Part of `amount` that is spendable right away.
+    pub liquid: Balance,
+    /// Block at which the linear unlock starts.
+    pub cliff: BlockNumber,
+    /// Number of blocks over which the locked part is released.
+    pub duration: BlockNumber,
+}
+
+impl LockedAllocation {
+    /// Lock all of `amount` until `cliff`, then release it linearly over `duration` blocks.
+    pub fn new(who: AccountId, amount: Balance, cliff: BlockNumber, duration: BlockNumber) -> Self {
+        Self {
+            who,
+            amount,
+            liquid: 0,
+            cliff,
+            duration,
+        }
+    }
+
+    /// Leave `liquid` of the allocation spendable from genesis.
+    pub fn with_liquid(mut self, liquid: Balance) -> Self {
+        self.liquid = liquid.min(self.amount);
+        self
+    }
+}
+
 /// Generate the session keys from individual elements.
 ///
 /// The input must be a tuple of individual keys (a single arg for now since we have just one key).
@@ -101,7 +155,18 @@ pub fn development_config(para_id: u32) -> ChainSpec {
                     get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
                 ],
+                vec![
+                    // Locked for 30 days, then released over a year.
+                    LockedAllocation::new(
+                        get_account_id_from_seed::<sr25519::Public>("Eve//vesting"),
+                        1_000_000 * UNIT,
+                        30 * DAYS,
+                        365 * DAYS,
+                    )
+                    .with_liquid(1_000 * UNIT),
+                ],
                 para_id.into(),
+                RELAY_CHAIN,
             )
         },
         Vec::new(),
@@ -110,7 +175,7 @@ pub fn development_config(para_id: u32) -> ChainSpec {
         None,
         None,
         Extensions {
-            relay_chain: "rococo-local".into(), // You MUST set this to the correct network!
+            relay_chain: RELAY_CHAIN.into(),
             para_id,
         },
     )
@@ -157,7 +222,9 @@ pub fn local_testnet_config() -> ChainSpec {
                     get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
                 ],
+                Vec::new(),
                 1000.into(),
+                RELAY_CHAIN,
             )
         },
         // Bootnodes
@@ -172,18 +239,53 @@ pub fn local_testnet_config() -> ChainSpec {
         Some(properties),
         // Extensions
         Extensions {
-            relay_chain: "rococo-local".into(), // You MUST set this to the correct network!
+            relay_chain: RELAY_CHAIN.into(),
             para_id: 1000,
         },
     )
 }
 
+/// The name, symbol, decimals and existential deposit of the token of `relay_chain`.
+fn relay_token(relay_chain: &str) -> Option<(&'static str, &'static str, u8, Balance)> {
+    if relay_chain.starts_with("polkadot") {
+        Some(("Polkadot", "DOT", 10, 10_000_000_000))
+    } else if relay_chain.starts_with("kusama") {
+        Some(("Kusama", "KSM", 12, 33_333_333))
+    } else if relay_chain.starts_with("westend") {
+        Some(("Westend", "WND", 12, 10_000_000_000))
+    } else if relay_chain.starts_with("rococo") {
+        Some(("Rococo", "ROC", 12, 10_000_000_000))
+    } else {
+        None
+    }
+}
+
 fn testnet_genesis(
     root_key: AccountId,
     invulnerables: Vec<(AccountId, AuraId)>,
     endowed_accounts: Vec<AccountId>,
+    locked_allocations: Vec<LockedAllocation>,
     id: ParaId,
+    relay_chain: &str,
 ) -> nbchain_runtime::GenesisConfig {
+    let (relay_token_name, relay_token_symbol, relay_token_decimals, relay_token_ed) =
+        relay_token(relay_chain).expect("relay chain token is known; qed");
+    // `pallet_balances` credits every account once, and vesting would lock more than the locked
+    // part of an allocation merged with an endowment.
+    for (i, allocation) in locked_allocations.iter().enumerate() {
+        assert!(
+            !endowed_accounts.contains(&allocation.who)
+                && locked_allocations[..i]
+                    .iter()
+                    .all(|other| other.who != allocation.who),
+            "{} has a locked allocation and another allocation or an endowment",
+            allocation.who
+        );
+    }
+    let siblings: Vec<u32> = TESTNET_PARA_IDS
+        .into_iter()
+        .filter(|para_id| ParaId::from(*para_id) != id)
+        .collect();
     nbchain_runtime::GenesisConfig {
         system: nbchain_runtime::SystemConfig {
             code: nbchain_runtime::WASM_BINARY
@@ -197,7 +299,25 @@ fn testnet_genesis(
             balances: endowed_accounts
                 .iter()
                 .cloned()
-                .map(|k| (k, 10_000_000_000_000_000_000_000))
+                .map(|k| (k, ENDOWMENT))
+                .chain(
+                    locked_allocations
+                        .iter()
+                        .map(|allocation| (allocation.who.clone(), allocation.amount)),
+                )
+                .collect(),
+        },
+        vesting: nbchain_runtime::VestingConfig {
+            vesting: locked_allocations
+                .into_iter()
+                .map(|allocation| {
+                    (
+                        allocation.who,
+                        allocation.cliff,
+                        allocation.duration,
+                        allocation.liquid,
+                    )
+                })
                 .collect(),
         },
         parachain_info: nbchain_runtime::ParachainInfoConfig { parachain_id: id },
@@ -226,5 +346,28 @@ fn testnet_genesis(
         polkadot_xcm: nbchain_runtime::PolkadotXcmConfig {
             safe_xcm_version: Some(SAFE_XCM_VERSION),
         },
+        relay_operations: nbchain_runtime::RelayOperationsConfig {
+            relay_pallets: RelayPalletIndices::for_relay_chain(relay_chain)
+                .expect("relay chain pallet indices are known; qed"),
+        },
+        asset_registry: nbchain_runtime::AssetRegistryConfig {
+            // The relay token pays for execution from genesis, at the rate it was charged at
+            // before it had one in `AssetRegistry`.
+            relay_token: Some((
+                RELAY_TOKEN_ASSET_ID,
+                relay_token_name.into(),
+                relay_token_symbol.into(),
+                relay_token_decimals,
+                relay_token_ed,
+                Some(relay_token_genesis_units_per_second()),
+            )),
+        },
+        xcm_trust: nbchain_runtime::XcmTrustConfig {
+            unpaid_execution_siblings: siblings
+                .iter()
+                .map(|para_id| (*para_id, SIBLING_UNPAID_EXECUTION_LIMIT))
+                .collect(),
+            paid_execution_siblings: siblings,
+        },
     }
 }
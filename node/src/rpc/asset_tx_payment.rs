@@ -0,0 +1,100 @@
+//! RPC methods for querying the fees of transactions that pay in an asset.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::AssetTxPaymentApi as AssetTxPaymentRuntimeApi, opaque::Block, AssetId, Balance,
+};
+use pallet_transaction_payment_rpc_runtime_api::{FeeDetails, InclusionFee, RuntimeDispatchInfo};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use super::{decode_extrinsic, runtime_error};
+
+/// Fee queries for transactions paying with `ChargeAssetTxPayment`.
+#[rpc(client, server)]
+pub trait AssetTxPaymentApi<BlockHash> {
+    /// Dispatch info of the encoded extrinsic, with the partial fee expressed in `asset_id`.
+    #[method(name = "assetTxPayment_queryInfo")]
+    fn query_info(
+        &self,
+        encoded_xt: Bytes,
+        asset_id: AssetId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<RuntimeDispatchInfo<Balance>>>;
+
+    /// Fee details of the encoded extrinsic, with every component expressed in `asset_id`.
+    #[method(name = "assetTxPayment_queryFeeDetails")]
+    fn query_fee_details(
+        &self,
+        encoded_xt: Bytes,
+        asset_id: AssetId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<FeeDetails<NumberOrHex>>>;
+}
+
+/// Implements [`AssetTxPaymentApiServer`] on top of the runtime's `AssetTxPaymentApi`.
+pub struct AssetTxPayment<C> {
+    client: Arc<C>,
+}
+
+impl<C> AssetTxPayment<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> AssetTxPaymentApiServer<<Block as BlockT>::Hash> for AssetTxPayment<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: AssetTxPaymentRuntimeApi<Block, Balance, AssetId>,
+{
+    fn query_info(
+        &self,
+        encoded_xt: Bytes,
+        asset_id: AssetId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<RuntimeDispatchInfo<Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let encoded_len = encoded_xt.len() as u32;
+        let uxt = decode_extrinsic(&encoded_xt)?;
+
+        api.query_info_in_asset(&at, uxt, encoded_len, asset_id)
+            .map_err(|e| runtime_error("Unable to query dispatch info.", e))
+    }
+
+    fn query_fee_details(
+        &self,
+        encoded_xt: Bytes,
+        asset_id: AssetId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<FeeDetails<NumberOrHex>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let encoded_len = encoded_xt.len() as u32;
+        let uxt = decode_extrinsic(&encoded_xt)?;
+
+        let fee_details = api
+            .query_fee_details_in_asset(&at, uxt, encoded_len, asset_id)
+            .map_err(|e| runtime_error("Unable to query fee details.", e))?;
+
+        Ok(fee_details.map(|details| FeeDetails {
+            inclusion_fee: details.inclusion_fee.map(|fee| InclusionFee {
+                base_fee: fee.base_fee.into(),
+                len_fee: fee.len_fee.into(),
+                adjusted_weight_fee: fee.adjusted_weight_fee.into(),
+            }),
+            tip: details.tip.into(),
+        }))
+    }
+}
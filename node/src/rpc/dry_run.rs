@@ -0,0 +1,183 @@
+//! RPC methods for previewing what an extrinsic or an XCM message would do.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::{DryRunApi as DryRunRuntimeApi, ExtrinsicDryRunEffects, XcmDryRunEffects},
+    opaque::Block,
+    Balance, Event,
+};
+use sc_rpc::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::blake2_256, Bytes, H256};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use xcm::{latest::Outcome, VersionedMultiLocation, VersionedXcm};
+
+use super::{decode_extrinsic, decode_param, runtime_error};
+
+/// An XCM message sent during a dry run, in a readable form.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentXcm {
+    /// The destination of the message.
+    pub dest: String,
+    /// The message.
+    pub message: String,
+    /// The hash `xcm_traceMessage` knows the message by.
+    pub hash: H256,
+    /// The SCALE-encoded `VersionedXcm` of the message.
+    pub encoded: Bytes,
+}
+
+/// What a dry run did, in a readable form.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunEffects {
+    /// Whether the extrinsic was applied and dispatched successfully, or the message executed
+    /// completely.
+    pub success: bool,
+    /// The result of applying the extrinsic, or the outcome of executing the message.
+    pub result: String,
+    /// The events emitted.
+    pub events: Vec<String>,
+    /// The weight used.
+    pub actual_weight: u64,
+    /// The fee paid in NB, for extrinsics that paid one in NB.
+    pub fee: Option<NumberOrHex>,
+    /// The XCM messages sent to other chains.
+    pub sent_xcm: Vec<SentXcm>,
+}
+
+fn sent_xcm(sent: Vec<(VersionedMultiLocation, VersionedXcm<()>)>) -> Vec<SentXcm> {
+    sent.into_iter()
+        .map(|(dest, message)| {
+            let encoded = message.encode();
+            SentXcm {
+                dest: format!("{:?}", dest),
+                message: format!("{:?}", message),
+                hash: blake2_256(&encoded).into(),
+                encoded: encoded.into(),
+            }
+        })
+        .collect()
+}
+
+fn events(events: Vec<Event>) -> Vec<String> {
+    events.iter().map(|event| format!("{:?}", event)).collect()
+}
+
+impl From<ExtrinsicDryRunEffects<Event, Balance>> for DryRunEffects {
+    fn from(effects: ExtrinsicDryRunEffects<Event, Balance>) -> Self {
+        Self {
+            success: matches!(effects.result, Ok(Ok(()))),
+            result: format!("{:?}", effects.result),
+            events: events(effects.events),
+            actual_weight: effects.actual_weight,
+            fee: effects.fee.map(Into::into),
+            sent_xcm: sent_xcm(effects.sent_xcm),
+        }
+    }
+}
+
+impl From<XcmDryRunEffects<Event>> for DryRunEffects {
+    fn from(effects: XcmDryRunEffects<Event>) -> Self {
+        Self {
+            success: matches!(effects.outcome, Outcome::Complete(_)),
+            result: format!("{:?}", effects.outcome),
+            events: events(effects.events),
+            actual_weight: effects.outcome.weight_used(),
+            fee: None,
+            sent_xcm: sent_xcm(effects.sent_xcm),
+        }
+    }
+}
+
+/// Dry runs of extrinsics and XCM messages. Both are unsafe: they execute whatever they are
+/// given.
+#[rpc(client, server)]
+pub trait DryRunApi<BlockHash> {
+    /// Apply the SCALE-encoded signed extrinsic `extrinsic` on top of the block `at`, without
+    /// keeping any of its changes.
+    #[method(name = "dryRun_extrinsic")]
+    fn dry_run_extrinsic(
+        &self,
+        extrinsic: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<DryRunEffects>;
+
+    /// Execute the SCALE-encoded `VersionedXcm` `message` as if it was received from the
+    /// SCALE-encoded `VersionedMultiLocation` `origin`, on top of the block `at`, without keeping
+    /// any of its changes. Returns `None` if either can't be converted to the latest version.
+    #[method(name = "dryRun_xcm")]
+    fn dry_run_xcm(
+        &self,
+        origin: Bytes,
+        message: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<DryRunEffects>>;
+}
+
+/// Implements [`DryRunApiServer`] on top of the runtime's `DryRunApi`.
+pub struct DryRun<C> {
+    client: Arc<C>,
+    deny_unsafe: DenyUnsafe,
+}
+
+impl<C> DryRun<C> {
+    /// Create a new instance backed by `client`, serving unsafe calls as `deny_unsafe` allows.
+    pub fn new(client: Arc<C>, deny_unsafe: DenyUnsafe) -> Self {
+        Self {
+            client,
+            deny_unsafe,
+        }
+    }
+}
+
+#[async_trait]
+impl<C> DryRunApiServer<<Block as BlockT>::Hash> for DryRun<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: DryRunRuntimeApi<Block, Event, Balance>,
+{
+    fn dry_run_extrinsic(
+        &self,
+        extrinsic: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<DryRunEffects> {
+        self.deny_unsafe.check_if_safe()?;
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let uxt = decode_extrinsic(&extrinsic)?;
+
+        let effects = api
+            .dry_run_extrinsic(&at, uxt)
+            .map_err(|e| runtime_error("Unable to dry run extrinsic.", e))?;
+        Ok(effects.into())
+    }
+
+    fn dry_run_xcm(
+        &self,
+        origin: Bytes,
+        message: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<DryRunEffects>> {
+        self.deny_unsafe.check_if_safe()?;
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let origin: VersionedMultiLocation = decode_param(&origin, "origin")?;
+        let message: VersionedXcm<()> = decode_param(&message, "message")?;
+
+        let effects = api
+            .dry_run_xcm(&at, origin, message)
+            .map_err(|e| runtime_error("Unable to dry run XCM message.", e))?;
+        Ok(effects.map(Into::into))
+    }
+}
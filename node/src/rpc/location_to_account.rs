@@ -0,0 +1,63 @@
+//! RPC methods for finding the local account of an XCM location.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::LocationToAccountApi as LocationToAccountRuntimeApi, opaque::Block, AccountId,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use xcm::VersionedMultiLocation;
+
+use super::{decode_param, runtime_error};
+
+/// Queries for the local accounts of XCM locations.
+#[rpc(client, server)]
+pub trait LocationToAccountApi<BlockHash> {
+    /// The local account of the SCALE-encoded `VersionedMultiLocation` `location`, e.g. the
+    /// account a user on a sibling `Transact`s from. Returns `None` if it has none.
+    #[method(name = "xcm_locationToAccountId")]
+    fn location_to_account(
+        &self,
+        location: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<AccountId>>;
+}
+
+/// Implements [`LocationToAccountApiServer`] on top of the runtime's `LocationToAccountApi`.
+pub struct LocationToAccount<C> {
+    client: Arc<C>,
+}
+
+impl<C> LocationToAccount<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> LocationToAccountApiServer<<Block as BlockT>::Hash> for LocationToAccount<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: LocationToAccountRuntimeApi<Block, AccountId>,
+{
+    fn location_to_account(
+        &self,
+        location: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let location: VersionedMultiLocation = decode_param(&location, "location")?;
+
+        api.location_to_account(&at, location)
+            .map_err(|e| runtime_error("Unable to convert location.", e))
+    }
+}
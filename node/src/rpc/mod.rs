@@ -0,0 +1,130 @@
+//! A collection of node-specific RPC methods.
+//! Substrate provides the `sc-rpc` crate, which defines the core RPC layer
+//! used by Substrate nodes. This file extends those RPC definitions with
+//! capabilities that are specific to this project's runtime configuration.
+
+#![warn(missing_docs)]
+
+use std::sync::Arc;
+
+use codec::Decode;
+use jsonrpsee::types::error::{CallError, ErrorObject};
+use nbchain_runtime::{
+    opaque::Block, AccountId, AssetId, Balance, BlockNumber, Event, Index as Nonce,
+};
+
+use sc_client_api::AuxStore;
+pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::Block as BlockT;
+
+mod asset_tx_payment;
+mod dry_run;
+mod location_to_account;
+mod overweight_xcm;
+mod xcm_fee;
+mod xcm_log;
+mod xcm_version;
+
+/// Error code returned when a runtime API call fails.
+const RUNTIME_ERROR: i32 = 1;
+/// Error code returned when a parameter cannot be decoded.
+const DECODE_ERROR: i32 = 2;
+
+/// Turn a failed runtime API call into an RPC error.
+fn runtime_error(message: &str, error: impl std::fmt::Display) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        message,
+        Some(error.to_string()),
+    ))
+    .into()
+}
+
+/// Decode a SCALE-encoded parameter, turning a failure into an RPC error.
+fn decode_param<T: Decode>(encoded: &[u8], what: &str) -> Result<T, jsonrpsee::core::Error> {
+    T::decode(&mut &*encoded).map_err(|e| {
+        CallError::Custom(ErrorObject::owned(
+            DECODE_ERROR,
+            format!("Unable to decode {}.", what),
+            Some(e.to_string()),
+        ))
+        .into()
+    })
+}
+
+/// Decode an opaque extrinsic.
+fn decode_extrinsic(
+    encoded: &[u8],
+) -> Result<<Block as BlockT>::Extrinsic, jsonrpsee::core::Error> {
+    decode_param(encoded, "extrinsic")
+}
+
+/// A type representing all RPC extensions.
+pub type RpcExtension = jsonrpsee::RpcModule<()>;
+
+/// Full client dependencies
+pub struct FullDeps<C, P> {
+    /// The client instance to use.
+    pub client: Arc<C>,
+    /// Transaction pool instance.
+    pub pool: Arc<P>,
+    /// Whether to deny unsafe calls
+    pub deny_unsafe: DenyUnsafe,
+}
+
+/// Instantiate all RPC extensions.
+pub fn create_full<C, P>(
+    deps: FullDeps<C, P>,
+) -> Result<RpcExtension, Box<dyn std::error::Error + Send + Sync>>
+where
+    C: ProvideRuntimeApi<Block>
+        + HeaderBackend<Block>
+        + AuxStore
+        + HeaderMetadata<Block, Error = BlockChainError>
+        + Send
+        + Sync
+        + 'static,
+    C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: nbchain_runtime::apis::AssetTxPaymentApi<Block, Balance, AssetId>,
+    C::Api: nbchain_runtime::apis::LocationToAccountApi<Block, AccountId>,
+    C::Api: nbchain_runtime::apis::XcmVersionApi<Block>,
+    C::Api: nbchain_runtime::apis::XcmFeeApi<Block, Balance>,
+    C::Api: nbchain_runtime::apis::DryRunApi<Block, Event, Balance>,
+    C::Api: nbchain_runtime::apis::XcmLogApi<Block, BlockNumber>,
+    C::Api: nbchain_runtime::apis::OverweightXcmApi<Block>,
+    C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+    C::Api: BlockBuilder<Block>,
+    P: TransactionPool + Sync + Send + 'static,
+{
+    use asset_tx_payment::{AssetTxPayment, AssetTxPaymentApiServer};
+    use dry_run::{DryRun, DryRunApiServer};
+    use location_to_account::{LocationToAccount, LocationToAccountApiServer};
+    use overweight_xcm::{OverweightXcm, OverweightXcmApiServer};
+    use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+    use substrate_frame_rpc_system::{System, SystemApiServer};
+    use xcm_fee::{XcmFeeApiServer, XcmFeeEstimator};
+    use xcm_log::{XcmLog, XcmLogApiServer};
+    use xcm_version::{XcmVersion, XcmVersionApiServer};
+
+    let mut module = RpcExtension::new(());
+    let FullDeps {
+        client,
+        pool,
+        deny_unsafe,
+    } = deps;
+
+    module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
+    module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+    module.merge(AssetTxPayment::new(client.clone()).into_rpc())?;
+    module.merge(LocationToAccount::new(client.clone()).into_rpc())?;
+    module.merge(XcmVersion::new(client.clone()).into_rpc())?;
+    module.merge(XcmFeeEstimator::new(client.clone()).into_rpc())?;
+    module.merge(DryRun::new(client.clone(), deny_unsafe).into_rpc())?;
+    module.merge(XcmLog::new(client.clone()).into_rpc())?;
+    module.merge(OverweightXcm::new(client).into_rpc())?;
+    Ok(module)
+}
@@ -0,0 +1,102 @@
+//! RPC methods for inspecting the overweight XCM messages parked by the inbound queues.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::{OverweightMessage, OverweightXcmApi as OverweightXcmRuntimeApi, OverweightXcmQueue},
+    opaque::Block,
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use super::runtime_error;
+
+/// An overweight message, in a readable form.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverweightXcmMessage {
+    /// The queue holding the message, `xcmp` or `dmp`.
+    pub queue: String,
+    /// The index to pass to `OverweightXcm::service_overweight`.
+    pub index: u64,
+    /// The chain that sent the message.
+    pub origin: String,
+    /// The relay chain block the message was sent in.
+    pub sent_at: u32,
+    /// The size of the encoded message, in bytes.
+    pub size: u32,
+    /// The weight needed to execute the message, `None` if it can't be decoded or weighed.
+    pub required_weight: Option<u64>,
+    /// The instructions of the message, `None` if it can't be decoded.
+    pub instructions: Option<Vec<String>>,
+}
+
+impl From<OverweightMessage> for OverweightXcmMessage {
+    fn from(message: OverweightMessage) -> Self {
+        Self {
+            queue: match message.queue {
+                OverweightXcmQueue::Xcmp => "xcmp",
+                OverweightXcmQueue::Dmp => "dmp",
+            }
+            .into(),
+            index: message.index,
+            origin: format!("{:?}", message.origin),
+            sent_at: message.sent_at,
+            size: message.size,
+            required_weight: message.required_weight,
+            instructions: message.message.map(|message| {
+                message
+                    .0
+                    .iter()
+                    .map(|instruction| format!("{:?}", instruction))
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Overweight XCM message inspection.
+#[rpc(client, server)]
+pub trait OverweightXcmApi<BlockHash> {
+    /// The overweight messages parked by `XcmpQueue` and `DmpQueue` as of the block `at`.
+    #[method(name = "xcm_overweightMessages")]
+    fn overweight_messages(&self, at: Option<BlockHash>) -> RpcResult<Vec<OverweightXcmMessage>>;
+}
+
+/// Implements [`OverweightXcmApiServer`] on top of the runtime's `OverweightXcmApi`.
+pub struct OverweightXcm<C> {
+    client: Arc<C>,
+}
+
+impl<C> OverweightXcm<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> OverweightXcmApiServer<<Block as BlockT>::Hash> for OverweightXcm<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: OverweightXcmRuntimeApi<Block>,
+{
+    fn overweight_messages(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<OverweightXcmMessage>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        let messages = api
+            .overweight_xcm_messages(&at)
+            .map_err(|e| runtime_error("Unable to query overweight XCM messages.", e))?;
+        Ok(messages.into_iter().map(Into::into).collect())
+    }
+}
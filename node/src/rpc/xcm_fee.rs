@@ -0,0 +1,107 @@
+//! RPC methods for estimating the weight and fee of XCM messages executed by this chain.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{apis::XcmFeeApi as XcmFeeRuntimeApi, opaque::Block, Balance};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use xcm::{VersionedMultiLocation, VersionedXcm};
+
+use super::{decode_param, runtime_error};
+
+/// The weight and fee of executing a message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XcmFee {
+    /// The weight of the message, e.g. for `require_weight_at_most` or a `BuyExecution` limit.
+    pub weight: u64,
+    /// The fee charged for that weight.
+    pub fee: NumberOrHex,
+}
+
+/// XCM weight and fee estimates.
+#[rpc(client, server)]
+pub trait XcmFeeApi<BlockHash> {
+    /// The weight of executing the SCALE-encoded `VersionedXcm` `message` on this chain.
+    /// Returns `None` if it can't be weighed or converted to the latest version.
+    #[method(name = "xcm_queryWeight")]
+    fn query_weight(&self, message: Bytes, at: Option<BlockHash>) -> RpcResult<Option<u64>>;
+
+    /// The weight of executing the SCALE-encoded `VersionedXcm` `message` on this chain, and the
+    /// fee charged for it in the asset at the SCALE-encoded `VersionedMultiLocation` `asset`.
+    /// Returns `None` if the message can't be weighed or the asset is not accepted as payment.
+    #[method(name = "xcm_queryFee")]
+    fn query_fee(
+        &self,
+        message: Bytes,
+        asset: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<XcmFee>>;
+}
+
+/// Implements [`XcmFeeApiServer`] on top of the runtime's `XcmFeeApi`.
+pub struct XcmFeeEstimator<C> {
+    client: Arc<C>,
+}
+
+impl<C> XcmFeeEstimator<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> XcmFeeApiServer<<Block as BlockT>::Hash> for XcmFeeEstimator<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: XcmFeeRuntimeApi<Block, Balance>,
+{
+    fn query_weight(
+        &self,
+        message: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<u64>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let message: VersionedXcm<()> = decode_param(&message, "message")?;
+
+        api.query_xcm_weight(&at, message)
+            .map_err(|e| runtime_error("Unable to query XCM weight.", e))
+    }
+
+    fn query_fee(
+        &self,
+        message: Bytes,
+        asset: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<XcmFee>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let message: VersionedXcm<()> = decode_param(&message, "message")?;
+        let asset: VersionedMultiLocation = decode_param(&asset, "asset")?;
+
+        let weight = match api
+            .query_xcm_weight(&at, message)
+            .map_err(|e| runtime_error("Unable to query XCM weight.", e))?
+        {
+            Some(weight) => weight,
+            None => return Ok(None),
+        };
+        let fee = api
+            .query_xcm_fee(&at, weight, asset)
+            .map_err(|e| runtime_error("Unable to query XCM fee.", e))?;
+        Ok(fee.map(|fee| XcmFee {
+            weight,
+            fee: fee.into(),
+        }))
+    }
+}
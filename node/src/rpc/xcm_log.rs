@@ -0,0 +1,134 @@
+//! RPC methods for tracing XCM messages through the log kept by `XcmLog`.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::{LoggedMessage, XcmLogApi as XcmLogRuntimeApi, XcmLogEntry},
+    opaque::Block,
+    BlockNumber,
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use super::runtime_error;
+
+/// A logged XCM message, in a readable form.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XcmMessage {
+    /// The position of the message among all messages ever logged.
+    pub id: u64,
+    /// The hash identifying the message on both chains.
+    pub hash: H256,
+    /// Whether the message was `inbound` or `outbound`.
+    pub direction: String,
+    /// The origin of an inbound message, or the destination of an outbound one.
+    pub location: String,
+    /// The instructions of the message, empty if it was too large to be kept.
+    pub instructions: Vec<String>,
+    /// The outcome of executing an inbound message, or the result of sending an outbound one.
+    pub outcome: String,
+    /// The weight used to execute the message, zero for outbound messages.
+    pub weight_used: u64,
+    /// The block the message was sent or executed in.
+    pub block: BlockNumber,
+    /// The SCALE-encoded `Xcm` of the message, if it was not too large to be kept.
+    pub encoded: Option<Bytes>,
+}
+
+impl From<XcmLogEntry<BlockNumber>> for XcmMessage {
+    fn from(entry: XcmLogEntry<BlockNumber>) -> Self {
+        let (direction, location, outcome) = match entry.kind {
+            LoggedMessage::Inbound { origin, outcome } => {
+                ("inbound", format!("{:?}", origin), format!("{:?}", outcome))
+            }
+            LoggedMessage::Outbound { dest, result } => {
+                ("outbound", format!("{:?}", dest), format!("{:?}", result))
+            }
+        };
+        Self {
+            id: entry.id,
+            hash: entry.hash.into(),
+            direction: direction.into(),
+            location,
+            instructions: entry
+                .message
+                .iter()
+                .flat_map(|message| message.0.iter())
+                .map(|instruction| format!("{:?}", instruction))
+                .collect(),
+            outcome,
+            weight_used: entry.weight_used,
+            block: entry.block,
+            encoded: entry.message.map(|message| message.encode().into()),
+        }
+    }
+}
+
+/// XCM message log queries.
+#[rpc(client, server)]
+pub trait XcmLogApi<BlockHash> {
+    /// The last `count` XCM messages sent or executed by this chain, most recent first.
+    #[method(name = "xcm_recentMessages")]
+    fn recent_messages(&self, count: u32, at: Option<BlockHash>) -> RpcResult<Vec<XcmMessage>>;
+
+    /// The logged XCM messages with the hash `hash`. Querying the same hash on the sending and
+    /// the receiving chain traces a message end to end.
+    #[method(name = "xcm_traceMessage")]
+    fn trace_message(&self, hash: H256, at: Option<BlockHash>) -> RpcResult<Vec<XcmMessage>>;
+}
+
+/// Implements [`XcmLogApiServer`] on top of the runtime's `XcmLogApi`.
+pub struct XcmLog<C> {
+    client: Arc<C>,
+}
+
+impl<C> XcmLog<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> XcmLogApiServer<<Block as BlockT>::Hash> for XcmLog<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: XcmLogRuntimeApi<Block, BlockNumber>,
+{
+    fn recent_messages(
+        &self,
+        count: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<XcmMessage>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        let entries = api
+            .recent_xcm_messages(&at, count)
+            .map_err(|e| runtime_error("Unable to query the XCM log.", e))?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+
+    fn trace_message(
+        &self,
+        hash: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<XcmMessage>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        let entries = api
+            .xcm_messages_with_hash(&at, hash.into())
+            .map_err(|e| runtime_error("Unable to query the XCM log.", e))?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+}
@@ -0,0 +1,64 @@
+//! RPC methods for inspecting the XCM versions negotiated with other chains.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+};
+use nbchain_runtime::{
+    apis::{XcmVersionApi as XcmVersionRuntimeApi, XcmVersionInfo},
+    opaque::Block,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use xcm::VersionedMultiLocation;
+
+use super::{decode_param, runtime_error};
+
+/// XCM version negotiation queries.
+#[rpc(client, server)]
+pub trait XcmVersionApi<BlockHash> {
+    /// The XCM versions used with the chain at the SCALE-encoded `VersionedMultiLocation`
+    /// `dest`. Returns `None` if `dest` can't be converted to the latest version.
+    #[method(name = "xcm_negotiatedVersion")]
+    fn negotiated_version(
+        &self,
+        dest: Bytes,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<XcmVersionInfo>>;
+}
+
+/// Implements [`XcmVersionApiServer`] on top of the runtime's `XcmVersionApi`.
+pub struct XcmVersion<C> {
+    client: Arc<C>,
+}
+
+impl<C> XcmVersion<C> {
+    /// Create a new instance backed by `client`.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C> XcmVersionApiServer<<Block as BlockT>::Hash> for XcmVersion<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: XcmVersionRuntimeApi<Block>,
+{
+    fn negotiated_version(
+        &self,
+        dest: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<XcmVersionInfo>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let dest: VersionedMultiLocation = decode_param(&dest, "destination")?;
+
+        api.xcm_version_for(&at, dest)
+            .map_err(|e| runtime_error("Unable to query XCM version.", e))
+    }
+}
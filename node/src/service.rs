@@ -8,7 +8,9 @@ use jsonrpsee::RpcModule;
 
 use cumulus_client_cli::CollatorOptions;
 // Local Runtime Types
-use nbchain_runtime::{opaque::Block, AccountId, Balance, Hash, Index as Nonce, RuntimeApi};
+use nbchain_runtime::{
+    opaque::Block, AccountId, AssetId, Balance, BlockNumber, Event, Hash, Index as Nonce, RuntimeApi,
+};
 
 // Cumulus Imports
 use cumulus_client_consensus_aura::{AuraConsensus, BuildAuraConsensusParams, SlotProportion};
@@ -225,6 +227,13 @@ where
         + sp_block_builder::BlockBuilder<Block>
         + cumulus_primitives_core::CollectCollationInfo<Block>
         + pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
+        + nbchain_runtime::apis::AssetTxPaymentApi<Block, Balance, AssetId>
+        + nbchain_runtime::apis::LocationToAccountApi<Block, AccountId>
+        + nbchain_runtime::apis::XcmVersionApi<Block>
+        + nbchain_runtime::apis::XcmFeeApi<Block, Balance>
+        + nbchain_runtime::apis::DryRunApi<Block, Event, Balance>
+        + nbchain_runtime::apis::XcmLogApi<Block, BlockNumber>
+        + nbchain_runtime::apis::OverweightXcmApi<Block>
         + substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
     sc_client_api::StateBackendFor<TFullBackend<Block>, Block>: sp_api::StateBackend<BlakeTwo256>,
     Executor: sc_executor::NativeExecutionDispatch + 'static,
@@ -0,0 +1,363 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Registry of the assets received from other chains.
+//!
+//! Every registered asset has a local id in `Assets`, the location it is known by in XCM, its
+//! metadata and optionally the rate it is accepted at to pay for XCM execution and transactions.
+//! Registering an asset creates it in `Assets` as a sufficient asset, owned by the account of
+//! `PalletId`, with its existential deposit as minimum balance. Assets are only registered under
+//! the ids in `ForeignAssetIds`, and an asset that already exists under such an id is adopted as
+//! is, so the runtime must make sure no one but its force origin can create assets with them.
+//! The relay chain token can be registered at genesis.
+//!
+//! [`LocationToAssetId`] converts between locations and ids for the XCM asset transactor.
+
+use frame_support::{
+    pallet_prelude::*,
+    traits::{
+        tokens::fungibles::{Create, Inspect},
+        Contains,
+    },
+    PalletId,
+};
+use scale_info::TypeInfo;
+use sp_runtime::traits::{AccountIdConversion, Zero};
+use sp_std::{borrow::Borrow, boxed::Box, marker::PhantomData, vec::Vec};
+use xcm::{latest::prelude::*, VersionedMultiLocation};
+use xcm_executor::traits::Convert;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub type AssetIdOf<T> =
+    <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+pub type BalanceOf<T> =
+    <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// What wallets and users need to know to display a registered asset.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AssetMetadata<Balance, Name> {
+    /// The name of the asset.
+    pub name: Name,
+    /// The ticker symbol of the asset.
+    pub symbol: Name,
+    /// The number of decimals of the asset.
+    pub decimals: u8,
+    /// The minimum balance of an account holding the asset.
+    pub existential_deposit: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to register assets and change their metadata and rate.
+        type RegistryOrigin: EnsureOrigin<Self::Origin>;
+
+        /// The assets registered assets are held in.
+        type Assets: Create<Self::AccountId>;
+
+        /// The ids assets can be registered under.
+        type ForeignAssetIds: Contains<AssetIdOf<Self>>;
+
+        /// The id of the account owning the registered assets.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// The maximum length of the name and symbol of an asset.
+        #[pallet::constant]
+        type StringLimit: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The location of each registered asset.
+    #[pallet::storage]
+    #[pallet::getter(fn location)]
+    pub type AssetLocations<T: Config> = StorageMap<_, Twox64Concat, AssetIdOf<T>, MultiLocation>;
+
+    /// The id of each registered location.
+    #[pallet::storage]
+    #[pallet::getter(fn asset_id)]
+    pub type LocationAssets<T: Config> =
+        StorageMap<_, Blake2_128Concat, MultiLocation, AssetIdOf<T>>;
+
+    /// The metadata of each registered asset.
+    #[pallet::storage]
+    #[pallet::getter(fn metadata)]
+    pub type Metadata<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        AssetIdOf<T>,
+        AssetMetadata<BalanceOf<T>, BoundedVec<u8, T::StringLimit>>,
+    >;
+
+    /// Units of a registered asset charged per second of weight, for the assets accepted as
+    /// payment.
+    #[pallet::storage]
+    #[pallet::getter(fn units_per_second)]
+    pub type UnitsPerSecond<T: Config> = StorageMap<_, Twox64Concat, AssetIdOf<T>, u128>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config>
+    where
+        AssetIdOf<T>: MaybeSerializeDeserialize,
+        BalanceOf<T>: MaybeSerializeDeserialize,
+    {
+        /// The relay chain token, registered at the parent location: `(asset_id, name, symbol,
+        /// decimals, existential_deposit, units_per_second)`.
+        #[allow(clippy::type_complexity)]
+        pub relay_token: Option<(
+            AssetIdOf<T>,
+            Vec<u8>,
+            Vec<u8>,
+            u8,
+            BalanceOf<T>,
+            Option<u128>,
+        )>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T>
+    where
+        AssetIdOf<T>: MaybeSerializeDeserialize,
+        BalanceOf<T>: MaybeSerializeDeserialize,
+    {
+        fn default() -> Self {
+            Self { relay_token: None }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T>
+    where
+        AssetIdOf<T>: MaybeSerializeDeserialize,
+        BalanceOf<T>: MaybeSerializeDeserialize,
+    {
+        fn build(&self) {
+            if let Some((asset_id, name, symbol, decimals, existential_deposit, units_per_second)) =
+                self.relay_token.clone()
+            {
+                Pallet::<T>::do_register_asset(
+                    asset_id,
+                    MultiLocation::parent(),
+                    name,
+                    symbol,
+                    decimals,
+                    existential_deposit,
+                    units_per_second,
+                )
+                .expect("the relay token can be registered; qed");
+            }
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// An asset was registered. [asset_id, location]
+        AssetRegistered(AssetIdOf<T>, MultiLocation),
+        /// The metadata of an asset was changed. [asset_id]
+        MetadataUpdated(AssetIdOf<T>),
+        /// The rate of an asset was changed, `None` meaning it is no longer accepted.
+        /// [asset_id, units_per_second]
+        UnitsPerSecondSet(AssetIdOf<T>, Option<u128>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// A location could not be converted to the latest XCM version.
+        BadVersion,
+        /// The name or symbol is too long.
+        BadMetadata,
+        /// The existential deposit is zero.
+        ZeroExistentialDeposit,
+        /// The asset id is already registered.
+        AssetAlreadyRegistered,
+        /// The location is already registered.
+        LocationAlreadyRegistered,
+        /// The asset id is not registered.
+        AssetNotRegistered,
+        /// The asset id is not one assets can be registered under.
+        NotForeignAssetId,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Register the asset at `location` under `asset_id`, which must be in `ForeignAssetIds`,
+        /// creating it if needed, and accept it as payment for `units_per_second` per second of
+        /// weight unless that is `None`. An existing asset keeps its minimum balance as
+        /// existential deposit.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 5))]
+        #[allow(clippy::too_many_arguments)]
+        pub fn register_asset(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T>,
+            location: Box<VersionedMultiLocation>,
+            name: Vec<u8>,
+            symbol: Vec<u8>,
+            decimals: u8,
+            existential_deposit: BalanceOf<T>,
+            units_per_second: Option<u128>,
+        ) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            let location =
+                MultiLocation::try_from(*location).map_err(|()| Error::<T>::BadVersion)?;
+
+            Self::do_register_asset(
+                asset_id,
+                location,
+                name,
+                symbol,
+                decimals,
+                existential_deposit,
+                units_per_second,
+            )
+        }
+
+        /// Change the name, symbol and decimals of the registered asset `asset_id`. Its
+        /// existential deposit stays the minimum balance it was created with.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn update_metadata(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T>,
+            name: Vec<u8>,
+            symbol: Vec<u8>,
+            decimals: u8,
+        ) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            let existential_deposit = Metadata::<T>::get(asset_id)
+                .ok_or(Error::<T>::AssetNotRegistered)?
+                .existential_deposit;
+
+            let metadata = Self::bounded_metadata(name, symbol, decimals, existential_deposit)?;
+            Metadata::<T>::insert(asset_id, metadata);
+            Self::deposit_event(Event::MetadataUpdated(asset_id));
+            Ok(())
+        }
+
+        /// Accept the registered asset `asset_id` as payment for `units_per_second` per second
+        /// of weight, or stop accepting it if `units_per_second` is `None`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn set_units_per_second(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T>,
+            units_per_second: Option<u128>,
+        ) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            ensure!(
+                AssetLocations::<T>::contains_key(asset_id),
+                Error::<T>::AssetNotRegistered
+            );
+
+            UnitsPerSecond::<T>::set(asset_id, units_per_second);
+            Self::deposit_event(Event::UnitsPerSecondSet(asset_id, units_per_second));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Register the asset at `location` under `asset_id`, as `register_asset`.
+    #[allow(clippy::too_many_arguments)]
+    fn do_register_asset(
+        asset_id: AssetIdOf<T>,
+        location: MultiLocation,
+        name: Vec<u8>,
+        symbol: Vec<u8>,
+        decimals: u8,
+        existential_deposit: BalanceOf<T>,
+        units_per_second: Option<u128>,
+    ) -> DispatchResult {
+        ensure!(
+            T::ForeignAssetIds::contains(&asset_id),
+            Error::<T>::NotForeignAssetId
+        );
+        ensure!(
+            !existential_deposit.is_zero(),
+            Error::<T>::ZeroExistentialDeposit
+        );
+        ensure!(
+            !AssetLocations::<T>::contains_key(asset_id),
+            Error::<T>::AssetAlreadyRegistered
+        );
+        ensure!(
+            !LocationAssets::<T>::contains_key(&location),
+            Error::<T>::LocationAlreadyRegistered
+        );
+
+        // Existing assets always have a non-zero minimum balance, which they keep.
+        let minimum_balance = T::Assets::minimum_balance(asset_id);
+        let existential_deposit = if minimum_balance.is_zero() {
+            T::Assets::create(
+                asset_id,
+                T::PalletId::get().into_account_truncating(),
+                true,
+                existential_deposit,
+            )?;
+            existential_deposit
+        } else {
+            minimum_balance
+        };
+        let metadata = Self::bounded_metadata(name, symbol, decimals, existential_deposit)?;
+
+        AssetLocations::<T>::insert(asset_id, &location);
+        LocationAssets::<T>::insert(&location, asset_id);
+        Metadata::<T>::insert(asset_id, metadata);
+        Self::deposit_event(Event::AssetRegistered(asset_id, location));
+        if units_per_second.is_some() {
+            UnitsPerSecond::<T>::set(asset_id, units_per_second);
+            Self::deposit_event(Event::UnitsPerSecondSet(asset_id, units_per_second));
+        }
+        Ok(())
+    }
+
+    /// Units of the asset at `location` charged per second of weight, if it is registered and
+    /// accepted as payment.
+    pub fn units_per_second_at(location: &MultiLocation) -> Option<u128> {
+        LocationAssets::<T>::get(location).and_then(UnitsPerSecond::<T>::get)
+    }
+
+    fn bounded_metadata(
+        name: Vec<u8>,
+        symbol: Vec<u8>,
+        decimals: u8,
+        existential_deposit: BalanceOf<T>,
+    ) -> Result<AssetMetadata<BalanceOf<T>, BoundedVec<u8, T::StringLimit>>, Error<T>> {
+        Ok(AssetMetadata {
+            name: name.try_into().map_err(|_| Error::<T>::BadMetadata)?,
+            symbol: symbol.try_into().map_err(|_| Error::<T>::BadMetadata)?,
+            decimals,
+            existential_deposit,
+        })
+    }
+}
+
+/// Converts between the locations and ids of registered assets, for the XCM asset transactor.
+pub struct LocationToAssetId<T>(PhantomData<T>);
+
+impl<T: Config> Convert<MultiLocation, AssetIdOf<T>> for LocationToAssetId<T> {
+    fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<AssetIdOf<T>, ()> {
+        LocationAssets::<T>::get(location.borrow()).ok_or(())
+    }
+
+    fn reverse_ref(asset_id: impl Borrow<AssetIdOf<T>>) -> Result<MultiLocation, ()> {
+        AssetLocations::<T>::get(asset_id.borrow()).ok_or(())
+    }
+}
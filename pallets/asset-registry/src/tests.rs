@@ -0,0 +1,197 @@
+use crate::{mock::*, AssetMetadata, Error, LocationToAssetId};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::tokens::fungibles::{Inspect, Mutate},
+    PalletId,
+};
+use sp_runtime::{traits::AccountIdConversion, DispatchError};
+use xcm::{latest::prelude::*, VersionedMultiLocation};
+use xcm_executor::traits::Convert;
+
+fn sibling_token() -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(2000), GeneralIndex(0)))
+}
+
+fn versioned(location: MultiLocation) -> Box<VersionedMultiLocation> {
+    Box::new(location.into())
+}
+
+fn register(asset_id: u32, location: MultiLocation) -> frame_support::dispatch::DispatchResult {
+    AssetRegistry::register_asset(
+        Origin::root(),
+        asset_id,
+        versioned(location),
+        b"Token".to_vec(),
+        b"TKN".to_vec(),
+        12,
+        10,
+        Some(1_000),
+    )
+}
+
+#[test]
+fn registering_creates_a_sufficient_asset_owned_by_the_pallet() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(7, sibling_token()));
+
+        assert_eq!(Assets::minimum_balance(7), 10);
+        let owner: u64 = PalletId(*b"nb/asreg").into_account_truncating();
+        assert_eq!(pallet_assets::Asset::<Test>::get(7).unwrap().owner, owner);
+        assert!(pallet_assets::Asset::<Test>::get(7).unwrap().is_sufficient);
+        // Sufficient assets can be received by accounts without a native balance.
+        assert_ok!(Assets::mint_into(7, &1, 10));
+
+        assert_eq!(
+            AssetRegistry::metadata(7),
+            Some(AssetMetadata {
+                name: b"Token".to_vec().try_into().unwrap(),
+                symbol: b"TKN".to_vec().try_into().unwrap(),
+                decimals: 12,
+                existential_deposit: 10,
+            })
+        );
+        assert_eq!(AssetRegistry::units_per_second(7), Some(1_000));
+        assert_eq!(
+            AssetRegistry::units_per_second_at(&sibling_token()),
+            Some(1_000)
+        );
+    });
+}
+
+#[test]
+fn locations_and_ids_convert_both_ways() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            LocationToAssetId::<Test>::convert(sibling_token()),
+            Err(sibling_token())
+        );
+
+        assert_ok!(register(7, sibling_token()));
+        assert_eq!(LocationToAssetId::<Test>::convert(sibling_token()), Ok(7));
+        assert_eq!(LocationToAssetId::<Test>::reverse(7), Ok(sibling_token()));
+        assert_eq!(LocationToAssetId::<Test>::reverse(8), Err(8));
+    });
+}
+
+#[test]
+fn existing_assets_are_adopted() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Assets::force_create(Origin::root(), 7, 1, true, 5));
+
+        assert_ok!(register(7, sibling_token()));
+        assert_eq!(pallet_assets::Asset::<Test>::get(7).unwrap().owner, 1);
+        assert_eq!(Assets::minimum_balance(7), 5);
+        assert_eq!(AssetRegistry::metadata(7).unwrap().existential_deposit, 5);
+    });
+}
+
+#[test]
+fn only_foreign_asset_ids_are_registered() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Assets::force_create(Origin::root(), 100, 1, true, 5));
+
+        assert_noop!(
+            register(100, sibling_token()),
+            Error::<Test>::NotForeignAssetId
+        );
+        assert_noop!(
+            register(101, sibling_token()),
+            Error::<Test>::NotForeignAssetId
+        );
+    });
+}
+
+#[test]
+fn relay_token_is_registered_at_genesis() {
+    new_test_ext_with(crate::GenesisConfig {
+        relay_token: Some((1, b"Relay".to_vec(), b"DOT".to_vec(), 10, 5, Some(2_000))),
+    })
+    .execute_with(|| {
+        assert_eq!(
+            LocationToAssetId::<Test>::convert(MultiLocation::parent()),
+            Ok(1)
+        );
+        assert_eq!(Assets::minimum_balance(1), 5);
+        assert_eq!(AssetRegistry::metadata(1).unwrap().decimals, 10);
+        assert_eq!(
+            AssetRegistry::units_per_second_at(&MultiLocation::parent()),
+            Some(2_000)
+        );
+    });
+}
+
+#[test]
+fn ids_and_locations_are_registered_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(7, sibling_token()));
+
+        assert_noop!(
+            register(7, MultiLocation::parent()),
+            Error::<Test>::AssetAlreadyRegistered
+        );
+        assert_noop!(
+            register(8, sibling_token()),
+            Error::<Test>::LocationAlreadyRegistered
+        );
+    });
+}
+
+#[test]
+fn registry_is_managed_by_registry_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AssetRegistry::register_asset(
+                Origin::signed(1),
+                7,
+                versioned(sibling_token()),
+                b"Token".to_vec(),
+                b"TKN".to_vec(),
+                12,
+                10,
+                None,
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(register(7, sibling_token()));
+
+        assert_noop!(
+            AssetRegistry::set_units_per_second(Origin::signed(1), 7, None),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(AssetRegistry::set_units_per_second(Origin::root(), 7, None));
+        assert_eq!(AssetRegistry::units_per_second_at(&sibling_token()), None);
+        assert_noop!(
+            AssetRegistry::set_units_per_second(Origin::root(), 8, Some(1)),
+            Error::<Test>::AssetNotRegistered
+        );
+    });
+}
+
+#[test]
+fn metadata_can_be_updated() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(7, sibling_token()));
+
+        assert_noop!(
+            AssetRegistry::update_metadata(
+                Origin::root(),
+                7,
+                b"Much too long".to_vec(),
+                b"TKN".to_vec(),
+                12
+            ),
+            Error::<Test>::BadMetadata
+        );
+        assert_ok!(AssetRegistry::update_metadata(
+            Origin::root(),
+            7,
+            b"Wrapped".to_vec(),
+            b"WTKN".to_vec(),
+            10
+        ));
+        let metadata = AssetRegistry::metadata(7).unwrap();
+        assert_eq!(metadata.symbol.into_inner(), b"WTKN".to_vec());
+        assert_eq!(metadata.decimals, 10);
+        assert_eq!(metadata.existential_deposit, 10);
+    });
+}
@@ -0,0 +1,291 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! HRMP channel management.
+//!
+//! Channels between parachains are opened and closed on the relay chain. The dispatchables of
+//! this pallet send the relay chain a `Transact` of the matching `Hrmp` call, paid for with
+//! `RelayFee` withdrawn from our sovereign account there, and track the channel until the relay
+//! chain state, as reported by `Config::ChannelStatus`, shows it opened or closed. At most
+//! `MaxChannelChecks` tracked channels are checked at the end of each block, going around them
+//! over the following blocks.
+
+use codec::{Decode, Encode};
+use cumulus_primitives_core::ParaId;
+use frame_support::{pallet_prelude::*, weights::Weight, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_std::{vec, vec::Vec};
+use xcm::latest::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// An HRMP channel, encoded as the relay chain's `HrmpChannelId`.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ChannelId {
+    pub sender: ParaId,
+    pub recipient: ParaId,
+}
+
+/// The state of a channel tracked by this pallet.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum ChannelState {
+    /// The channel was requested or accepted, but isn't open yet.
+    Pending,
+    /// The channel is open.
+    Open,
+    /// Closing the channel was requested.
+    Closing,
+}
+
+/// Whether channels are open on the relay chain.
+pub trait HrmpChannelStatus {
+    /// Whether `channel` is open, or `None` if the relay chain state isn't known.
+    fn is_open(channel: &ChannelId) -> Option<bool>;
+}
+
+/// Indices of the calls of the relay chain `Hrmp` pallet.
+const HRMP_INIT_OPEN_CHANNEL: u8 = 0;
+const HRMP_ACCEPT_OPEN_CHANNEL: u8 = 1;
+const HRMP_CLOSE_CHANNEL: u8 = 2;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to open and close channels.
+        type ChannelOrigin: EnsureOrigin<Self::Origin>;
+
+        /// The id of this parachain.
+        type SelfParaId: Get<ParaId>;
+
+        /// Sends the `Hrmp` calls to the relay chain.
+        type XcmSender: SendXcm;
+
+        /// Reports the channels open on the relay chain.
+        type ChannelStatus: HrmpChannelStatus;
+
+        /// The index of the `Hrmp` pallet in the relay chain runtime.
+        #[pallet::constant]
+        type RelayHrmpPalletIndex: Get<u8>;
+
+        /// The amount of the relay chain token withdrawn from our sovereign account to pay for
+        /// each call. What isn't used is deposited back.
+        #[pallet::constant]
+        type RelayFee: Get<u128>;
+
+        /// The weight bought on the relay chain for each call.
+        #[pallet::constant]
+        type RelayCallWeight: Get<Weight>;
+
+        /// The maximum number of tracked channels checked against the relay chain state per
+        /// block.
+        #[pallet::constant]
+        type MaxChannelChecks: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The channels being opened, open or being closed.
+    #[pallet::storage]
+    #[pallet::getter(fn channels)]
+    pub type Channels<T> = CountedStorageMap<_, Twox64Concat, ChannelId, ChannelState>;
+
+    /// The raw key of the last channel checked, `None` to start over from the first one.
+    #[pallet::storage]
+    pub type CheckCursor<T> = StorageValue<_, Vec<u8>>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Opening a channel to a sibling was requested.
+        /// [recipient, max_capacity, max_message_size]
+        OpenChannelRequested(ParaId, u32, u32),
+        /// A channel from a sibling was accepted. [sender]
+        OpenChannelAccepted(ParaId),
+        /// Closing a channel was requested. [channel]
+        CloseChannelRequested(ChannelId),
+        /// A channel went live. [channel]
+        ChannelOpened(ChannelId),
+        /// A channel was closed. [channel]
+        ChannelClosed(ChannelId),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The channel is already open.
+        ChannelOpen,
+        /// Neither end of the channel is this parachain.
+        NotOurChannel,
+        /// The relay chain state doesn't show the channel open.
+        ChannelNotOpen,
+        /// The message to the relay chain could not be sent.
+        SendFailure,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            // Up to `MaxChannelChecks` channels are read in `on_finalize` with the relay chain
+            // state, and may be updated.
+            let checks = Channels::<T>::count().min(T::MaxChannelChecks::get()) as Weight;
+            T::DbWeight::get().reads_writes(2 + 2 * checks, 1 + checks)
+        }
+
+        fn on_finalize(_n: T::BlockNumber) {
+            Self::update_channels();
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Request a channel from this parachain to `recipient`. It goes live once `recipient`
+        /// accepts it and the relay chain opens it at the next session.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn request_open_channel(
+            origin: OriginFor<T>,
+            recipient: ParaId,
+            max_capacity: u32,
+            max_message_size: u32,
+        ) -> DispatchResult {
+            T::ChannelOrigin::ensure_origin(origin)?;
+            let channel = ChannelId {
+                sender: T::SelfParaId::get(),
+                recipient,
+            };
+            Self::ensure_not_open(&channel)?;
+
+            Self::send_to_relay(
+                HRMP_INIT_OPEN_CHANNEL,
+                (recipient, max_capacity, max_message_size),
+            )?;
+            Channels::<T>::insert(&channel, ChannelState::Pending);
+            Self::deposit_event(Event::OpenChannelRequested(
+                recipient,
+                max_capacity,
+                max_message_size,
+            ));
+            Ok(())
+        }
+
+        /// Accept the channel `sender` requested to this parachain.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn accept_open_channel(origin: OriginFor<T>, sender: ParaId) -> DispatchResult {
+            T::ChannelOrigin::ensure_origin(origin)?;
+            let channel = ChannelId {
+                sender,
+                recipient: T::SelfParaId::get(),
+            };
+            Self::ensure_not_open(&channel)?;
+
+            Self::send_to_relay(HRMP_ACCEPT_OPEN_CHANNEL, sender)?;
+            Channels::<T>::insert(&channel, ChannelState::Pending);
+            Self::deposit_event(Event::OpenChannelAccepted(sender));
+            Ok(())
+        }
+
+        /// Close `channel`, in either direction. It is closed by the relay chain at the next
+        /// session.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn close_channel(origin: OriginFor<T>, channel: ChannelId) -> DispatchResult {
+            T::ChannelOrigin::ensure_origin(origin)?;
+            let self_id = T::SelfParaId::get();
+            ensure!(
+                channel.sender == self_id || channel.recipient == self_id,
+                Error::<T>::NotOurChannel
+            );
+            ensure!(
+                T::ChannelStatus::is_open(&channel) == Some(true),
+                Error::<T>::ChannelNotOpen
+            );
+
+            Self::send_to_relay(HRMP_CLOSE_CHANNEL, channel)?;
+            Channels::<T>::insert(&channel, ChannelState::Closing);
+            Self::deposit_event(Event::CloseChannelRequested(channel));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    fn ensure_not_open(channel: &ChannelId) -> DispatchResult {
+        ensure!(
+            Channels::<T>::get(channel) != Some(ChannelState::Open),
+            Error::<T>::ChannelOpen
+        );
+        Ok(())
+    }
+
+    /// Send the relay chain a `Transact` of the `Hrmp` call `call_index` with `args`, paid from
+    /// our sovereign account.
+    fn send_to_relay(call_index: u8, args: impl Encode) -> DispatchResult {
+        let call = (T::RelayHrmpPalletIndex::get(), call_index, args).encode();
+        T::XcmSender::send_xcm(MultiLocation::parent(), Self::relay_message(call))
+            .map_err(|_| Error::<T>::SendFailure)?;
+        Ok(())
+    }
+
+    /// The program dispatching the relay chain `call` with our parachain origin.
+    pub fn relay_message(call: Vec<u8>) -> Xcm<()> {
+        let fees: MultiAsset = (MultiLocation::here(), T::RelayFee::get()).into();
+        let sovereign_account: MultiLocation = Parachain(T::SelfParaId::get().into()).into();
+        Xcm(vec![
+            WithdrawAsset(fees.clone().into()),
+            BuyExecution {
+                fees,
+                weight_limit: Unlimited,
+            },
+            Transact {
+                origin_type: OriginKind::Native,
+                require_weight_at_most: T::RelayCallWeight::get(),
+                call: call.into(),
+            },
+            RefundSurplus,
+            DepositAsset {
+                assets: Wild(All),
+                max_assets: 1,
+                beneficiary: sovereign_account,
+            },
+        ])
+    }
+
+    /// Follow up to `MaxChannelChecks` tracked channels, from where the last block stopped, to
+    /// the state of the relay chain.
+    fn update_channels() {
+        let max_checks = T::MaxChannelChecks::get() as usize;
+        let channels: Vec<_> = match CheckCursor::<T>::take() {
+            Some(last) => Channels::<T>::iter_from(last).take(max_checks).collect(),
+            None => Channels::<T>::iter().take(max_checks).collect(),
+        };
+        if channels.len() == max_checks {
+            if let Some((last, _)) = channels.last() {
+                CheckCursor::<T>::put(Channels::<T>::hashed_key_for(last));
+            }
+        }
+        for (channel, state) in channels {
+            match (state, T::ChannelStatus::is_open(&channel)) {
+                (ChannelState::Pending, Some(true)) => {
+                    Channels::<T>::insert(&channel, ChannelState::Open);
+                    Self::deposit_event(Event::ChannelOpened(channel));
+                }
+                (ChannelState::Open | ChannelState::Closing, Some(false)) => {
+                    Channels::<T>::remove(&channel);
+                    Self::deposit_event(Event::ChannelClosed(channel));
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{self as pallet_hrmp_channels, ChannelId, HrmpChannelStatus};
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, Everything},
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::cell::RefCell;
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        HrmpChannels: pallet_hrmp_channels::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+/// The id of the mock parachain.
+pub const SELF_PARA_ID: u32 = 1000;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub SelfParaId: ParaId = ParaId::new(SELF_PARA_ID);
+    pub const RelayHrmpPalletIndex: u8 = 60;
+    pub const RelayFee: u128 = 1_000;
+    pub const RelayCallWeight: Weight = 1_000_000;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+thread_local! {
+    pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+    pub static OPEN_CHANNELS: RefCell<Vec<ChannelId>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent so far, with their destination.
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+    SENT.with(|s| s.borrow().clone())
+}
+
+/// Records the messages instead of sending them.
+pub struct RecordingSender;
+impl SendXcm for RecordingSender {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        SENT.with(|s| s.borrow_mut().push((dest.into(), message)));
+        Ok(())
+    }
+}
+
+/// Open or close `channel` on the mock relay chain.
+pub fn set_open(channel: ChannelId, open: bool) {
+    OPEN_CHANNELS.with(|c| {
+        let mut channels = c.borrow_mut();
+        channels.retain(|other| *other != channel);
+        if open {
+            channels.push(channel);
+        }
+    });
+}
+
+pub struct MockChannelStatus;
+impl HrmpChannelStatus for MockChannelStatus {
+    fn is_open(channel: &ChannelId) -> Option<bool> {
+        Some(OPEN_CHANNELS.with(|c| c.borrow().contains(channel)))
+    }
+}
+
+impl pallet_hrmp_channels::Config for Test {
+    type Event = Event;
+    type ChannelOrigin = frame_system::EnsureRoot<u64>;
+    type SelfParaId = SelfParaId;
+    type XcmSender = RecordingSender;
+    type ChannelStatus = MockChannelStatus;
+    type RelayHrmpPalletIndex = RelayHrmpPalletIndex;
+    type RelayFee = RelayFee;
+    type RelayCallWeight = RelayCallWeight;
+    type MaxChannelChecks = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
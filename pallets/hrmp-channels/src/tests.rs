@@ -0,0 +1,172 @@
+use crate::{mock::*, ChannelId, ChannelState, Error, Event as HrmpEvent};
+use codec::Encode;
+use cumulus_primitives_core::ParaId;
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use xcm::latest::prelude::*;
+
+fn para(id: u32) -> ParaId {
+    ParaId::new(id)
+}
+
+fn outbound(recipient: u32) -> ChannelId {
+    ChannelId {
+        sender: para(SELF_PARA_ID),
+        recipient: para(recipient),
+    }
+}
+
+fn inbound(sender: u32) -> ChannelId {
+    ChannelId {
+        sender: para(sender),
+        recipient: para(SELF_PARA_ID),
+    }
+}
+
+/// The call sent in the last message to the relay chain.
+fn last_relay_call() -> Vec<u8> {
+    let (dest, message) = sent().pop().unwrap();
+    assert_eq!(dest, MultiLocation::parent());
+    match &message.0[2] {
+        Transact { call, .. } => call.clone().into_encoded(),
+        other => panic!("unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn request_open_channel_sends_paid_transact_to_the_relay_chain() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(HrmpChannels::request_open_channel(
+            Origin::root(),
+            para(2000),
+            8,
+            1024
+        ));
+
+        let fees: MultiAsset = (MultiLocation::here(), 1_000).into();
+        let call = (60u8, 0u8, para(2000), 8u32, 1024u32).encode();
+        assert_eq!(
+            sent(),
+            vec![(
+                MultiLocation::parent(),
+                Xcm(vec![
+                    WithdrawAsset(fees.clone().into()),
+                    BuyExecution {
+                        fees,
+                        weight_limit: Unlimited,
+                    },
+                    Transact {
+                        origin_type: OriginKind::Native,
+                        require_weight_at_most: 1_000_000,
+                        call: call.into(),
+                    },
+                    RefundSurplus,
+                    DepositAsset {
+                        assets: Wild(All),
+                        max_assets: 1,
+                        beneficiary: Parachain(SELF_PARA_ID).into(),
+                    },
+                ])
+            )]
+        );
+        assert_eq!(
+            HrmpChannels::channels(outbound(2000)),
+            Some(ChannelState::Pending)
+        );
+        System::assert_last_event(HrmpEvent::OpenChannelRequested(para(2000), 8, 1024).into());
+    });
+}
+
+#[test]
+fn channels_follow_the_relay_chain_state() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(HrmpChannels::accept_open_channel(
+            Origin::root(),
+            para(2000)
+        ));
+        assert_eq!(last_relay_call(), (60u8, 1u8, para(2000)).encode());
+
+        HrmpChannels::on_finalize(1);
+        assert_eq!(
+            HrmpChannels::channels(inbound(2000)),
+            Some(ChannelState::Pending)
+        );
+
+        set_open(inbound(2000), true);
+        HrmpChannels::on_finalize(1);
+        assert_eq!(
+            HrmpChannels::channels(inbound(2000)),
+            Some(ChannelState::Open)
+        );
+        System::assert_last_event(HrmpEvent::ChannelOpened(inbound(2000)).into());
+        assert_noop!(
+            HrmpChannels::accept_open_channel(Origin::root(), para(2000)),
+            Error::<Test>::ChannelOpen
+        );
+
+        assert_ok!(HrmpChannels::close_channel(Origin::root(), inbound(2000)));
+        assert_eq!(last_relay_call(), (60u8, 2u8, inbound(2000)).encode());
+        assert_eq!(
+            HrmpChannels::channels(inbound(2000)),
+            Some(ChannelState::Closing)
+        );
+
+        set_open(inbound(2000), false);
+        HrmpChannels::on_finalize(1);
+        assert_eq!(HrmpChannels::channels(inbound(2000)), None);
+        System::assert_last_event(HrmpEvent::ChannelClosed(inbound(2000)).into());
+    });
+}
+
+#[test]
+fn channels_are_managed_only_by_the_channel_origin_and_for_this_chain() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            HrmpChannels::request_open_channel(Origin::signed(1), para(2000), 8, 1024),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            HrmpChannels::close_channel(
+                Origin::root(),
+                ChannelId {
+                    sender: para(2000),
+                    recipient: para(3000),
+                }
+            ),
+            Error::<Test>::NotOurChannel
+        );
+        assert_noop!(
+            HrmpChannels::close_channel(Origin::root(), outbound(2000)),
+            Error::<Test>::ChannelNotOpen
+        );
+        assert!(sent().is_empty());
+    });
+}
+
+#[test]
+fn a_bounded_number_of_channels_is_checked_per_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        for sender in [2000, 3000, 4000] {
+            assert_ok!(HrmpChannels::accept_open_channel(
+                Origin::root(),
+                para(sender)
+            ));
+            set_open(inbound(sender), true);
+        }
+        let open = || {
+            [2000, 3000, 4000]
+                .into_iter()
+                .filter(|sender| {
+                    HrmpChannels::channels(inbound(*sender)) == Some(ChannelState::Open)
+                })
+                .count()
+        };
+
+        HrmpChannels::on_finalize(1);
+        assert_eq!(open(), 2);
+        HrmpChannels::on_finalize(2);
+        assert_eq!(open(), 3);
+    });
+}
@@ -0,0 +1,332 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Accounts on this chain controlled by users of other chains.
+//!
+//! A user of a sibling chain reaches this chain with its chain's origin descended into its own
+//! account, e.g. `(1, X2(Parachain(2000), AccountId32 { .. }))`, which `LocationToAccountId`
+//! converts into a local account. To use that account the user opens it with `open_account`,
+//! sent in a `Transact` with `OriginKind::Xcm`, choosing which calls it may dispatch. The user
+//! can then dispatch calls from it with `Transact` and `OriginKind::SovereignAccount`:
+//!
+//! - [`AllowDescendedPaidExecutionFrom`] lets such messages through the barrier when they pay
+//!   for their execution with assets withdrawn from the account, so the user never needs keys on
+//!   this chain.
+//! - [`RestrictInterchainAccounts`] wraps the conversion of their origin into a signed origin,
+//!   which is refused until the account is opened and only lets through the calls allowed by
+//!   both `AllowedCalls` and the filter of the account.
+
+use codec::{Decode, Encode};
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Contains, OriginTrait},
+    weights::Weight,
+};
+use scale_info::TypeInfo;
+use sp_std::{marker::PhantomData, vec::Vec};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::{Convert, ConvertOrigin, ShouldExecute};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Calls matched by a call filter: the calls of a pallet, or a single one of them, by index.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct CallPattern {
+    /// The index of the pallet in the runtime.
+    pub pallet_index: u8,
+    /// The index of the call in the pallet, `None` matching all of its calls.
+    pub call_index: Option<u8>,
+}
+
+impl CallPattern {
+    /// Whether the SCALE-encoded call `call` is matched.
+    fn matches(&self, call: &[u8]) -> bool {
+        match call {
+            [pallet_index, call_index, ..] => {
+                *pallet_index == self.pallet_index
+                    && self.call_index.map_or(true, |index| index == *call_index)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The calls an interchain account may dispatch, on top of the ones allowed for all of them.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum CallFilter<Patterns> {
+    /// Every call.
+    All,
+    /// The calls matched by one of the patterns.
+    Only(Patterns),
+}
+
+/// An interchain account.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct InterchainAccount<Patterns> {
+    /// The location of the user controlling the account.
+    pub controller: MultiLocation,
+    /// The calls the account may dispatch.
+    pub filter: CallFilter<Patterns>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// The runtime call, encoded to be matched by call filters.
+        type Call: Encode + IsType<<Self as frame_system::Config>::Call>;
+
+        /// Origin of the users controlling interchain accounts, giving their location.
+        type ControllerOrigin: EnsureOrigin<
+            <Self as frame_system::Config>::Origin,
+            Success = MultiLocation,
+        >;
+
+        /// Origin allowed to close any interchain account.
+        type ForceOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+
+        /// The locations whose signed origins are restricted to the interchain accounts they
+        /// opened. It must cover every location `ControllerOrigin` lets open an account, and
+        /// may cover more, such as everything within siblings, to refuse the ones that can't.
+        type IsController: Contains<MultiLocation>;
+
+        /// Converts the location of a user into its interchain account.
+        type LocationToAccountId: Convert<MultiLocation, Self::AccountId>;
+
+        /// The calls any interchain account may dispatch.
+        type AllowedCalls: Contains<<Self as Config>::Call>;
+
+        /// The maximum number of patterns in a call filter.
+        #[pallet::constant]
+        type MaxCallPatterns: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The opened interchain accounts.
+    #[pallet::storage]
+    #[pallet::getter(fn account)]
+    pub type Accounts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        InterchainAccount<BoundedVec<CallPattern, T::MaxCallPatterns>>,
+    >;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// An interchain account was opened. [account, controller]
+        AccountOpened(T::AccountId, MultiLocation),
+        /// The call filter of an interchain account was changed. [account]
+        CallFilterSet(T::AccountId),
+        /// An interchain account was closed. [account]
+        AccountClosed(T::AccountId),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The location has no local account.
+        UnsupportedLocation,
+        /// The account is already opened.
+        AlreadyOpened,
+        /// The account is not opened.
+        NotOpened,
+        /// The call filter has more than `MaxCallPatterns` patterns.
+        TooManyPatterns,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Open the interchain account of the user sending the call, letting it dispatch the
+        /// calls allowed by `filter`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn open_account(
+            origin: OriginFor<T>,
+            filter: CallFilter<Vec<CallPattern>>,
+        ) -> DispatchResult {
+            let controller = T::ControllerOrigin::ensure_origin(origin)?;
+            let account = T::LocationToAccountId::convert_ref(&controller)
+                .map_err(|()| Error::<T>::UnsupportedLocation)?;
+            ensure!(
+                !Accounts::<T>::contains_key(&account),
+                Error::<T>::AlreadyOpened
+            );
+
+            let filter = Self::bounded_filter(filter)?;
+            Accounts::<T>::insert(
+                &account,
+                InterchainAccount {
+                    controller: controller.clone(),
+                    filter,
+                },
+            );
+            Self::deposit_event(Event::AccountOpened(account, controller));
+            Ok(())
+        }
+
+        /// Let the interchain account of the user sending the call dispatch the calls allowed by
+        /// `filter`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn set_call_filter(
+            origin: OriginFor<T>,
+            filter: CallFilter<Vec<CallPattern>>,
+        ) -> DispatchResult {
+            let controller = T::ControllerOrigin::ensure_origin(origin)?;
+            let account = T::LocationToAccountId::convert_ref(&controller)
+                .map_err(|()| Error::<T>::UnsupportedLocation)?;
+
+            let filter = Self::bounded_filter(filter)?;
+            Accounts::<T>::try_mutate(&account, |opened| {
+                let opened = opened.as_mut().ok_or(Error::<T>::NotOpened)?;
+                opened.filter = filter;
+                Ok::<_, Error<T>>(())
+            })?;
+            Self::deposit_event(Event::CallFilterSet(account));
+            Ok(())
+        }
+
+        /// Close the interchain account of the user sending the call. Its funds stay in it, to
+        /// be used again once it is reopened.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn close_account(origin: OriginFor<T>) -> DispatchResult {
+            let controller = T::ControllerOrigin::ensure_origin(origin)?;
+            let account = T::LocationToAccountId::convert_ref(&controller)
+                .map_err(|()| Error::<T>::UnsupportedLocation)?;
+
+            Accounts::<T>::take(&account).ok_or(Error::<T>::NotOpened)?;
+            Self::deposit_event(Event::AccountClosed(account));
+            Ok(())
+        }
+
+        /// Close the interchain account `account`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn force_close_account(origin: OriginFor<T>, account: T::AccountId) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            Accounts::<T>::take(&account).ok_or(Error::<T>::NotOpened)?;
+            Self::deposit_event(Event::AccountClosed(account));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Whether the interchain account `account` may dispatch `call`.
+    pub fn is_allowed(account: &T::AccountId, call: &<T as Config>::Call) -> bool {
+        if !T::AllowedCalls::contains(call) {
+            return false;
+        }
+        match Accounts::<T>::get(account).map(|opened| opened.filter) {
+            Some(CallFilter::All) => true,
+            Some(CallFilter::Only(patterns)) => {
+                let call = call.encode();
+                patterns.iter().any(|pattern| pattern.matches(&call))
+            }
+            None => false,
+        }
+    }
+
+    fn bounded_filter(
+        filter: CallFilter<Vec<CallPattern>>,
+    ) -> Result<CallFilter<BoundedVec<CallPattern, T::MaxCallPatterns>>, Error<T>> {
+        Ok(match filter {
+            CallFilter::All => CallFilter::All,
+            CallFilter::Only(patterns) => CallFilter::Only(
+                patterns
+                    .try_into()
+                    .map_err(|_| Error::<T>::TooManyPatterns)?,
+            ),
+        })
+    }
+}
+
+/// Converts origins with `Inner`, refusing to convert the `SovereignAccount` origins of users
+/// whose interchain account is not opened, and restricting the others to the calls their account
+/// may dispatch.
+pub struct RestrictInterchainAccounts<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T, Inner> ConvertOrigin<<T as frame_system::Config>::Origin>
+    for RestrictInterchainAccounts<T, Inner>
+where
+    T: Config,
+    Inner: ConvertOrigin<<T as frame_system::Config>::Origin>,
+{
+    fn convert_origin(
+        origin: impl Into<MultiLocation>,
+        kind: OriginKind,
+    ) -> Result<<T as frame_system::Config>::Origin, MultiLocation> {
+        let origin = origin.into();
+        if kind != OriginKind::SovereignAccount || !T::IsController::contains(&origin) {
+            return Inner::convert_origin(origin, kind);
+        }
+
+        let account = match T::LocationToAccountId::convert_ref(&origin) {
+            Ok(account) if Accounts::<T>::contains_key(&account) => account,
+            _ => return Err(origin),
+        };
+        let mut converted = Inner::convert_origin(origin, kind)?;
+        converted.add_filter(move |call| {
+            Pallet::<T>::is_allowed(&account, <<T as Config>::Call as IsType<_>>::from_ref(call))
+        });
+        Ok(converted)
+    }
+}
+
+/// Allows messages from `T` descending into one of their users, which pay for their execution
+/// with assets withdrawn from the account of the user.
+///
+/// The message must start with `DescendOrigin`, `WithdrawAsset`, any number of `ClearOrigin`
+/// and `BuyExecution` for at least the weight of the message.
+pub struct AllowDescendedPaidExecutionFrom<T>(PhantomData<T>);
+
+impl<T: Contains<MultiLocation>> ShouldExecute for AllowDescendedPaidExecutionFrom<T> {
+    fn should_execute<Call>(
+        origin: &MultiLocation,
+        message: &mut Xcm<Call>,
+        max_weight: Weight,
+        _weight_credit: &mut Weight,
+    ) -> Result<(), ()> {
+        ensure!(T::contains(origin), ());
+        let mut instructions = message.0.iter_mut();
+        ensure!(matches!(instructions.next(), Some(DescendOrigin(_))), ());
+        ensure!(matches!(instructions.next(), Some(WithdrawAsset(_))), ());
+        let mut instruction = instructions.next().ok_or(())?;
+        while let ClearOrigin = instruction {
+            instruction = instructions.next().ok_or(())?;
+        }
+        match instruction {
+            BuyExecution {
+                weight_limit: Limited(ref mut weight),
+                ..
+            } if *weight >= max_weight => {
+                *weight = max_weight;
+                Ok(())
+            }
+            BuyExecution {
+                ref mut weight_limit,
+                ..
+            } if weight_limit == &Unlimited => {
+                *weight_limit = Limited(max_weight);
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate as pallet_interchain_accounts;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, Contains, EnsureOrigin, Everything},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::borrow::Borrow;
+use xcm::latest::prelude::*;
+use xcm_executor::traits::{Convert, ConvertOrigin};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>} = 0,
+        InterchainAccounts: pallet_interchain_accounts::{Pallet, Call, Storage, Event<T>} = 1,
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+/// The location of the user `index` of the sibling 2000.
+pub fn user(index: u64) -> MultiLocation {
+    MultiLocation::new(
+        1,
+        X2(
+            Parachain(2000),
+            AccountIndex64 {
+                network: Any,
+                index,
+            },
+        ),
+    )
+}
+
+/// The interchain account of the user `index`.
+pub fn account_of(index: u64) -> u64 {
+    1_000 + index
+}
+
+/// The users of the sibling 2000.
+pub struct SiblingUsers;
+impl Contains<MultiLocation> for SiblingUsers {
+    fn contains(location: &MultiLocation) -> bool {
+        matches!(
+            location,
+            MultiLocation {
+                parents: 1,
+                interior: X2(Parachain(2000), AccountIndex64 { .. }),
+            }
+        )
+    }
+}
+
+pub struct UserToAccount;
+impl Convert<MultiLocation, u64> for UserToAccount {
+    fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<u64, ()> {
+        match location.borrow() {
+            MultiLocation {
+                parents: 1,
+                interior: X2(Parachain(2000), AccountIndex64 { index, .. }),
+            } => Ok(account_of(*index)),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Stands for the users sending calls with `OriginKind::Xcm`: the signed origin `index` is the
+/// user `index`.
+pub struct EnsureUser;
+impl EnsureOrigin<Origin> for EnsureUser {
+    type Success = MultiLocation;
+
+    fn try_origin(origin: Origin) -> Result<MultiLocation, Origin> {
+        frame_system::EnsureSigned::<u64>::try_origin(origin).map(user)
+    }
+}
+
+/// Converts the `SovereignAccount` origins of users into their signed interchain account.
+pub struct SignedUser;
+impl ConvertOrigin<Origin> for SignedUser {
+    fn convert_origin(
+        origin: impl Into<MultiLocation>,
+        kind: OriginKind,
+    ) -> Result<Origin, MultiLocation> {
+        let origin = origin.into();
+        match (kind, UserToAccount::convert_ref(&origin)) {
+            (OriginKind::SovereignAccount, Ok(account)) => Ok(Origin::signed(account)),
+            _ => Err(origin),
+        }
+    }
+}
+
+/// Interchain accounts may dispatch anything but `System::remark_with_event`.
+pub struct NoRemarkWithEvent;
+impl Contains<Call> for NoRemarkWithEvent {
+    fn contains(call: &Call) -> bool {
+        !matches!(
+            call,
+            Call::System(frame_system::Call::remark_with_event { .. })
+        )
+    }
+}
+
+impl pallet_interchain_accounts::Config for Test {
+    type Event = Event;
+    type Call = Call;
+    type ControllerOrigin = EnsureUser;
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type IsController = SiblingUsers;
+    type LocationToAccountId = UserToAccount;
+    type AllowedCalls = NoRemarkWithEvent;
+    type MaxCallPatterns = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,198 @@
+use crate::{
+    mock::*, AllowDescendedPaidExecutionFrom, CallFilter, CallPattern, Error, Event as PalletEvent,
+    RestrictInterchainAccounts,
+};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{Everything, Nothing, OriginTrait},
+};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::{ConvertOrigin, ShouldExecute};
+
+type OriginConverter = RestrictInterchainAccounts<Test, SignedUser>;
+
+fn remark() -> Call {
+    Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn remark_with_event() -> Call {
+    Call::System(frame_system::Call::remark_with_event { remark: vec![] })
+}
+
+fn close_account() -> Call {
+    Call::InterchainAccounts(crate::Call::close_account {})
+}
+
+fn system_calls() -> CallFilter<Vec<CallPattern>> {
+    CallFilter::Only(vec![CallPattern {
+        pallet_index: 0,
+        call_index: None,
+    }])
+}
+
+#[test]
+fn opening_an_account_records_its_controller() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(InterchainAccounts::open_account(
+            Origin::signed(1),
+            CallFilter::All
+        ));
+
+        let opened = InterchainAccounts::account(account_of(1)).unwrap();
+        assert_eq!(opened.controller, user(1));
+        assert_eq!(opened.filter, CallFilter::All);
+        System::assert_last_event(PalletEvent::AccountOpened(account_of(1), user(1)).into());
+
+        assert_noop!(
+            InterchainAccounts::open_account(Origin::signed(1), CallFilter::All),
+            Error::<Test>::AlreadyOpened
+        );
+        assert_noop!(
+            InterchainAccounts::open_account(
+                Origin::signed(2),
+                CallFilter::Only(vec![
+                    CallPattern {
+                        pallet_index: 0,
+                        call_index: None
+                    };
+                    3
+                ])
+            ),
+            Error::<Test>::TooManyPatterns
+        );
+    });
+}
+
+#[test]
+fn users_can_only_dispatch_from_opened_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            OriginConverter::convert_origin(user(1), OriginKind::SovereignAccount).err(),
+            Some(user(1))
+        );
+
+        assert_ok!(InterchainAccounts::open_account(
+            Origin::signed(1),
+            CallFilter::All
+        ));
+        let origin = OriginConverter::convert_origin(user(1), OriginKind::SovereignAccount)
+            .ok()
+            .unwrap();
+        assert_eq!(origin.caller(), Origin::signed(account_of(1)).caller());
+        assert!(origin.filter_call(&remark()));
+        // Calls not allowed for all interchain accounts stay filtered.
+        assert!(!origin.filter_call(&remark_with_event()));
+
+        assert_ok!(InterchainAccounts::close_account(Origin::signed(1)));
+        assert!(OriginConverter::convert_origin(user(1), OriginKind::SovereignAccount).is_err());
+    });
+}
+
+#[test]
+fn calls_are_filtered_per_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(InterchainAccounts::open_account(
+            Origin::signed(1),
+            system_calls()
+        ));
+        let origin = OriginConverter::convert_origin(user(1), OriginKind::SovereignAccount)
+            .ok()
+            .unwrap();
+        assert!(origin.filter_call(&remark()));
+        assert!(!origin.filter_call(&close_account()));
+
+        // The filter is read when the call is dispatched.
+        assert_ok!(InterchainAccounts::set_call_filter(
+            Origin::signed(1),
+            CallFilter::Only(vec![CallPattern {
+                pallet_index: 1,
+                call_index: Some(2),
+            }])
+        ));
+        assert!(!origin.filter_call(&remark()));
+        assert!(origin.filter_call(&close_account()));
+    });
+}
+
+#[test]
+fn accounts_can_be_force_closed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(InterchainAccounts::open_account(
+            Origin::signed(1),
+            CallFilter::All
+        ));
+
+        assert_noop!(
+            InterchainAccounts::force_close_account(Origin::signed(1), account_of(1)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(InterchainAccounts::force_close_account(
+            Origin::root(),
+            account_of(1)
+        ));
+        assert_eq!(InterchainAccounts::account(account_of(1)), None);
+        assert_noop!(
+            InterchainAccounts::set_call_filter(Origin::signed(1), CallFilter::All),
+            Error::<Test>::NotOpened
+        );
+    });
+}
+
+#[test]
+fn descended_messages_must_pay_for_their_execution() {
+    let sibling = MultiLocation::new(1, X1(Parachain(2000)));
+    let fees: MultiAsset = (Parent, 1_000).into();
+    let descended = |weight_limit| {
+        Xcm::<()>(vec![
+            DescendOrigin(X1(AccountIndex64 {
+                network: Any,
+                index: 1,
+            })),
+            WithdrawAsset(fees.clone().into()),
+            ClearOrigin,
+            BuyExecution {
+                fees: fees.clone(),
+                weight_limit,
+            },
+        ])
+    };
+
+    let mut message = descended(Unlimited);
+    assert_ok!(
+        AllowDescendedPaidExecutionFrom::<Everything>::should_execute(
+            &sibling,
+            &mut message,
+            50,
+            &mut 0
+        )
+    );
+    assert_eq!(descended(Limited(50)), message);
+    assert!(
+        AllowDescendedPaidExecutionFrom::<Everything>::should_execute(
+            &sibling,
+            &mut descended(Limited(49)),
+            50,
+            &mut 0
+        )
+        .is_err()
+    );
+    assert!(AllowDescendedPaidExecutionFrom::<Nothing>::should_execute(
+        &sibling,
+        &mut descended(Unlimited),
+        50,
+        &mut 0
+    )
+    .is_err());
+
+    // Fees must be withdrawn from the account of the user.
+    let mut undescended = Xcm::<()>(descended(Unlimited).0.split_off(1));
+    assert!(
+        AllowDescendedPaidExecutionFrom::<Everything>::should_execute(
+            &sibling,
+            &mut undescended,
+            50,
+            &mut 0
+        )
+        .is_err()
+    );
+}
@@ -0,0 +1,250 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Maintenance mode and pause switches for incident response.
+//!
+//! The pallet implements `Contains<Call>` and is meant to be plugged into
+//! `frame_system::Config::BaseCallFilter`. While maintenance mode is on, only the calls in
+//! `Config::WhitelistedCalls` can be dispatched. Outside of maintenance mode, whole pallets or
+//! single calls can be paused by name.
+
+use frame_support::{
+    pallet_prelude::*,
+    traits::{CallMetadata, Contains, GetCallMetadata, PalletInfoAccess},
+};
+use sp_std::vec::Vec;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The name of a pallet as it appears in `construct_runtime!`.
+pub type PalletNameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+
+/// The name of a dispatchable function.
+pub type CallNameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to switch maintenance mode and to pause or resume calls.
+        type MaintenanceOrigin: EnsureOrigin<Self::Origin>;
+
+        /// Calls that can always be dispatched, even in maintenance mode. They are also never
+        /// affected by pausing. This should cover governance, sudo, system and inherent calls.
+        type WhitelistedCalls: Contains<<Self as frame_system::Config>::Call>;
+
+        /// Maximum length of a pallet or call name.
+        #[pallet::constant]
+        type MaxNameLen: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// Whether the chain is in maintenance mode.
+    #[pallet::storage]
+    #[pallet::getter(fn maintenance_mode)]
+    pub type MaintenanceMode<T> = StorageValue<_, bool, ValueQuery>;
+
+    /// Pallets whose calls are all paused.
+    #[pallet::storage]
+    #[pallet::getter(fn paused_pallets)]
+    pub type PausedPallets<T: Config> = StorageMap<_, Blake2_128Concat, PalletNameOf<T>, ()>;
+
+    /// Single calls that are paused, by pallet and call name.
+    #[pallet::storage]
+    #[pallet::getter(fn paused_calls)]
+    pub type PausedCalls<T: Config> =
+        StorageMap<_, Blake2_128Concat, (PalletNameOf<T>, CallNameOf<T>), ()>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Maintenance mode was switched on.
+        EnteredMaintenanceMode,
+        /// Maintenance mode was switched off.
+        NormalOperationResumed,
+        /// All calls of a pallet were paused. [pallet_name]
+        PalletPaused(PalletNameOf<T>),
+        /// The calls of a pallet were resumed. [pallet_name]
+        PalletResumed(PalletNameOf<T>),
+        /// A call was paused. [pallet_name, call_name]
+        CallPaused(PalletNameOf<T>, CallNameOf<T>),
+        /// A call was resumed. [pallet_name, call_name]
+        CallResumed(PalletNameOf<T>, CallNameOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The chain is already in maintenance mode.
+        AlreadyInMaintenanceMode,
+        /// The chain is not in maintenance mode.
+        NotInMaintenanceMode,
+        /// A pallet or call name is longer than `MaxNameLen`.
+        NameTooLong,
+        /// This pallet's own calls cannot be paused.
+        CannotPauseSelf,
+        /// The pallet or call is already paused.
+        AlreadyPaused,
+        /// The pallet or call is not paused.
+        NotPaused,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Only allow `WhitelistedCalls` to be dispatched until `resume_normal_operation`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn enter_maintenance_mode(origin: OriginFor<T>) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            ensure!(
+                !MaintenanceMode::<T>::get(),
+                Error::<T>::AlreadyInMaintenanceMode
+            );
+
+            MaintenanceMode::<T>::put(true);
+            Self::deposit_event(Event::EnteredMaintenanceMode);
+            Ok(())
+        }
+
+        /// Leave maintenance mode. Pallets and calls paused individually stay paused.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn resume_normal_operation(origin: OriginFor<T>) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            ensure!(
+                MaintenanceMode::<T>::get(),
+                Error::<T>::NotInMaintenanceMode
+            );
+
+            MaintenanceMode::<T>::put(false);
+            Self::deposit_event(Event::NormalOperationResumed);
+            Ok(())
+        }
+
+        /// Pause every call of the pallet named `pallet_name`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn pause_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            let pallet_name = Self::pallet_name(pallet_name)?;
+            ensure!(
+                !PausedPallets::<T>::contains_key(&pallet_name),
+                Error::<T>::AlreadyPaused
+            );
+
+            PausedPallets::<T>::insert(&pallet_name, ());
+            Self::deposit_event(Event::PalletPaused(pallet_name));
+            Ok(())
+        }
+
+        /// Resume the calls of a pallet paused with `pause_pallet`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn resume_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            let pallet_name = Self::bounded(pallet_name)?;
+            ensure!(
+                PausedPallets::<T>::take(&pallet_name).is_some(),
+                Error::<T>::NotPaused
+            );
+
+            Self::deposit_event(Event::PalletResumed(pallet_name));
+            Ok(())
+        }
+
+        /// Pause the call `call_name` of the pallet named `pallet_name`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn pause_call(
+            origin: OriginFor<T>,
+            pallet_name: Vec<u8>,
+            call_name: Vec<u8>,
+        ) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            let key = (Self::pallet_name(pallet_name)?, Self::bounded(call_name)?);
+            ensure!(
+                !PausedCalls::<T>::contains_key(&key),
+                Error::<T>::AlreadyPaused
+            );
+
+            PausedCalls::<T>::insert(&key, ());
+            Self::deposit_event(Event::CallPaused(key.0, key.1));
+            Ok(())
+        }
+
+        /// Resume a call paused with `pause_call`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn resume_call(
+            origin: OriginFor<T>,
+            pallet_name: Vec<u8>,
+            call_name: Vec<u8>,
+        ) -> DispatchResult {
+            T::MaintenanceOrigin::ensure_origin(origin)?;
+            let key = (Self::bounded(pallet_name)?, Self::bounded(call_name)?);
+            ensure!(
+                PausedCalls::<T>::take(&key).is_some(),
+                Error::<T>::NotPaused
+            );
+
+            Self::deposit_event(Event::CallResumed(key.0, key.1));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    fn bounded(name: Vec<u8>) -> Result<BoundedVec<u8, T::MaxNameLen>, DispatchError> {
+        BoundedVec::try_from(name).map_err(|_| Error::<T>::NameTooLong.into())
+    }
+
+    /// A pallet name that may be paused, i.e. anything but this pallet.
+    fn pallet_name(name: Vec<u8>) -> Result<PalletNameOf<T>, DispatchError> {
+        ensure!(
+            name != <Self as PalletInfoAccess>::name().as_bytes(),
+            Error::<T>::CannotPauseSelf
+        );
+        Self::bounded(name)
+    }
+
+    /// Whether the call described by `metadata` was paused, either on its own or with its pallet.
+    fn is_paused(metadata: &CallMetadata) -> bool {
+        let pallet_name =
+            match PalletNameOf::<T>::try_from(metadata.pallet_name.as_bytes().to_vec()) {
+                Ok(name) => name,
+                // Names that don't fit cannot have been paused.
+                Err(_) => return false,
+            };
+        if PausedPallets::<T>::contains_key(&pallet_name) {
+            return true;
+        }
+        match CallNameOf::<T>::try_from(metadata.function_name.as_bytes().to_vec()) {
+            Ok(call_name) => PausedCalls::<T>::contains_key((pallet_name, call_name)),
+            Err(_) => false,
+        }
+    }
+}
+
+impl<T: Config> Contains<<T as frame_system::Config>::Call> for Pallet<T>
+where
+    <T as frame_system::Config>::Call: GetCallMetadata,
+{
+    fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+        if T::WhitelistedCalls::contains(call) {
+            return true;
+        }
+        if MaintenanceMode::<T>::get() {
+            return false;
+        }
+        !Self::is_paused(&call.get_call_metadata())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok, traits::Contains};
+
+fn remark() -> Call {
+    Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn remark_with_event() -> Call {
+    Call::System(frame_system::Call::remark_with_event { remark: vec![] })
+}
+
+#[test]
+fn maintenance_mode_blocks_all_but_whitelisted_calls() {
+    new_test_ext().execute_with(|| {
+        assert!(MaintenanceMode::contains(&remark()));
+
+        assert_ok!(MaintenanceMode::enter_maintenance_mode(Origin::root()));
+        assert!(!MaintenanceMode::contains(&remark()));
+        assert!(MaintenanceMode::contains(&Call::MaintenanceMode(
+            crate::Call::resume_normal_operation {}
+        )));
+
+        assert_ok!(MaintenanceMode::resume_normal_operation(Origin::root()));
+        assert!(MaintenanceMode::contains(&remark()));
+    });
+}
+
+#[test]
+fn maintenance_mode_requires_maintenance_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MaintenanceMode::enter_maintenance_mode(Origin::signed(1)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            MaintenanceMode::resume_normal_operation(Origin::root()),
+            Error::<Test>::NotInMaintenanceMode
+        );
+    });
+}
+
+#[test]
+fn pausing_a_call_only_blocks_that_call() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MaintenanceMode::pause_call(
+            Origin::root(),
+            b"System".to_vec(),
+            b"remark".to_vec()
+        ));
+        assert!(!MaintenanceMode::contains(&remark()));
+        assert!(MaintenanceMode::contains(&remark_with_event()));
+
+        assert_ok!(MaintenanceMode::resume_call(
+            Origin::root(),
+            b"System".to_vec(),
+            b"remark".to_vec()
+        ));
+        assert!(MaintenanceMode::contains(&remark()));
+    });
+}
+
+#[test]
+fn pausing_a_pallet_blocks_all_its_calls() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(MaintenanceMode::pause_pallet(
+            Origin::root(),
+            b"System".to_vec()
+        ));
+        assert!(!MaintenanceMode::contains(&remark()));
+        assert!(!MaintenanceMode::contains(&remark_with_event()));
+        assert_noop!(
+            MaintenanceMode::pause_pallet(Origin::root(), b"System".to_vec()),
+            Error::<Test>::AlreadyPaused
+        );
+
+        assert_ok!(MaintenanceMode::resume_pallet(
+            Origin::root(),
+            b"System".to_vec()
+        ));
+        assert!(MaintenanceMode::contains(&remark()));
+    });
+}
+
+#[test]
+fn cannot_pause_itself() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MaintenanceMode::pause_pallet(Origin::root(), b"MaintenanceMode".to_vec()),
+            Error::<Test>::CannotPauseSelf
+        );
+        assert_noop!(
+            MaintenanceMode::pause_call(
+                Origin::root(),
+                b"MaintenanceMode".to_vec(),
+                b"resume_normal_operation".to_vec()
+            ),
+            Error::<Test>::CannotPauseSelf
+        );
+    });
+}
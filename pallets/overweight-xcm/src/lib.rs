@@ -0,0 +1,293 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Management of the overweight XCM messages parked by the inbound queues.
+//!
+//! `XcmpQueue` and `DmpQueue` park the messages too heavy to be executed automatically under an
+//! increasing index, until they are serviced by hand. This pallet lists them with what is needed
+//! to decide what to do with them, lets `ServiceOrigin` service a batch of them at once and drops
+//! the ones sent more than `ExpiryPeriod` relay chain blocks ago.
+//!
+//! The queues are accessed through [`OverweightQueue`], which the runtime implements on top of
+//! their storage.
+
+use codec::{Decode, DecodeLimit, Encode, MaxEncodedLen};
+use frame_support::{pallet_prelude::*, weights::Weight, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_runtime::traits::BlockNumberProvider;
+use sp_std::vec::Vec;
+use xcm::{latest::prelude::*, VersionedXcm, MAX_XCM_DECODE_DEPTH};
+use xcm_executor::traits::WeightBounds;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The index of a message among the overweight messages of a queue.
+pub type OverweightIndex = u64;
+
+/// A relay chain block number.
+pub type RelayBlockNumber = u32;
+
+/// An inbound queue parking overweight messages.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum Queue {
+    /// `XcmpQueue`, receiving messages from siblings.
+    Xcmp,
+    /// `DmpQueue`, receiving messages from the relay chain.
+    Dmp,
+}
+
+/// An overweight message as parked by its queue.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ParkedMessage {
+    /// The chain that sent the message.
+    pub origin: MultiLocation,
+    /// The relay chain block the message was sent in.
+    pub sent_at: RelayBlockNumber,
+    /// The encoded `VersionedXcm` of the message.
+    pub data: Vec<u8>,
+}
+
+/// Access to the overweight messages parked by a queue.
+pub trait OverweightQueue {
+    /// The number of messages ever parked, which is the index of the next one.
+    fn overweight_count() -> OverweightIndex;
+
+    /// The message parked under `index`, if it is still there.
+    fn overweight_message(index: OverweightIndex) -> Option<ParkedMessage>;
+
+    /// All the messages still parked.
+    fn overweight_messages() -> Vec<(OverweightIndex, ParkedMessage)>;
+
+    /// Execute the message parked under `index` with up to `weight_limit`, and remove it if that
+    /// was enough. Returns the weight used.
+    fn service_overweight(
+        index: OverweightIndex,
+        weight_limit: Weight,
+    ) -> Result<Weight, DispatchError>;
+
+    /// Drop the message parked under `index`.
+    fn remove_overweight(index: OverweightIndex);
+}
+
+/// An overweight message, decoded.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct OverweightMessage {
+    pub queue: Queue,
+    pub index: OverweightIndex,
+    /// The chain that sent the message.
+    pub origin: MultiLocation,
+    /// The relay chain block the message was sent in.
+    pub sent_at: RelayBlockNumber,
+    /// The size of the encoded message, in bytes.
+    pub size: u32,
+    /// The weight needed to execute the message, `None` if it can't be decoded or weighed.
+    pub required_weight: Option<Weight>,
+    /// The message, `None` if it can't be decoded.
+    pub message: Option<Xcm<()>>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to service overweight messages.
+        type ServiceOrigin: EnsureOrigin<Self::Origin>;
+
+        /// The queue of messages from siblings.
+        type XcmpQueue: OverweightQueue;
+
+        /// The queue of messages from the relay chain.
+        type DmpQueue: OverweightQueue;
+
+        /// Weighs the messages as the XCM executor does.
+        type Weigher: WeightBounds<Self::Call>;
+
+        /// Gives the current relay chain block number.
+        type RelayBlockNumberProvider: BlockNumberProvider<BlockNumber = RelayBlockNumber>;
+
+        /// The number of relay chain blocks after which an overweight message is dropped.
+        #[pallet::constant]
+        type ExpiryPeriod: Get<RelayBlockNumber>;
+
+        /// The maximum number of messages dropped in a block.
+        #[pallet::constant]
+        type MaxExpiredPerBlock: Get<u32>;
+
+        /// The maximum number of messages of each queue checked for expiry in a block.
+        #[pallet::constant]
+        type MaxChecksPerBlock: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The index of the next message of a queue to check for expiry.
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_cursor)]
+    pub type ExpiryCursor<T> = StorageMap<_, Twox64Concat, Queue, OverweightIndex, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// An overweight message was executed. [queue, index, weight_used]
+        Serviced(Queue, OverweightIndex, Weight),
+        /// An overweight message could not be executed and is still parked.
+        /// [queue, index, error]
+        ServiceFailed(Queue, OverweightIndex, DispatchError),
+        /// An overweight message expired and was dropped. [queue, index]
+        Expired(Queue, OverweightIndex),
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+            Self::expire_messages(remaining_weight)
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Execute the overweight messages `messages`, each given as its queue, its index and
+        /// the weight it may use. Messages that fail are left parked.
+        #[pallet::weight(messages.iter().fold(10_000, |weight: Weight, (_, _, limit)| {
+            weight
+                .saturating_add(*limit)
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        }))]
+        pub fn service_overweight(
+            origin: OriginFor<T>,
+            messages: Vec<(Queue, OverweightIndex, Weight)>,
+        ) -> DispatchResultWithPostInfo {
+            T::ServiceOrigin::ensure_origin(origin)?;
+
+            let mut weight_used: Weight = 10_000;
+            for (queue, index, weight_limit) in messages {
+                let result = match queue {
+                    Queue::Xcmp => T::XcmpQueue::service_overweight(index, weight_limit),
+                    Queue::Dmp => T::DmpQueue::service_overweight(index, weight_limit),
+                };
+                weight_used = weight_used.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+                match result {
+                    Ok(used) => {
+                        weight_used = weight_used.saturating_add(used);
+                        Self::deposit_event(Event::Serviced(queue, index, used));
+                    }
+                    Err(error) => Self::deposit_event(Event::ServiceFailed(queue, index, error)),
+                }
+            }
+            Ok(Some(weight_used).into())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// The messages parked by both queues, decoded.
+    pub fn overweight_messages() -> Vec<OverweightMessage> {
+        let xcmp = T::XcmpQueue::overweight_messages()
+            .into_iter()
+            .map(|(index, parked)| Self::decode(Queue::Xcmp, index, parked));
+        let dmp = T::DmpQueue::overweight_messages()
+            .into_iter()
+            .map(|(index, parked)| Self::decode(Queue::Dmp, index, parked));
+        xcmp.chain(dmp).collect()
+    }
+
+    fn decode(queue: Queue, index: OverweightIndex, parked: ParkedMessage) -> OverweightMessage {
+        let message = VersionedXcm::<()>::decode_all_with_depth_limit(
+            MAX_XCM_DECODE_DEPTH,
+            &mut &parked.data[..],
+        )
+        .ok()
+        .and_then(|versioned| Xcm::<()>::try_from(versioned).ok());
+        let required_weight = message
+            .clone()
+            .and_then(|message| T::Weigher::weight(&mut Xcm::<T::Call>::from(message)).ok());
+        OverweightMessage {
+            queue,
+            index,
+            origin: parked.origin,
+            sent_at: parked.sent_at,
+            size: parked.data.len() as u32,
+            required_weight,
+            message,
+        }
+    }
+
+    /// Drop the messages that expired, in the order they were parked, within `weight_limit`.
+    /// Returns the weight used.
+    fn expire_messages(weight_limit: Weight) -> Weight {
+        let step = T::DbWeight::get().reads_writes(2, 2);
+        let mut weight_used = T::DbWeight::get().reads(1);
+        if weight_limit < weight_used {
+            return 0;
+        }
+        let expire_up_to =
+            T::RelayBlockNumberProvider::current_block_number().checked_sub(T::ExpiryPeriod::get());
+        let expire_up_to = match expire_up_to {
+            Some(block) => block,
+            None => return weight_used,
+        };
+
+        let mut expired = 0;
+        for queue in [Queue::Xcmp, Queue::Dmp] {
+            weight_used = weight_used.saturating_add(T::DbWeight::get().reads(2));
+            let count = match queue {
+                Queue::Xcmp => T::XcmpQueue::overweight_count(),
+                Queue::Dmp => T::DmpQueue::overweight_count(),
+            };
+            let mut cursor = ExpiryCursor::<T>::get(queue);
+            let start = cursor;
+            let mut first_kept = None;
+            // The messages kept are checked again every block, so a long run of them must not
+            // use up the idle weight of every block.
+            let mut checked = 0;
+            while cursor < count
+                && checked < T::MaxChecksPerBlock::get()
+                && expired < T::MaxExpiredPerBlock::get()
+                && weight_used.saturating_add(step) <= weight_limit
+            {
+                weight_used = weight_used.saturating_add(step);
+                checked += 1;
+                let parked = match queue {
+                    Queue::Xcmp => T::XcmpQueue::overweight_message(cursor),
+                    Queue::Dmp => T::DmpQueue::overweight_message(cursor),
+                };
+                match parked {
+                    // Messages are parked in the order they arrive, which is not quite the order
+                    // they were sent in, so the following ones may still be old enough. The
+                    // cursor comes back to this one.
+                    Some(parked) if parked.sent_at > expire_up_to => {
+                        first_kept.get_or_insert(cursor);
+                    }
+                    Some(_) => {
+                        match queue {
+                            Queue::Xcmp => T::XcmpQueue::remove_overweight(cursor),
+                            Queue::Dmp => T::DmpQueue::remove_overweight(cursor),
+                        }
+                        Self::deposit_event(Event::Expired(queue, cursor));
+                        expired += 1;
+                    }
+                    // Already serviced.
+                    None => {}
+                }
+                cursor += 1;
+            }
+            let cursor = first_kept.unwrap_or(cursor);
+            if cursor != start {
+                ExpiryCursor::<T>::insert(queue, cursor);
+            }
+        }
+        weight_used
+    }
+}
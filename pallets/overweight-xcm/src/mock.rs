@@ -0,0 +1,195 @@
+use crate::{self as pallet_overweight_xcm, OverweightIndex, OverweightQueue, ParkedMessage};
+use codec::{DecodeLimit, Encode};
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, Everything},
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, BlockNumberProvider, IdentityLookup},
+    DispatchError,
+};
+use sp_std::cell::RefCell;
+use xcm::{latest::prelude::*, VersionedXcm, MAX_XCM_DECODE_DEPTH};
+use xcm_executor::traits::WeightBounds;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        OverweightXcm: pallet_overweight_xcm::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub const ExpiryPeriod: u32 = 100;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+thread_local! {
+    pub static XCMP_PARKED: RefCell<Vec<Option<ParkedMessage>>> = RefCell::new(Vec::new());
+    pub static DMP_PARKED: RefCell<Vec<Option<ParkedMessage>>> = RefCell::new(Vec::new());
+    pub static RELAY_BLOCK: RefCell<u32> = RefCell::new(0);
+}
+
+/// Every instruction weighs 10.
+pub struct UnitWeigher;
+impl WeightBounds<Call> for UnitWeigher {
+    fn weight(message: &mut Xcm<Call>) -> Result<Weight, ()> {
+        Ok(message.0.len() as Weight * 10)
+    }
+
+    fn instr_weight(_instruction: &Instruction<Call>) -> Result<Weight, ()> {
+        Ok(10)
+    }
+}
+
+/// Park `data` in the mock XCMP queue if `dmp` is false, or the mock DMP queue. Returns its
+/// index.
+pub fn park(dmp: bool, origin: MultiLocation, sent_at: u32, data: Vec<u8>) -> OverweightIndex {
+    let parked = if dmp { &DMP_PARKED } else { &XCMP_PARKED };
+    parked.with(|p| {
+        let mut parked = p.borrow_mut();
+        parked.push(Some(ParkedMessage {
+            origin,
+            sent_at,
+            data,
+        }));
+        parked.len() as OverweightIndex - 1
+    })
+}
+
+/// Park `message` in the mock XCMP queue, as sent by sibling 2000.
+pub fn park_xcmp(sent_at: u32, message: Xcm<()>) -> OverweightIndex {
+    let origin = MultiLocation::new(1, X1(Parachain(2000)));
+    park(false, origin, sent_at, VersionedXcm::from(message).encode())
+}
+
+pub fn set_relay_block(block: u32) {
+    RELAY_BLOCK.with(|b| *b.borrow_mut() = block);
+}
+
+pub struct MockRelayBlock;
+impl BlockNumberProvider for MockRelayBlock {
+    type BlockNumber = u32;
+
+    fn current_block_number() -> u32 {
+        RELAY_BLOCK.with(|b| *b.borrow())
+    }
+}
+
+/// A queue whose messages execute if given 10 weight per instruction.
+pub struct MockQueue<const DMP: bool>;
+impl<const DMP: bool> MockQueue<DMP> {
+    fn with<R>(f: impl FnOnce(&mut Vec<Option<ParkedMessage>>) -> R) -> R {
+        let parked = if DMP { &DMP_PARKED } else { &XCMP_PARKED };
+        parked.with(|p| f(&mut p.borrow_mut()))
+    }
+}
+
+impl<const DMP: bool> OverweightQueue for MockQueue<DMP> {
+    fn overweight_count() -> OverweightIndex {
+        Self::with(|parked| parked.len() as OverweightIndex)
+    }
+
+    fn overweight_message(index: OverweightIndex) -> Option<ParkedMessage> {
+        Self::with(|parked| parked.get(index as usize).cloned().flatten())
+    }
+
+    fn overweight_messages() -> Vec<(OverweightIndex, ParkedMessage)> {
+        Self::with(|parked| {
+            parked
+                .iter()
+                .enumerate()
+                .filter_map(|(index, p)| Some((index as OverweightIndex, p.clone()?)))
+                .collect()
+        })
+    }
+
+    fn service_overweight(
+        index: OverweightIndex,
+        weight_limit: Weight,
+    ) -> Result<Weight, DispatchError> {
+        let parked =
+            Self::overweight_message(index).ok_or(DispatchError::Other("BadOverweightIndex"))?;
+        let message = VersionedXcm::<()>::decode_all_with_depth_limit(
+            MAX_XCM_DECODE_DEPTH,
+            &mut &parked.data[..],
+        )
+        .ok()
+        .and_then(|versioned| Xcm::<()>::try_from(versioned).ok())
+        .ok_or(DispatchError::Other("BadXcm"))?;
+        let required = message.0.len() as Weight * 10;
+        if required > weight_limit {
+            return Err(DispatchError::Other("WeightOverLimit"));
+        }
+        Self::remove_overweight(index);
+        Ok(required)
+    }
+
+    fn remove_overweight(index: OverweightIndex) {
+        Self::with(|parked| {
+            if let Some(p) = parked.get_mut(index as usize) {
+                *p = None;
+            }
+        })
+    }
+}
+
+impl pallet_overweight_xcm::Config for Test {
+    type Event = Event;
+    type ServiceOrigin = frame_system::EnsureRoot<u64>;
+    type XcmpQueue = MockQueue<false>;
+    type DmpQueue = MockQueue<true>;
+    type Weigher = UnitWeigher;
+    type RelayBlockNumberProvider = MockRelayBlock;
+    type ExpiryPeriod = ExpiryPeriod;
+    type MaxExpiredPerBlock = ConstU32<2>;
+    type MaxChecksPerBlock = ConstU32<3>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
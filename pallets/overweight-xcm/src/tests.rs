@@ -0,0 +1,141 @@
+use crate::{mock::*, Event as OverweightEvent, Queue};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_runtime::DispatchError;
+use xcm::latest::prelude::*;
+
+fn message(instructions: usize) -> Xcm<()> {
+    Xcm(vec![ClearOrigin; instructions])
+}
+
+#[test]
+fn overweight_messages_are_listed_decoded() {
+    new_test_ext().execute_with(|| {
+        park_xcmp(5, message(2));
+        park(true, MultiLocation::parent(), 7, vec![1, 2, 3]);
+
+        let messages = OverweightXcm::overweight_messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].queue, Queue::Xcmp);
+        assert_eq!(messages[0].index, 0);
+        assert_eq!(
+            messages[0].origin,
+            MultiLocation::new(1, X1(Parachain(2000)))
+        );
+        assert_eq!(messages[0].sent_at, 5);
+        assert_eq!(messages[0].required_weight, Some(20));
+        assert_eq!(messages[0].message, Some(message(2)));
+        // Undecodable messages are still listed.
+        assert_eq!(messages[1].queue, Queue::Dmp);
+        assert_eq!(messages[1].origin, MultiLocation::parent());
+        assert_eq!(messages[1].size, 3);
+        assert_eq!(messages[1].required_weight, None);
+        assert_eq!(messages[1].message, None);
+    });
+}
+
+#[test]
+fn service_overweight_reports_each_message() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        park_xcmp(5, message(2));
+        park_xcmp(5, message(3));
+
+        assert_noop!(
+            OverweightXcm::service_overweight(Origin::signed(1), vec![(Queue::Xcmp, 0, 100)]),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(OverweightXcm::service_overweight(
+            Origin::root(),
+            vec![
+                (Queue::Xcmp, 0, 100),
+                (Queue::Xcmp, 1, 20),
+                (Queue::Dmp, 0, 100)
+            ]
+        ));
+
+        System::assert_has_event(OverweightEvent::Serviced(Queue::Xcmp, 0, 20).into());
+        System::assert_has_event(
+            OverweightEvent::ServiceFailed(Queue::Xcmp, 1, DispatchError::Other("WeightOverLimit"))
+                .into(),
+        );
+        System::assert_last_event(
+            OverweightEvent::ServiceFailed(
+                Queue::Dmp,
+                0,
+                DispatchError::Other("BadOverweightIndex"),
+            )
+            .into(),
+        );
+        let indices: Vec<_> = OverweightXcm::overweight_messages()
+            .iter()
+            .map(|message| message.index)
+            .collect();
+        assert_eq!(indices, vec![1]);
+    });
+}
+
+#[test]
+fn expired_messages_are_dropped_oldest_first() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        park_xcmp(10, message(1));
+        park_xcmp(20, message(1));
+        park_xcmp(200, message(1));
+        park(true, MultiLocation::parent(), 15, vec![]);
+        set_relay_block(150);
+
+        // At most two messages are dropped per block.
+        OverweightXcm::on_idle(1, u64::MAX);
+        System::assert_has_event(OverweightEvent::Expired(Queue::Xcmp, 0).into());
+        System::assert_last_event(OverweightEvent::Expired(Queue::Xcmp, 1).into());
+        assert_eq!(OverweightXcm::overweight_messages().len(), 2);
+
+        // The last message of the XCMP queue is not old enough.
+        OverweightXcm::on_idle(2, u64::MAX);
+        System::assert_last_event(OverweightEvent::Expired(Queue::Dmp, 0).into());
+        assert_eq!(OverweightXcm::expiry_cursor(Queue::Xcmp), 2);
+        assert_eq!(OverweightXcm::expiry_cursor(Queue::Dmp), 1);
+        assert_eq!(OverweightXcm::overweight_messages()[0].sent_at, 200);
+    });
+}
+
+#[test]
+fn expiry_goes_past_messages_not_old_enough() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        // Parked in a different order than they were sent.
+        park_xcmp(120, message(1));
+        park_xcmp(10, message(1));
+        set_relay_block(150);
+
+        OverweightXcm::on_idle(1, u64::MAX);
+        System::assert_last_event(OverweightEvent::Expired(Queue::Xcmp, 1).into());
+        assert_eq!(OverweightXcm::overweight_messages().len(), 1);
+        // The message kept is looked at again.
+        assert_eq!(OverweightXcm::expiry_cursor(Queue::Xcmp), 0);
+
+        set_relay_block(220);
+        OverweightXcm::on_idle(2, u64::MAX);
+        System::assert_last_event(OverweightEvent::Expired(Queue::Xcmp, 0).into());
+        assert_eq!(OverweightXcm::expiry_cursor(Queue::Xcmp), 2);
+    });
+}
+
+#[test]
+fn a_bounded_number_of_messages_is_checked_per_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        park_xcmp(120, message(1));
+        park_xcmp(120, message(1));
+        park_xcmp(120, message(1));
+        park_xcmp(10, message(1));
+        park(true, MultiLocation::parent(), 10, vec![]);
+        set_relay_block(150);
+
+        // At most three messages of each queue are checked per block.
+        OverweightXcm::on_idle(1, u64::MAX);
+        System::assert_last_event(OverweightEvent::Expired(Queue::Dmp, 0).into());
+        assert_eq!(OverweightXcm::overweight_messages().len(), 4);
+        assert_eq!(OverweightXcm::expiry_cursor(Queue::Xcmp), 0);
+    });
+}
@@ -0,0 +1,506 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Operations of the sovereign account of this parachain on the relay chain.
+//!
+//! Every dispatchable sends the relay chain, over UMP, a program run by our sovereign account
+//! there: a `Transact` of a `Staking` or `Crowdloan` call, paid for with `RelayFee`, or a reserve
+//! transfer of relay chain tokens back to an account on this chain. The program reports its
+//! outcome back through `PolkadotXcm`, which dispatches `operation_result`, and the pallet emits
+//! whether the program was executed. In XCM v2 the outcome of a `Transact` does not include the
+//! result of the call it dispatched, so an executed `Staking` or `Crowdloan` call may still have
+//! failed. Operations whose outcome isn't reported within `ResultTimeout` blocks are dropped in
+//! `on_idle`.
+//!
+//! The call indices of these pallets are the same on every relay chain, but their pallet indices
+//! are not. They are kept in `RelayPallets`, set at genesis for the relay chain the chain spec
+//! is for.
+
+use codec::{Compact, Decode, Encode};
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    pallet_prelude::*,
+    weights::{GetDispatchInfo, Weight},
+    RuntimeDebug,
+};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    traits::{AccountIdConversion, Convert},
+    AccountId32, MultiAddress, MultiSignature,
+};
+use sp_std::{vec, vec::Vec};
+use xcm::latest::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The indices of the relay chain pallets operated on, `None` if the relay chain has none.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct RelayPalletIndices {
+    pub staking: Option<u8>,
+    pub crowdloan: Option<u8>,
+}
+
+impl RelayPalletIndices {
+    /// The indices for the relay chain with the chain spec id `relay_chain`, e.g. `kusama` or
+    /// `rococo-local`.
+    pub fn for_relay_chain(relay_chain: &str) -> Option<Self> {
+        let (staking, crowdloan) = if relay_chain.starts_with("polkadot") {
+            (Some(7), Some(73))
+        } else if relay_chain.starts_with("kusama") {
+            (Some(6), Some(73))
+        } else if relay_chain.starts_with("westend") {
+            (Some(6), Some(64))
+        } else if relay_chain.starts_with("rococo") {
+            (None, Some(73))
+        } else {
+            return None;
+        };
+        Some(Self { staking, crowdloan })
+    }
+}
+
+impl Default for RelayPalletIndices {
+    /// The indices for `rococo-local`.
+    fn default() -> Self {
+        Self {
+            staking: None,
+            crowdloan: Some(73),
+        }
+    }
+}
+
+/// An operation sent to the relay chain.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum RelayOperation<AccountId> {
+    Bond(u128),
+    BondExtra(u128),
+    Unbond(u128),
+    WithdrawUnbonded,
+    /// Nominate this many validators.
+    Nominate(u32),
+    Chill,
+    Contribute(ParaId, u128),
+    /// Transfer relay chain tokens to an account on this chain.
+    TransferToParachain(AccountId, u128),
+}
+
+/// Indices of the relay chain calls within their pallet.
+const STAKING_BOND: u8 = 0;
+const STAKING_BOND_EXTRA: u8 = 1;
+const STAKING_UNBOND: u8 = 2;
+const STAKING_WITHDRAW_UNBONDED: u8 = 3;
+const STAKING_NOMINATE: u8 = 5;
+const STAKING_CHILL: u8 = 6;
+const CROWDLOAN_CONTRIBUTE: u8 = 1;
+
+/// `RewardDestination::Staked`: rewards are bonded too.
+const REWARDS_STAKED: u8 = 0;
+
+type RelayAddress = MultiAddress<AccountId32, ()>;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_xcm::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        type Origin: From<<Self as frame_system::Config>::Origin>
+            + Into<Result<pallet_xcm::Origin, <Self as Config>::Origin>>;
+
+        type Call: From<Call<Self>> + Into<<Self as pallet_xcm::Config>::Call>;
+
+        /// Origin allowed to bond, unbond and nominate.
+        type StakingOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+
+        /// Origin allowed to contribute to crowdloans.
+        type CrowdloanOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+
+        /// Origin allowed to transfer relay chain tokens to this chain.
+        type TransferOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+
+        /// The id of this parachain.
+        type SelfParaId: Get<ParaId>;
+
+        /// Sends the operations to the relay chain.
+        type XcmSender: SendXcm;
+
+        /// Converts a local account into its location, to transfer relay chain tokens to.
+        type AccountIdToMultiLocation: Convert<Self::AccountId, MultiLocation>;
+
+        /// The amount of the relay chain token withdrawn from our sovereign account to pay for
+        /// each operation. What isn't used is deposited back.
+        #[pallet::constant]
+        type RelayFee: Get<u128>;
+
+        /// The weight bought on the relay chain for each call.
+        #[pallet::constant]
+        type RelayCallWeight: Get<Weight>;
+
+        /// The number of blocks after which the outcome of an operation is no longer expected.
+        #[pallet::constant]
+        type ResultTimeout: Get<Self::BlockNumber>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The indices of the relay chain pallets operated on.
+    #[pallet::storage]
+    #[pallet::getter(fn relay_pallets)]
+    pub type RelayPallets<T> = StorageValue<_, RelayPalletIndices, ValueQuery>;
+
+    /// The operations whose outcome hasn't been reported yet, keyed by the query reporting it,
+    /// with the block after which it is no longer expected.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_operations)]
+    pub type PendingOperations<T: Config> =
+        StorageMap<_, Twox64Concat, QueryId, (RelayOperation<T::AccountId>, T::BlockNumber)>;
+
+    /// The query of the first operation that may not have timed out yet.
+    #[pallet::storage]
+    pub type ExpiryCursor<T> = StorageValue<_, QueryId, ValueQuery>;
+
+    /// The query after that of the last operation sent.
+    #[pallet::storage]
+    pub type ExpiryEnd<T> = StorageValue<_, QueryId, ValueQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(Default)]
+    pub struct GenesisConfig {
+        pub relay_pallets: RelayPalletIndices,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            RelayPallets::<T>::put(self.relay_pallets);
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// An operation was sent to the relay chain. [query_id, operation]
+        OperationSent(QueryId, RelayOperation<T::AccountId>),
+        /// The program of an operation was executed on the relay chain. The call it dispatched may
+        /// still have failed. [query_id, operation]
+        OperationExecuted(QueryId, RelayOperation<T::AccountId>),
+        /// The program of an operation failed on the relay chain. [query_id, operation, error]
+        OperationFailed(QueryId, RelayOperation<T::AccountId>, XcmError),
+        /// The outcome of an operation was not reported in time. [query_id, operation]
+        OperationTimedOut(QueryId, RelayOperation<T::AccountId>),
+        /// The indices of the relay chain pallets were changed. [indices]
+        RelayPalletsSet(RelayPalletIndices),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The relay chain has no `Staking` pallet.
+        StakingUnavailable,
+        /// The relay chain has no `Crowdloan` pallet.
+        CrowdloanUnavailable,
+        /// The amount doesn't cover the fees on both chains.
+        AmountTooLow,
+        /// The message to the relay chain could not be sent.
+        SendFailure,
+        /// No operation is waiting for this response.
+        UnknownOperation,
+        /// The response doesn't report the outcome of an operation.
+        UnexpectedResponse,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+            Self::expire_operations(remaining_weight)
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Bond `value` of our sovereign account, which is its own controller. Rewards are
+        /// bonded too.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn bond(origin: OriginFor<T>, #[pallet::compact] value: u128) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let controller = RelayAddress::Id(Self::sovereign_account());
+            let call =
+                Self::staking_call(STAKING_BOND, (controller, Compact(value), REWARDS_STAKED))?;
+            Self::send_operation(RelayOperation::Bond(value), Self::transact(call))
+        }
+
+        /// Bond `value` more of our sovereign account.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn bond_extra(origin: OriginFor<T>, #[pallet::compact] value: u128) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let call = Self::staking_call(STAKING_BOND_EXTRA, Compact(value))?;
+            Self::send_operation(RelayOperation::BondExtra(value), Self::transact(call))
+        }
+
+        /// Start unbonding `value` of our sovereign account.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn unbond(origin: OriginFor<T>, #[pallet::compact] value: u128) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let call = Self::staking_call(STAKING_UNBOND, Compact(value))?;
+            Self::send_operation(RelayOperation::Unbond(value), Self::transact(call))
+        }
+
+        /// Unlock what finished unbonding.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn withdraw_unbonded(origin: OriginFor<T>, num_slashing_spans: u32) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let call = Self::staking_call(STAKING_WITHDRAW_UNBONDED, num_slashing_spans)?;
+            Self::send_operation(RelayOperation::WithdrawUnbonded, Self::transact(call))
+        }
+
+        /// Nominate `targets` with what our sovereign account bonded.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn nominate(origin: OriginFor<T>, targets: Vec<AccountId32>) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let count = targets.len() as u32;
+            let targets: Vec<_> = targets.into_iter().map(RelayAddress::Id).collect();
+            let call = Self::staking_call(STAKING_NOMINATE, targets)?;
+            Self::send_operation(RelayOperation::Nominate(count), Self::transact(call))
+        }
+
+        /// Stop nominating.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn chill(origin: OriginFor<T>) -> DispatchResult {
+            T::StakingOrigin::ensure_origin(origin)?;
+            let call = Self::staking_call(STAKING_CHILL, ())?;
+            Self::send_operation(RelayOperation::Chill, Self::transact(call))
+        }
+
+        /// Contribute `value` from our sovereign account to the crowdloan of `para_id`.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn contribute(
+            origin: OriginFor<T>,
+            para_id: ParaId,
+            #[pallet::compact] value: u128,
+        ) -> DispatchResult {
+            T::CrowdloanOrigin::ensure_origin(origin)?;
+            let crowdloan = Self::relay_pallets()
+                .crowdloan
+                .ok_or(Error::<T>::CrowdloanUnavailable)?;
+            let call = (
+                crowdloan,
+                CROWDLOAN_CONTRIBUTE,
+                Compact(u32::from(para_id)),
+                Compact(value),
+                None::<MultiSignature>,
+            )
+                .encode();
+            Self::send_operation(
+                RelayOperation::Contribute(para_id, value),
+                Self::transact(call),
+            )
+        }
+
+        /// Transfer `amount` of the relay chain token from our sovereign account to
+        /// `beneficiary` on this chain. Fees on both chains are paid from `amount`.
+        #[pallet::weight(100_000_000 + T::DbWeight::get().reads_writes(2, 3))]
+        pub fn transfer_to_parachain(
+            origin: OriginFor<T>,
+            beneficiary: T::AccountId,
+            #[pallet::compact] amount: u128,
+        ) -> DispatchResult {
+            T::TransferOrigin::ensure_origin(origin)?;
+            let fee = T::RelayFee::get();
+            ensure!(amount > fee.saturating_mul(2), Error::<T>::AmountTooLow);
+
+            let deposit = Xcm(vec![
+                BuyExecution {
+                    fees: (MultiLocation::parent(), fee).into(),
+                    weight_limit: Unlimited,
+                },
+                DepositAsset {
+                    assets: Wild(All),
+                    max_assets: 1,
+                    beneficiary: T::AccountIdToMultiLocation::convert(beneficiary.clone()),
+                },
+            ]);
+            let instructions = vec![
+                WithdrawAsset((MultiLocation::here(), amount).into()),
+                BuyExecution {
+                    fees: (MultiLocation::here(), fee).into(),
+                    weight_limit: Unlimited,
+                },
+                DepositReserveAsset {
+                    assets: Wild(All),
+                    max_assets: 1,
+                    dest: Self::self_location(),
+                    xcm: deposit,
+                },
+            ];
+            Self::send_operation(
+                RelayOperation::TransferToParachain(beneficiary, amount),
+                instructions,
+            )
+        }
+
+        /// Set the indices of the relay chain pallets operated on.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn set_relay_pallets(
+            origin: OriginFor<T>,
+            indices: RelayPalletIndices,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            RelayPallets::<T>::put(indices);
+            Self::deposit_event(Event::RelayPalletsSet(indices));
+            Ok(())
+        }
+
+        /// Record the outcome of the operation reported by `query_id`. Dispatched by
+        /// `PolkadotXcm` when the relay chain reports it.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn operation_result(
+            origin: OriginFor<T>,
+            query_id: QueryId,
+            response: Response,
+        ) -> DispatchResult {
+            pallet_xcm::ensure_response(<T as Config>::Origin::from(origin))?;
+            let result = match response {
+                Response::ExecutionResult(result) => result,
+                _ => return Err(Error::<T>::UnexpectedResponse.into()),
+            };
+            let (operation, _) =
+                PendingOperations::<T>::take(query_id).ok_or(Error::<T>::UnknownOperation)?;
+
+            match result {
+                None => Self::deposit_event(Event::OperationExecuted(query_id, operation)),
+                Some((_, error)) => {
+                    Self::deposit_event(Event::OperationFailed(query_id, operation, error))
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Our sovereign account on the relay chain.
+    pub fn sovereign_account() -> AccountId32 {
+        T::SelfParaId::get().into_account_truncating()
+    }
+
+    /// This chain as seen from the relay chain.
+    fn self_location() -> MultiLocation {
+        Parachain(T::SelfParaId::get().into()).into()
+    }
+
+    /// The encoded `Staking` call `call_index` with `args`.
+    fn staking_call(call_index: u8, args: impl Encode) -> Result<Vec<u8>, DispatchError> {
+        let staking = Self::relay_pallets()
+            .staking
+            .ok_or(Error::<T>::StakingUnavailable)?;
+        Ok((staking, call_index, args).encode())
+    }
+
+    /// The instructions dispatching the relay chain `call` from our sovereign account.
+    fn transact(call: Vec<u8>) -> Vec<Instruction<()>> {
+        let fees: MultiAsset = (MultiLocation::here(), T::RelayFee::get()).into();
+        vec![
+            WithdrawAsset(fees.clone().into()),
+            BuyExecution {
+                fees,
+                weight_limit: Unlimited,
+            },
+            Transact {
+                origin_type: OriginKind::SovereignAccount,
+                require_weight_at_most: T::RelayCallWeight::get(),
+                call: call.into(),
+            },
+            RefundSurplus,
+            DepositAsset {
+                assets: Wild(All),
+                max_assets: 1,
+                beneficiary: Self::self_location(),
+            },
+        ]
+    }
+
+    /// Send `instructions` to the relay chain, asking it to report their outcome.
+    fn send_operation(
+        operation: RelayOperation<T::AccountId>,
+        instructions: Vec<Instruction<()>>,
+    ) -> DispatchResult {
+        let notify = Call::<T>::operation_result {
+            query_id: 0,
+            response: Response::Null,
+        };
+        let max_response_weight = notify.get_dispatch_info().weight;
+        let timeout = frame_system::Pallet::<T>::block_number() + T::ResultTimeout::get();
+        let query_id = pallet_xcm::Pallet::<T>::new_notify_query(
+            MultiLocation::parent(),
+            <T as Config>::Call::from(notify),
+            timeout,
+        );
+
+        let report = ReportError {
+            query_id,
+            dest: Self::self_location(),
+            max_response_weight,
+        };
+        let mut message = vec![SetAppendix(Xcm(vec![report]))];
+        message.extend(instructions);
+        T::XcmSender::send_xcm(MultiLocation::parent(), Xcm(message))
+            .map_err(|_| Error::<T>::SendFailure)?;
+
+        PendingOperations::<T>::insert(query_id, (&operation, timeout));
+        // Queries of other pallets between ours are not walked through once nothing is pending.
+        if ExpiryCursor::<T>::get() == ExpiryEnd::<T>::get() {
+            ExpiryCursor::<T>::put(query_id);
+        }
+        ExpiryEnd::<T>::put(query_id.wrapping_add(1));
+        Self::deposit_event(Event::OperationSent(query_id, operation));
+        Ok(())
+    }
+
+    /// Drop the operations whose outcome is no longer expected, in the order they were sent,
+    /// within `weight_limit`.
+    fn expire_operations(weight_limit: Weight) -> Weight {
+        let step = 10_000 + T::DbWeight::get().reads_writes(1, 1);
+        let mut weight_used = T::DbWeight::get().reads_writes(2, 1);
+        if weight_limit < weight_used {
+            return 0;
+        }
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let end = ExpiryEnd::<T>::get();
+        let mut cursor = ExpiryCursor::<T>::get();
+        let start = cursor;
+        while cursor != end && weight_used.saturating_add(step) <= weight_limit {
+            weight_used = weight_used.saturating_add(step);
+            match PendingOperations::<T>::get(cursor) {
+                // Operations are sent in the order of their queries, so the following ones time
+                // out no earlier while `ResultTimeout` is unchanged.
+                Some((_, timeout)) if timeout >= now => break,
+                Some((operation, _)) => {
+                    PendingOperations::<T>::remove(cursor);
+                    Self::deposit_event(Event::OperationTimedOut(cursor, operation));
+                }
+                // Already reported, or the query of another pallet.
+                None => {}
+            }
+            cursor = cursor.wrapping_add(1);
+        }
+        if cursor != start {
+            ExpiryCursor::<T>::put(cursor);
+        }
+        weight_used
+    }
+}
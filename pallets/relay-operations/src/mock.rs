@@ -0,0 +1,144 @@
+use crate as pallet_relay_operations;
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    parameter_types,
+    traits::{Everything, Nothing},
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, Convert, IdentityLookup},
+};
+use sp_std::cell::RefCell;
+use xcm::latest::prelude::*;
+use xcm_builder::{EnsureXcmOrigin, FixedWeightBounds, LocationInverter};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        PolkadotXcm: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+        RelayOperations: pallet_relay_operations::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+/// The id of the mock parachain.
+pub const SELF_PARA_ID: u32 = 1000;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub SelfParaId: ParaId = ParaId::new(SELF_PARA_ID);
+    pub Ancestry: MultiLocation = Parachain(SELF_PARA_ID).into();
+    pub const UnitWeightCost: Weight = 10;
+    pub const MaxInstructions: u32 = 100;
+    pub const RelayFee: u128 = 1_000;
+    pub const RelayCallWeight: Weight = 1_000_000;
+    pub const ResultTimeout: u64 = 10;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+thread_local! {
+    pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent so far, with their destination.
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+    SENT.with(|s| s.borrow().clone())
+}
+
+/// Records the messages instead of sending them.
+pub struct RecordingSender;
+impl SendXcm for RecordingSender {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        SENT.with(|s| s.borrow_mut().push((dest.into(), message)));
+        Ok(())
+    }
+}
+
+impl pallet_xcm::Config for Test {
+    type Event = Event;
+    type SendXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+    type XcmRouter = RecordingSender;
+    type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, ()>;
+    type XcmExecuteFilter = Nothing;
+    type XcmExecutor = ();
+    type XcmTeleportFilter = Nothing;
+    type XcmReserveTransferFilter = Nothing;
+    type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+    type LocationInverter = LocationInverter<Ancestry>;
+    type Origin = Origin;
+    type Call = Call;
+
+    const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+    type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+}
+
+pub struct AccountIndexLocation;
+impl Convert<u64, MultiLocation> for AccountIndexLocation {
+    fn convert(index: u64) -> MultiLocation {
+        AccountIndex64 {
+            network: Any,
+            index,
+        }
+        .into()
+    }
+}
+
+impl pallet_relay_operations::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type Call = Call;
+    type StakingOrigin = frame_system::EnsureRoot<u64>;
+    type CrowdloanOrigin = frame_system::EnsureRoot<u64>;
+    type TransferOrigin = frame_system::EnsureRoot<u64>;
+    type SelfParaId = SelfParaId;
+    type XcmSender = RecordingSender;
+    type AccountIdToMultiLocation = AccountIndexLocation;
+    type RelayFee = RelayFee;
+    type RelayCallWeight = RelayCallWeight;
+    type ResultTimeout = ResultTimeout;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
@@ -0,0 +1,275 @@
+use crate::{mock::*, Error, Event as OperationsEvent, RelayOperation, RelayPalletIndices};
+use codec::{Compact, Encode};
+use cumulus_primitives_core::ParaId;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight};
+use sp_runtime::{AccountId32, MultiAddress};
+use xcm::latest::prelude::*;
+
+const STAKING: u8 = 6;
+
+fn enable_staking() {
+    assert_ok!(RelayOperations::set_relay_pallets(
+        Origin::root(),
+        RelayPalletIndices {
+            staking: Some(STAKING),
+            crowdloan: Some(73),
+        }
+    ));
+}
+
+fn self_location() -> MultiLocation {
+    Parachain(SELF_PARA_ID).into()
+}
+
+/// The error report appended to every operation.
+fn report(query_id: QueryId) -> Instruction<()> {
+    SetAppendix(Xcm(vec![ReportError {
+        query_id,
+        dest: self_location(),
+        max_response_weight: 10_000,
+    }]))
+}
+
+fn response_origin() -> Origin {
+    pallet_xcm::Origin::Response(MultiLocation::parent()).into()
+}
+
+#[test]
+fn bond_transacts_from_the_sovereign_account_and_reports_the_outcome() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        enable_staking();
+        assert_ok!(RelayOperations::bond(Origin::root(), 5_000));
+
+        let sovereign_account = RelayOperations::sovereign_account();
+        let mut expected_account = [0u8; 32];
+        expected_account[..8].copy_from_slice(&(*b"para", SELF_PARA_ID).encode());
+        assert_eq!(sovereign_account, AccountId32::new(expected_account));
+
+        let call = (
+            STAKING,
+            0u8,
+            MultiAddress::<AccountId32, ()>::Id(sovereign_account),
+            Compact(5_000u128),
+            0u8,
+        )
+            .encode();
+        let fees: MultiAsset = (MultiLocation::here(), 1_000).into();
+        assert_eq!(
+            sent(),
+            vec![(
+                MultiLocation::parent(),
+                Xcm(vec![
+                    report(0),
+                    WithdrawAsset(fees.clone().into()),
+                    BuyExecution {
+                        fees,
+                        weight_limit: Unlimited,
+                    },
+                    Transact {
+                        origin_type: OriginKind::SovereignAccount,
+                        require_weight_at_most: 1_000_000,
+                        call: call.into(),
+                    },
+                    RefundSurplus,
+                    DepositAsset {
+                        assets: Wild(All),
+                        max_assets: 1,
+                        beneficiary: self_location(),
+                    },
+                ])
+            )]
+        );
+        assert_eq!(
+            RelayOperations::pending_operations(0),
+            Some((RelayOperation::Bond(5_000), 11))
+        );
+        System::assert_last_event(
+            OperationsEvent::OperationSent(0, RelayOperation::Bond(5_000)).into(),
+        );
+    });
+}
+
+#[test]
+fn reported_outcomes_are_recorded() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        enable_staking();
+        assert_ok!(RelayOperations::chill(Origin::root()));
+        assert_ok!(RelayOperations::contribute(
+            Origin::root(),
+            ParaId::new(2000),
+            100
+        ));
+
+        assert_noop!(
+            RelayOperations::operation_result(Origin::root(), 0, Response::ExecutionResult(None)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(RelayOperations::operation_result(
+            response_origin(),
+            0,
+            Response::ExecutionResult(None)
+        ));
+        System::assert_last_event(
+            OperationsEvent::OperationExecuted(0, RelayOperation::Chill).into(),
+        );
+
+        assert_ok!(RelayOperations::operation_result(
+            response_origin(),
+            1,
+            Response::ExecutionResult(Some((2, XcmError::FailedToTransactAsset(""))))
+        ));
+        System::assert_last_event(
+            OperationsEvent::OperationFailed(
+                1,
+                RelayOperation::Contribute(ParaId::new(2000), 100),
+                XcmError::FailedToTransactAsset(""),
+            )
+            .into(),
+        );
+        assert_eq!(RelayOperations::pending_operations(1), None);
+
+        assert_noop!(
+            RelayOperations::operation_result(
+                response_origin(),
+                1,
+                Response::ExecutionResult(None)
+            ),
+            Error::<Test>::UnknownOperation
+        );
+    });
+}
+
+#[test]
+fn unreported_operations_time_out() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(RelayOperations::contribute(
+            Origin::root(),
+            ParaId::new(2000),
+            100
+        ));
+        System::set_block_number(5);
+        assert_ok!(RelayOperations::contribute(
+            Origin::root(),
+            ParaId::new(3000),
+            100
+        ));
+
+        System::set_block_number(11);
+        RelayOperations::on_idle(11, Weight::MAX);
+        assert!(RelayOperations::pending_operations(0).is_some());
+
+        System::set_block_number(12);
+        RelayOperations::on_idle(12, Weight::MAX);
+        assert_eq!(RelayOperations::pending_operations(0), None);
+        System::assert_last_event(
+            OperationsEvent::OperationTimedOut(
+                0,
+                RelayOperation::Contribute(ParaId::new(2000), 100),
+            )
+            .into(),
+        );
+        // The operation sent later is still expected.
+        assert!(RelayOperations::pending_operations(1).is_some());
+
+        // A late report is refused.
+        assert_noop!(
+            RelayOperations::operation_result(
+                response_origin(),
+                0,
+                Response::ExecutionResult(None)
+            ),
+            Error::<Test>::UnknownOperation
+        );
+
+        System::set_block_number(16);
+        // Not enough weight to look at the operation.
+        assert_eq!(RelayOperations::on_idle(16, 5_000), 0);
+        assert!(RelayOperations::pending_operations(1).is_some());
+        RelayOperations::on_idle(16, Weight::MAX);
+        assert_eq!(RelayOperations::pending_operations(1), None);
+    });
+}
+
+#[test]
+fn operations_are_gated_and_need_the_relay_pallet() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            RelayOperations::bond(Origin::signed(1), 5_000),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        // `rococo-local` has no staking.
+        assert_noop!(
+            RelayOperations::bond(Origin::root(), 5_000),
+            Error::<Test>::StakingUnavailable
+        );
+        assert_noop!(
+            RelayOperations::transfer_to_parachain(Origin::root(), 1, 2_000),
+            Error::<Test>::AmountTooLow
+        );
+        assert!(sent().is_empty());
+    });
+}
+
+#[test]
+fn transfer_to_parachain_deposits_back_to_the_beneficiary() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RelayOperations::transfer_to_parachain(
+            Origin::root(),
+            1,
+            10_000
+        ));
+
+        let (_, message) = sent().pop().unwrap();
+        assert_eq!(
+            message,
+            Xcm(vec![
+                report(0),
+                WithdrawAsset((MultiLocation::here(), 10_000).into()),
+                BuyExecution {
+                    fees: (MultiLocation::here(), 1_000).into(),
+                    weight_limit: Unlimited,
+                },
+                DepositReserveAsset {
+                    assets: Wild(All),
+                    max_assets: 1,
+                    dest: self_location(),
+                    xcm: Xcm(vec![
+                        BuyExecution {
+                            fees: (MultiLocation::parent(), 1_000).into(),
+                            weight_limit: Unlimited,
+                        },
+                        DepositAsset {
+                            assets: Wild(All),
+                            max_assets: 1,
+                            beneficiary: AccountIndex64 {
+                                network: Any,
+                                index: 1,
+                            }
+                            .into(),
+                        },
+                    ]),
+                },
+            ])
+        );
+    });
+}
+
+#[test]
+fn relay_pallets_are_known_for_each_relay_chain() {
+    assert_eq!(
+        RelayPalletIndices::for_relay_chain("rococo-local"),
+        Some(RelayPalletIndices::default())
+    );
+    assert_eq!(
+        RelayPalletIndices::for_relay_chain("kusama-dev"),
+        Some(RelayPalletIndices {
+            staking: Some(6),
+            crowdloan: Some(73),
+        })
+    );
+    assert_eq!(RelayPalletIndices::for_relay_chain("unknown"), None);
+}
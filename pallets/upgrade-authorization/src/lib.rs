@@ -0,0 +1,65 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime upgrade authorization by a configurable origin.
+//!
+//! `cumulus_pallet_parachain_system::authorize_upgrade` can only be called by root. This pallet
+//! lets `Config::AuthorizeOrigin`, e.g. relay chain governance, authorize an upgrade too. The
+//! upgrade is then enacted as usual with `ParachainSystem::enact_authorized_upgrade`.
+
+use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::{pallet_prelude::*, RawOrigin};
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + cumulus_pallet_parachain_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to authorize runtime upgrades.
+        type AuthorizeOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// An upgrade to the runtime with this code hash was authorized. [code_hash]
+        UpgradeAuthorized(T::Hash),
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Authorize an upgrade to the runtime whose code hashes to `code_hash`, as root would
+        /// with `ParachainSystem::authorize_upgrade`.
+        #[pallet::weight(1_000_000 + T::DbWeight::get().writes(1))]
+        pub fn authorize_upgrade(
+            origin: OriginFor<T>,
+            code_hash: T::Hash,
+        ) -> DispatchResultWithPostInfo {
+            T::AuthorizeOrigin::ensure_origin(origin)?;
+
+            cumulus_pallet_parachain_system::Pallet::<T>::authorize_upgrade(
+                RawOrigin::Root.into(),
+                code_hash,
+            )?;
+            Self::deposit_event(Event::UpgradeAuthorized(code_hash));
+            Ok(().into())
+        }
+    }
+}
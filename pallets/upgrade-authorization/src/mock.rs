@@ -0,0 +1,111 @@
+use crate as pallet_upgrade_authorization;
+use cumulus_pallet_parachain_system::RelayNumberStrictlyIncreases;
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    parameter_types,
+    traits::{EnsureOrigin, Everything},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        ParachainSystem: cumulus_pallet_parachain_system::{
+            Pallet, Call, Config, Storage, Inherent, Event<T>, ValidateUnsigned,
+        },
+        UpgradeAuthorization: pallet_upgrade_authorization::{Pallet, Call, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub const ParachainId: ParaId = ParaId::new(1000);
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = cumulus_pallet_parachain_system::ParachainSetCode<Self>;
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl cumulus_pallet_parachain_system::Config for Test {
+    type Event = Event;
+    type OnSystemEvent = ();
+    type SelfParaId = ParachainId;
+    type OutboundXcmpMessageSource = ();
+    type DmpMessageHandler = ();
+    type ReservedDmpWeight = ();
+    type XcmpMessageHandler = ();
+    type ReservedXcmpWeight = ();
+    type CheckAssociatedRelayNumber = RelayNumberStrictlyIncreases;
+}
+
+/// The account standing in for relay chain governance.
+pub const GOVERNANCE: u64 = 42;
+
+/// Only accepts `GOVERNANCE`.
+pub struct EnsureGovernance;
+impl EnsureOrigin<Origin> for EnsureGovernance {
+    type Success = ();
+
+    fn try_origin(o: Origin) -> Result<(), Origin> {
+        let raw: Result<system::RawOrigin<u64>, Origin> = o.clone().into();
+        match raw {
+            Ok(system::RawOrigin::Signed(GOVERNANCE)) => Ok(()),
+            _ => Err(o),
+        }
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> Origin {
+        Origin::signed(GOVERNANCE)
+    }
+}
+
+impl pallet_upgrade_authorization::Config for Test {
+    type Event = Event;
+    type AuthorizeOrigin = EnsureGovernance;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
@@ -0,0 +1,32 @@
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok};
+use sp_core::H256;
+use sp_runtime::DispatchError;
+
+#[test]
+fn authorize_origin_can_authorize_upgrade() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let code_hash = H256::repeat_byte(1);
+
+        assert_ok!(UpgradeAuthorization::authorize_upgrade(
+            Origin::signed(GOVERNANCE),
+            code_hash
+        ));
+        System::assert_last_event(crate::Event::UpgradeAuthorized(code_hash).into());
+    });
+}
+
+#[test]
+fn other_origins_cannot_authorize_upgrade() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            UpgradeAuthorization::authorize_upgrade(Origin::signed(1), H256::repeat_byte(1)),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            UpgradeAuthorization::authorize_upgrade(Origin::root(), H256::repeat_byte(1)),
+            DispatchError::BadOrigin
+        );
+    });
+}
@@ -0,0 +1,190 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Emergency switches for XCM.
+//!
+//! [`PausableExecutor`] wraps the XCM executor of the inbound queues. While inbound XCM is
+//! paused it executes nothing and reports every message as too heavy for now, so `XcmpQueue` and
+//! `DmpQueue` keep them queued until execution resumes. [`PausableRouter`] wraps the XCM router
+//! and fails every send while outbound XCM is paused.
+//!
+//! A pause lasts `PauseDuration` blocks and then lifts by itself, unless it is renewed by
+//! pausing again. It can also be lifted early.
+
+use frame_support::{pallet_prelude::*, weights::Weight};
+use sp_std::marker::PhantomData;
+use xcm::latest::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to pause and resume XCM.
+        type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+        /// The number of blocks a pause lasts unless renewed.
+        #[pallet::constant]
+        type PauseDuration: Get<Self::BlockNumber>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The block at which inbound XCM execution resumes, if paused.
+    #[pallet::storage]
+    #[pallet::getter(fn inbound_paused_until)]
+    pub type InboundPausedUntil<T: Config> = StorageValue<_, T::BlockNumber>;
+
+    /// The block at which outbound XCM sends resume, if paused.
+    #[pallet::storage]
+    #[pallet::getter(fn outbound_paused_until)]
+    pub type OutboundPausedUntil<T: Config> = StorageValue<_, T::BlockNumber>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Inbound XCM execution was paused. [until]
+        InboundPaused(T::BlockNumber),
+        /// Inbound XCM execution resumed.
+        InboundResumed,
+        /// Outbound XCM sends were paused. [until]
+        OutboundPaused(T::BlockNumber),
+        /// Outbound XCM sends resumed.
+        OutboundResumed,
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The switch is not paused.
+        NotPaused,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let mut writes = 0;
+            if InboundPausedUntil::<T>::get().map_or(false, |until| until <= now) {
+                InboundPausedUntil::<T>::kill();
+                Self::deposit_event(Event::InboundResumed);
+                writes += 1;
+            }
+            if OutboundPausedUntil::<T>::get().map_or(false, |until| until <= now) {
+                OutboundPausedUntil::<T>::kill();
+                Self::deposit_event(Event::OutboundResumed);
+                writes += 1;
+            }
+            T::DbWeight::get().reads_writes(2, writes)
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Pause the execution of inbound XCM for `PauseDuration` blocks, or renew the pause.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn pause_inbound(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            let until = Self::pause_end();
+            InboundPausedUntil::<T>::put(until);
+            Self::deposit_event(Event::InboundPaused(until));
+            Ok(())
+        }
+
+        /// Resume the execution of inbound XCM.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn resume_inbound(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+            InboundPausedUntil::<T>::take().ok_or(Error::<T>::NotPaused)?;
+
+            Self::deposit_event(Event::InboundResumed);
+            Ok(())
+        }
+
+        /// Pause outbound XCM sends for `PauseDuration` blocks, or renew the pause.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn pause_outbound(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            let until = Self::pause_end();
+            OutboundPausedUntil::<T>::put(until);
+            Self::deposit_event(Event::OutboundPaused(until));
+            Ok(())
+        }
+
+        /// Resume outbound XCM sends.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn resume_outbound(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+            OutboundPausedUntil::<T>::take().ok_or(Error::<T>::NotPaused)?;
+
+            Self::deposit_event(Event::OutboundResumed);
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Whether inbound XCM execution is paused.
+    pub fn inbound_paused() -> bool {
+        Self::is_paused(InboundPausedUntil::<T>::get())
+    }
+
+    /// Whether outbound XCM sends are paused.
+    pub fn outbound_paused() -> bool {
+        Self::is_paused(OutboundPausedUntil::<T>::get())
+    }
+
+    fn is_paused(until: Option<T::BlockNumber>) -> bool {
+        until.map_or(false, |until| {
+            frame_system::Pallet::<T>::block_number() < until
+        })
+    }
+
+    fn pause_end() -> T::BlockNumber {
+        frame_system::Pallet::<T>::block_number().saturating_add(T::PauseDuration::get())
+    }
+}
+
+/// Executes messages with `Inner` unless inbound XCM is paused.
+pub struct PausableExecutor<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T: Config, Inner: ExecuteXcm<Call>, Call> ExecuteXcm<Call> for PausableExecutor<T, Inner> {
+    fn execute_xcm_in_credit(
+        origin: impl Into<MultiLocation>,
+        message: Xcm<Call>,
+        weight_limit: Weight,
+        weight_credit: Weight,
+    ) -> Outcome {
+        if Pallet::<T>::inbound_paused() {
+            // The queues keep messages reported as too heavy to retry them later. Requiring no
+            // weight keeps them from being moved to the overweight queues instead.
+            return Outcome::Error(XcmError::WeightLimitReached(0));
+        }
+        Inner::execute_xcm_in_credit(origin, message, weight_limit, weight_credit)
+    }
+}
+
+/// Sends messages with `Inner` unless outbound XCM is paused.
+pub struct PausableRouter<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T: Config, Inner: SendXcm> SendXcm for PausableRouter<T, Inner> {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        if Pallet::<T>::outbound_paused() {
+            return Err(SendError::Transport("Outbound XCM is paused"));
+        }
+        Inner::send_xcm(dest, message)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{self as pallet_xcm_circuit_breaker, PausableExecutor, PausableRouter};
+use frame_support::{parameter_types, traits::Everything, weights::Weight};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        XcmCircuitBreaker: pallet_xcm_circuit_breaker::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub const PauseDuration: u64 = 10;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_xcm_circuit_breaker::Config for Test {
+    type Event = Event;
+    type PauseOrigin = frame_system::EnsureRoot<u64>;
+    type PauseDuration = PauseDuration;
+}
+
+/// Sends everything.
+pub struct AcceptingRouter;
+impl SendXcm for AcceptingRouter {
+    fn send_xcm(_dest: impl Into<MultiLocation>, _message: Xcm<()>) -> SendResult {
+        Ok(())
+    }
+}
+
+/// Every message completes using 10.
+pub struct CompletingExecutor;
+impl ExecuteXcm<()> for CompletingExecutor {
+    fn execute_xcm_in_credit(
+        _origin: impl Into<MultiLocation>,
+        _message: Xcm<()>,
+        _weight_limit: Weight,
+        _weight_credit: Weight,
+    ) -> Outcome {
+        Outcome::Complete(10)
+    }
+}
+
+pub type Router = PausableRouter<Test, AcceptingRouter>;
+pub type Executor = PausableExecutor<Test, CompletingExecutor>;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
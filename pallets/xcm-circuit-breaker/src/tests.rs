@@ -0,0 +1,75 @@
+use crate::{mock::*, Error, Event as CircuitBreakerEvent};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use xcm::latest::prelude::*;
+
+fn execute() -> Outcome {
+    Executor::execute_xcm(MultiLocation::parent(), Xcm(vec![ClearOrigin]), 100)
+}
+
+fn send() -> SendResult {
+    Router::send_xcm(MultiLocation::parent(), Xcm(vec![ClearOrigin]))
+}
+
+fn run_to_block(n: u64) {
+    System::set_block_number(n);
+    XcmCircuitBreaker::on_initialize(n);
+}
+
+#[test]
+fn paused_inbound_messages_stay_queued_until_the_pause_expires() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_eq!(execute(), Outcome::Complete(10));
+
+        assert_ok!(XcmCircuitBreaker::pause_inbound(Origin::root()));
+        System::assert_last_event(CircuitBreakerEvent::InboundPaused(11).into());
+        assert_eq!(execute(), Outcome::Error(XcmError::WeightLimitReached(0)));
+        // Sends are not affected.
+        assert_eq!(send(), Ok(()));
+
+        run_to_block(10);
+        assert!(XcmCircuitBreaker::inbound_paused());
+        run_to_block(11);
+        assert!(!XcmCircuitBreaker::inbound_paused());
+        assert_eq!(XcmCircuitBreaker::inbound_paused_until(), None);
+        System::assert_last_event(CircuitBreakerEvent::InboundResumed.into());
+        assert_eq!(execute(), Outcome::Complete(10));
+    });
+}
+
+#[test]
+fn pauses_can_be_renewed_and_lifted() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(XcmCircuitBreaker::pause_outbound(Origin::root()));
+        assert_eq!(send(), Err(SendError::Transport("Outbound XCM is paused")));
+
+        run_to_block(8);
+        assert_ok!(XcmCircuitBreaker::pause_outbound(Origin::root()));
+        run_to_block(12);
+        assert!(XcmCircuitBreaker::outbound_paused());
+        assert_eq!(execute(), Outcome::Complete(10));
+
+        assert_ok!(XcmCircuitBreaker::resume_outbound(Origin::root()));
+        System::assert_last_event(CircuitBreakerEvent::OutboundResumed.into());
+        assert_eq!(send(), Ok(()));
+        assert_noop!(
+            XcmCircuitBreaker::resume_outbound(Origin::root()),
+            Error::<Test>::NotPaused
+        );
+    });
+}
+
+#[test]
+fn only_the_pause_origin_can_pause() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            XcmCircuitBreaker::pause_inbound(Origin::signed(1)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            XcmCircuitBreaker::pause_outbound(Origin::signed(1)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
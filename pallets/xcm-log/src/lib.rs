@@ -0,0 +1,284 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A bounded log of the XCM messages sent and executed by this chain.
+//!
+//! [`LoggingRouter`] wraps the XCM router and logs every message sent with the result of sending
+//! it. [`LoggingExecutor`] wraps the XCM executor of the inbound queues and logs every message
+//! executed with its outcome and the weight it used, charging the write of the entry on top.
+//! [`LoggingWeigher`] charges the entries of the messages sent while executing a message. Only
+//! the last `MaxEntries` messages are kept, and only those not longer than `MaxMessageSize` with
+//! their content.
+//!
+//! Messages are identified by the `blake2_256` hash of their encoding as a `VersionedXcm`, the
+//! same hash `XcmpQueue` reports in its events. Sent messages are hashed in the version
+//! `VersionWrapper` sends them in, and executed messages in the latest version, so a message sent
+//! in the latest version is logged under the same hash on both chains and can be traced end to
+//! end.
+
+use codec::{Decode, Encode};
+use frame_support::{pallet_prelude::*, weights::Weight, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_std::{marker::PhantomData, vec::Vec};
+use xcm::{latest::prelude::*, VersionedXcm, WrapVersion};
+use xcm_executor::traits::WeightBounds;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Where a logged message went or came from, and what happened to it.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum LoggedMessage {
+    /// A message executed here.
+    Inbound {
+        origin: MultiLocation,
+        outcome: Outcome,
+    },
+    /// A message sent from here.
+    Outbound {
+        dest: MultiLocation,
+        result: Result<(), SendError>,
+    },
+}
+
+/// A message in the log.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct LogEntry<BlockNumber> {
+    /// The position of the message among all messages ever logged.
+    pub id: u64,
+    /// The `blake2_256` hash of the message as a `VersionedXcm`.
+    pub hash: [u8; 32],
+    /// The message, unless its encoding is longer than `MaxMessageSize`.
+    pub message: Option<Xcm<()>>,
+    /// Whether the message was sent or executed, and what happened to it.
+    pub kind: LoggedMessage,
+    /// The weight used to execute and log the message, zero for sent messages.
+    pub weight_used: Weight,
+    /// The block the message was sent or executed in.
+    pub block: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The number of messages kept in the log.
+        #[pallet::constant]
+        type MaxEntries: Get<u32>;
+
+        /// The length of the longest encoded message whose content is kept in the log.
+        #[pallet::constant]
+        type MaxMessageSize: Get<u32>;
+
+        /// Wraps sent messages in the version they are sent in, as the router does.
+        type VersionWrapper: WrapVersion;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The logged messages, in a ring buffer of `MaxEntries` slots.
+    #[pallet::storage]
+    pub type Entries<T: Config> = StorageMap<_, Twox64Concat, u32, LogEntry<T::BlockNumber>>;
+
+    /// The number of messages ever logged.
+    #[pallet::storage]
+    #[pallet::getter(fn entry_count)]
+    pub type EntryCount<T> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {}
+}
+
+impl<T: Config> Pallet<T> {
+    /// The last `count` messages logged, most recent first.
+    pub fn recent_entries(count: u32) -> Vec<LogEntry<T::BlockNumber>> {
+        let total = EntryCount::<T>::get();
+        let count = (count as u64).min(total).min(T::MaxEntries::get() as u64);
+        (1..=count)
+            .filter_map(|back| Entries::<T>::get(Self::slot(total - back)))
+            .collect()
+    }
+
+    /// The logged messages with the hash `hash`, oldest first.
+    pub fn entries_with_hash(hash: [u8; 32]) -> Vec<LogEntry<T::BlockNumber>> {
+        let mut entries: Vec<_> = Entries::<T>::iter_values()
+            .filter(|entry| entry.hash == hash)
+            .collect();
+        entries.sort_by_key(|entry| entry.id);
+        entries
+    }
+
+    /// The messages logged from the entry `id` on which are still kept, oldest first.
+    pub fn entries_since(id: u64) -> Vec<LogEntry<T::BlockNumber>> {
+        let total = EntryCount::<T>::get();
+        let first = id.max(total.saturating_sub(T::MaxEntries::get() as u64));
+        (first..total)
+            .filter_map(|id| Entries::<T>::get(Self::slot(id)))
+            .collect()
+    }
+
+    /// The hash of the last message logged, if it was sent from here successfully.
+    pub fn last_sent_hash() -> Option<[u8; 32]> {
+        let id = EntryCount::<T>::get().checked_sub(1)?;
+        Entries::<T>::get(Self::slot(id))
+            .filter(|entry| matches!(entry.kind, LoggedMessage::Outbound { result: Ok(()), .. }))
+            .map(|entry| entry.hash)
+    }
+
+    /// The weight of logging a message.
+    pub fn log_weight() -> Weight {
+        T::DbWeight::get().reads_writes(1, 2)
+    }
+
+    fn slot(id: u64) -> u32 {
+        (id % T::MaxEntries::get().max(1) as u64) as u32
+    }
+
+    fn log(message: Xcm<()>, hash: [u8; 32], kind: LoggedMessage, weight_used: Weight) {
+        let id = EntryCount::<T>::get();
+        let message = if message.encoded_size() <= T::MaxMessageSize::get() as usize {
+            Some(message)
+        } else {
+            None
+        };
+        let entry = LogEntry {
+            id,
+            hash,
+            message,
+            kind,
+            weight_used,
+            block: frame_system::Pallet::<T>::block_number(),
+        };
+        Entries::<T>::insert(Self::slot(id), entry);
+        EntryCount::<T>::put(id + 1);
+    }
+}
+
+/// Logs the messages sent through `Inner`.
+pub struct LoggingRouter<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T: Config, Inner: SendXcm> SendXcm for LoggingRouter<T, Inner> {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        let dest = dest.into();
+        // Messages that can't be wrapped are not sent either, so any hash will do for them.
+        let hash = T::VersionWrapper::wrap_version(&dest, message.clone())
+            .unwrap_or_else(|()| VersionedXcm::from(message.clone()))
+            .using_encoded(sp_io::hashing::blake2_256);
+        let result = Inner::send_xcm(dest.clone(), message.clone());
+        Pallet::<T>::log(
+            message,
+            hash,
+            LoggedMessage::Outbound {
+                dest,
+                result: result.clone(),
+            },
+            0,
+        );
+        result
+    }
+}
+
+/// Gives the hash of the last message sent, if the last message logged was sent successfully.
+pub struct LastSentHash<T>(PhantomData<T>);
+
+impl<T: Config> Get<Option<[u8; 32]>> for LastSentHash<T> {
+    fn get() -> Option<[u8; 32]> {
+        Pallet::<T>::last_sent_hash()
+    }
+}
+
+/// Logs the messages executed by `Inner`, adding the weight of the log entry to their outcome.
+///
+/// Messages that didn't fit in the weight left are not logged, as the queues keep them to
+/// execute later. Messages that failed to execute are charged the weight of their entry.
+pub struct LoggingExecutor<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T: Config, Inner: ExecuteXcm<Call>, Call> ExecuteXcm<Call> for LoggingExecutor<T, Inner> {
+    fn execute_xcm_in_credit(
+        origin: impl Into<MultiLocation>,
+        message: Xcm<Call>,
+        weight_limit: Weight,
+        weight_credit: Weight,
+    ) -> Outcome {
+        let origin = origin.into();
+        let logged = Xcm::<()>::from(message.clone());
+        let hash = VersionedXcm::from(logged.clone()).using_encoded(sp_io::hashing::blake2_256);
+        let log_weight = Pallet::<T>::log_weight();
+        let outcome = match Inner::execute_xcm_in_credit(
+            origin.clone(),
+            message,
+            weight_limit.saturating_sub(log_weight),
+            weight_credit,
+        ) {
+            Outcome::Error(XcmError::WeightLimitReached(weight)) => {
+                return Outcome::Error(XcmError::WeightLimitReached(
+                    weight.saturating_add(log_weight),
+                ))
+            }
+            Outcome::Complete(weight) => Outcome::Complete(weight.saturating_add(log_weight)),
+            Outcome::Incomplete(weight, error) => {
+                Outcome::Incomplete(weight.saturating_add(log_weight), error)
+            }
+            Outcome::Error(error) => Outcome::Incomplete(log_weight, error),
+        };
+        Pallet::<T>::log(
+            logged,
+            hash,
+            LoggedMessage::Inbound {
+                origin,
+                outcome: outcome.clone(),
+            },
+            outcome.weight_used(),
+        );
+        outcome
+    }
+}
+
+/// Weighs messages with `Inner`, adding the weight of logging each message they may send.
+pub struct LoggingWeigher<T, Inner>(PhantomData<(T, Inner)>);
+
+impl<T: Config, Inner: WeightBounds<Call>, Call> WeightBounds<Call> for LoggingWeigher<T, Inner> {
+    fn weight(message: &mut Xcm<Call>) -> Result<Weight, ()> {
+        let log_weight = Pallet::<T>::log_weight().saturating_mul(sends(message));
+        Inner::weight(message).map(|weight| weight.saturating_add(log_weight))
+    }
+
+    fn instr_weight(instruction: &Instruction<Call>) -> Result<Weight, ()> {
+        let log_weight = Pallet::<T>::log_weight().saturating_mul(instruction_sends(instruction));
+        Inner::instr_weight(instruction).map(|weight| weight.saturating_add(log_weight))
+    }
+}
+
+/// The number of messages executing `message` may send.
+fn sends<Call>(message: &Xcm<Call>) -> Weight {
+    message.0.iter().map(instruction_sends).sum()
+}
+
+/// The number of messages executing `instruction` may send.
+fn instruction_sends<Call>(instruction: &Instruction<Call>) -> Weight {
+    match instruction {
+        TransferReserveAsset { .. }
+        | DepositReserveAsset { .. }
+        | InitiateReserveWithdraw { .. }
+        | InitiateTeleport { .. }
+        | QueryHolding { .. }
+        | ReportError { .. }
+        | SubscribeVersion { .. }
+        | UnsubscribeVersion => 1,
+        SetErrorHandler(xcm) | SetAppendix(xcm) => sends(xcm),
+        _ => 0,
+    }
+}
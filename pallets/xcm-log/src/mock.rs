@@ -0,0 +1,142 @@
+use crate::{self as pallet_xcm_log, LoggingExecutor, LoggingRouter, LoggingWeigher};
+use frame_support::{
+    parameter_types,
+    traits::Everything,
+    weights::{RuntimeDbWeight, Weight},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use xcm::{latest::prelude::*, VersionedXcm, WrapVersion};
+use xcm_executor::traits::WeightBounds;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        XcmLog: pallet_xcm_log::{Pallet, Storage},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub const MaxEntries: u32 = 3;
+    pub const MaxMessageSize: u32 = 10;
+    // Logging a message weighs 5.
+    pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = DbWeight;
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_xcm_log::Config for Test {
+    type MaxEntries = MaxEntries;
+    type MaxMessageSize = MaxMessageSize;
+    type VersionWrapper = RelayInV1;
+}
+
+/// Sends messages to the relay chain in XCM v1, and to other locations in the latest version.
+pub struct RelayInV1;
+impl WrapVersion for RelayInV1 {
+    fn wrap_version<Call>(
+        dest: &MultiLocation,
+        xcm: impl Into<VersionedXcm<Call>>,
+    ) -> Result<VersionedXcm<Call>, ()> {
+        let version = if *dest == MultiLocation::parent() {
+            1
+        } else {
+            xcm::latest::VERSION
+        };
+        xcm.into().into_version(version)
+    }
+}
+
+/// Reaches only the relay chain.
+pub struct RelayOnlyRouter;
+impl SendXcm for RelayOnlyRouter {
+    fn send_xcm(dest: impl Into<MultiLocation>, _message: Xcm<()>) -> SendResult {
+        let dest = dest.into();
+        if dest == MultiLocation::parent() {
+            Ok(())
+        } else {
+            Err(SendError::CannotReachDestination(dest, Xcm(vec![])))
+        }
+    }
+}
+
+/// Every instruction weighs 10, and fails if it doesn't fit in the limit.
+pub struct CountingExecutor;
+impl ExecuteXcm<()> for CountingExecutor {
+    fn execute_xcm_in_credit(
+        _origin: impl Into<MultiLocation>,
+        message: Xcm<()>,
+        weight_limit: Weight,
+        _weight_credit: Weight,
+    ) -> Outcome {
+        let weight = message.0.len() as Weight * 10;
+        if weight > weight_limit {
+            Outcome::Error(XcmError::WeightLimitReached(weight))
+        } else {
+            Outcome::Complete(weight)
+        }
+    }
+}
+
+/// Every instruction weighs 10.
+pub struct CountingWeigher;
+impl WeightBounds<()> for CountingWeigher {
+    fn weight(message: &mut Xcm<()>) -> Result<Weight, ()> {
+        Ok(message.0.len() as Weight * 10)
+    }
+
+    fn instr_weight(_instruction: &Instruction<()>) -> Result<Weight, ()> {
+        Ok(10)
+    }
+}
+
+pub type Router = LoggingRouter<Test, RelayOnlyRouter>;
+pub type Executor = LoggingExecutor<Test, CountingExecutor>;
+pub type Weigher = LoggingWeigher<Test, CountingWeigher>;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
@@ -0,0 +1,205 @@
+use crate::{mock::*, LoggedMessage};
+use codec::Encode;
+use frame_support::weights::Weight;
+use xcm::{latest::prelude::*, VersionedXcm};
+use xcm_executor::traits::WeightBounds;
+
+fn message(instructions: usize) -> Xcm<()> {
+    Xcm(vec![ClearOrigin; instructions])
+}
+
+fn hash(message: &Xcm<()>) -> [u8; 32] {
+    VersionedXcm::from(message.clone()).using_encoded(sp_io::hashing::blake2_256)
+}
+
+fn sibling(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+#[test]
+fn sent_messages_are_logged_with_the_send_result() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(5);
+        assert_eq!(
+            Router::send_xcm(MultiLocation::parent(), message(1)),
+            Ok(())
+        );
+        assert!(Router::send_xcm(sibling(2000), message(2)).is_err());
+
+        let entries = XcmLog::recent_entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].hash, hash(&message(2)));
+        assert_eq!(
+            entries[0].kind,
+            LoggedMessage::Outbound {
+                dest: sibling(2000),
+                result: Err(SendError::CannotReachDestination(
+                    sibling(2000),
+                    Xcm(vec![])
+                )),
+            }
+        );
+        assert_eq!(entries[1].message, Some(message(1)));
+        assert_eq!(
+            entries[1].kind,
+            LoggedMessage::Outbound {
+                dest: MultiLocation::parent(),
+                result: Ok(()),
+            }
+        );
+        assert_eq!(entries[1].block, 5);
+        // The last message could not be sent.
+        assert_eq!(XcmLog::last_sent_hash(), None);
+    });
+}
+
+#[test]
+fn executed_messages_are_logged_unless_deferred() {
+    new_test_ext().execute_with(|| {
+        // Logging the message weighs 5.
+        assert_eq!(
+            Executor::execute_xcm(sibling(2000), message(2), 100),
+            Outcome::Complete(25)
+        );
+        // Too heavy for now: the queue keeps it for later.
+        assert_eq!(
+            Executor::execute_xcm(sibling(2000), message(10), 100),
+            Outcome::Error(XcmError::WeightLimitReached(105))
+        );
+
+        let entries = XcmLog::entries_with_hash(hash(&message(2)));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].kind,
+            LoggedMessage::Inbound {
+                origin: sibling(2000),
+                outcome: Outcome::Complete(25),
+            }
+        );
+        assert_eq!(entries[0].weight_used, 25);
+        assert_eq!(XcmLog::entry_count(), 1);
+    });
+}
+
+#[test]
+fn only_the_last_entries_are_kept() {
+    new_test_ext().execute_with(|| {
+        for instructions in 1..=5 {
+            assert_eq!(
+                Router::send_xcm(MultiLocation::parent(), message(instructions)),
+                Ok(())
+            );
+        }
+
+        let ids: Vec<_> = XcmLog::recent_entries(10)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, vec![4, 3, 2]);
+        let ids: Vec<_> = XcmLog::entries_since(0)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert_eq!(XcmLog::entries_since(4).len(), 1);
+        assert!(XcmLog::entries_with_hash(hash(&message(1))).is_empty());
+        assert_eq!(XcmLog::entries_with_hash(hash(&message(5)))[0].id, 4);
+        assert_eq!(XcmLog::last_sent_hash(), Some(hash(&message(5))));
+    });
+}
+
+#[test]
+fn sent_messages_are_hashed_in_the_version_they_are_sent_in() {
+    new_test_ext().execute_with(|| {
+        let transact = Xcm(vec![Transact {
+            origin_type: OriginKind::Native,
+            require_weight_at_most: 1_000,
+            call: vec![0].into(),
+        }]);
+        assert_eq!(
+            Router::send_xcm(MultiLocation::parent(), transact.clone()),
+            Ok(())
+        );
+
+        let v1 = VersionedXcm::from(transact.clone())
+            .into_version(1)
+            .unwrap();
+        assert_eq!(
+            XcmLog::last_sent_hash(),
+            Some(v1.using_encoded(sp_io::hashing::blake2_256))
+        );
+        assert_ne!(XcmLog::last_sent_hash(), Some(hash(&transact)));
+    });
+}
+
+#[test]
+fn large_messages_are_logged_without_their_content() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            Router::send_xcm(MultiLocation::parent(), message(20)),
+            Ok(())
+        );
+
+        let entry = XcmLog::recent_entries(1).pop().unwrap();
+        assert_eq!(entry.hash, hash(&message(20)));
+        assert_eq!(entry.message, None);
+    });
+}
+
+#[test]
+fn failed_messages_are_charged_their_entry() {
+    pub struct FailingExecutor;
+    impl ExecuteXcm<()> for FailingExecutor {
+        fn execute_xcm_in_credit(
+            _origin: impl Into<MultiLocation>,
+            _message: Xcm<()>,
+            _weight_limit: Weight,
+            _weight_credit: Weight,
+        ) -> Outcome {
+            Outcome::Error(XcmError::Barrier)
+        }
+    }
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            crate::LoggingExecutor::<Test, FailingExecutor>::execute_xcm(
+                sibling(2000),
+                message(1),
+                100
+            ),
+            Outcome::Incomplete(5, XcmError::Barrier)
+        );
+        assert_eq!(XcmLog::entry_count(), 1);
+    });
+}
+
+#[test]
+fn messages_are_charged_the_entries_of_what_they_send() {
+    let mut message = Xcm(vec![
+        ClearOrigin,
+        InitiateTeleport {
+            assets: Wild(All),
+            dest: MultiLocation::parent(),
+            xcm: Xcm(vec![]),
+        },
+        SetErrorHandler(Xcm(vec![QueryHolding {
+            query_id: 0,
+            dest: MultiLocation::parent(),
+            assets: Wild(All),
+            max_response_weight: 0,
+        }])),
+    ]);
+    new_test_ext().execute_with(|| {
+        // 10 per instruction, and 5 per message sent.
+        assert_eq!(Weigher::weight(&mut message), Ok(40));
+        assert_eq!(Weigher::instr_weight(&message.0[0]), Ok(10));
+        assert_eq!(Weigher::instr_weight(&message.0[1]), Ok(15));
+        let report_error: Instruction<()> = ReportError {
+            query_id: 0,
+            dest: MultiLocation::parent(),
+            max_response_weight: 0,
+        };
+        assert_eq!(Weigher::instr_weight(&report_error), Ok(15));
+    });
+}
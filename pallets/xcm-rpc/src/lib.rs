@@ -0,0 +1,359 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Typed requests to sibling parachains, and their replies.
+//!
+//! Pallets implement [`RpcHandler`] for each kind of request they serve or send, and the runtime
+//! lists the handlers in `Handlers`. [`Pallet::send_request`] sends a request to a sibling as a
+//! `Transact` of `handle_request`, which routes it to the handler with the same id there. The
+//! handler's result is sent back as a `Transact` of `handle_response`, which is correlated with
+//! the pending request by its id and delivered to `RpcHandler::on_response`. Requests left without
+//! a response for `RequestTimeout` blocks are expired in `on_idle`, and delivered a
+//! [`RpcError::Timeout`] instead.
+//!
+//! Requests and replies are SCALE-encoded in versioned envelopes. Siblings must run this pallet
+//! at the same index, with the same handler ids and request and response types.
+
+use codec::{Decode, DecodeAll, Encode};
+use cumulus_pallet_xcm::{ensure_sibling_para, Origin as CumulusOrigin};
+use cumulus_primitives_core::ParaId;
+use frame_support::{pallet_prelude::*, weights::Weight};
+use frame_system::Config as SystemConfig;
+use scale_info::TypeInfo;
+use sp_std::{vec, vec::Vec};
+use xcm::latest::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The id of a request, unique among the requests sent by a chain.
+pub type RequestId = u64;
+
+/// The id requests are routed to their handler by.
+pub type HandlerId = [u8; 8];
+
+/// Why a request got no response.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum RpcError {
+    /// The sibling has no handler with the id of the request.
+    UnknownHandler,
+    /// The sibling could not decode the request.
+    BadRequest,
+    /// The handler of the sibling failed.
+    Failed(DispatchError),
+    /// The response could not be decoded.
+    BadResponse,
+    /// No response came in time.
+    Timeout,
+}
+
+/// A request, as sent to siblings.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum VersionedRequest {
+    #[codec(index = 1)]
+    V1 {
+        id: RequestId,
+        handler: HandlerId,
+        payload: Vec<u8>,
+    },
+}
+
+/// A response, as sent back by siblings.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum VersionedResponse {
+    #[codec(index = 1)]
+    V1 {
+        id: RequestId,
+        result: Result<Vec<u8>, RpcError>,
+    },
+}
+
+/// Serves and sends one kind of request.
+pub trait RpcHandler {
+    /// The id requests of this kind are routed by, unique in the runtime.
+    const ID: HandlerId;
+
+    /// The request.
+    type Request: Encode + Decode;
+
+    /// The response to the request.
+    type Response: Encode + Decode;
+
+    /// Serve `request`, sent by the sibling `origin`.
+    fn handle_request(
+        origin: ParaId,
+        request: Self::Request,
+    ) -> Result<Self::Response, DispatchError>;
+
+    /// Receive the response to the request `id` sent to the sibling `dest`.
+    fn on_response(id: RequestId, dest: ParaId, response: Result<Self::Response, RpcError>);
+}
+
+/// Routes encoded requests and responses to the [`RpcHandler`] with their id. Implemented for
+/// tuples of handlers.
+pub trait RpcHandlers {
+    /// Serve the encoded request `payload` with the handler `handler`, or return `None` if
+    /// there is no such handler.
+    fn handle_request(
+        handler: HandlerId,
+        origin: ParaId,
+        payload: &[u8],
+    ) -> Option<Result<Vec<u8>, RpcError>>;
+
+    /// Deliver the encoded response to the request `id` to the handler `handler`. Returns
+    /// whether there is such a handler.
+    fn on_response(
+        handler: HandlerId,
+        id: RequestId,
+        dest: ParaId,
+        response: Result<Vec<u8>, RpcError>,
+    ) -> bool;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+#[tuple_types_custom_trait_bound(RpcHandler)]
+impl RpcHandlers for Tuple {
+    fn handle_request(
+        handler: HandlerId,
+        origin: ParaId,
+        payload: &[u8],
+    ) -> Option<Result<Vec<u8>, RpcError>> {
+        for_tuples!( #(
+            if Tuple::ID == handler {
+                return Some(serve::<Tuple>(origin, payload));
+            }
+        )* );
+        None
+    }
+
+    fn on_response(
+        handler: HandlerId,
+        id: RequestId,
+        dest: ParaId,
+        response: Result<Vec<u8>, RpcError>,
+    ) -> bool {
+        for_tuples!( #(
+            if Tuple::ID == handler {
+                let response = response.and_then(|encoded| {
+                    Tuple::Response::decode_all(&mut &encoded[..])
+                        .map_err(|_| RpcError::BadResponse)
+                });
+                Tuple::on_response(id, dest, response);
+                return true;
+            }
+        )* );
+        false
+    }
+}
+
+fn serve<H: RpcHandler>(origin: ParaId, payload: &[u8]) -> Result<Vec<u8>, RpcError> {
+    let request = H::Request::decode_all(&mut &payload[..]).map_err(|_| RpcError::BadRequest)?;
+    H::handle_request(origin, request)
+        .map(|response| response.encode())
+        .map_err(RpcError::Failed)
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        type Origin: From<<Self as SystemConfig>::Origin>
+            + Into<Result<CumulusOrigin, <Self as Config>::Origin>>;
+
+        type Call: From<Call<Self>> + Encode;
+
+        type XcmSender: SendXcm;
+
+        /// The handlers of the requests this chain serves and sends.
+        type Handlers: RpcHandlers;
+
+        /// The weight allowed to handle a request or a response, which handlers must stay
+        /// under.
+        #[pallet::constant]
+        type HandlerWeight: Get<Weight>;
+
+        /// The number of blocks a request waits for its response before it times out.
+        #[pallet::constant]
+        type RequestTimeout: Get<Self::BlockNumber>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The id of the next request sent.
+    #[pallet::storage]
+    pub type NextRequestId<T> = StorageValue<_, RequestId, ValueQuery>;
+
+    /// The requests waiting for a response, with the sibling they were sent to, their handler
+    /// and the block they time out at.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_request)]
+    pub type PendingRequests<T: Config> =
+        StorageMap<_, Twox64Concat, RequestId, (ParaId, HandlerId, T::BlockNumber), OptionQuery>;
+
+    /// The id of the oldest request that may still be pending.
+    #[pallet::storage]
+    pub type ExpiryCursor<T> = StorageValue<_, RequestId, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A request was sent. [id, dest, handler]
+        RequestSent(RequestId, ParaId, HandlerId),
+        /// A request could not be sent. [dest, handler, error]
+        RequestNotSent(ParaId, HandlerId, SendError),
+        /// A request from a sibling was handled, successfully if `error` is `None`.
+        /// [origin, id, handler, error]
+        RequestHandled(ParaId, RequestId, HandlerId, Option<RpcError>),
+        /// The response to a request from a sibling could not be sent. [origin, id, error]
+        ResponseNotSent(ParaId, RequestId, SendError),
+        /// The response to a request was received. [dest, id]
+        ResponseReceived(ParaId, RequestId),
+        /// A request got no response in time. [dest, id]
+        RequestTimedOut(ParaId, RequestId),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// No request with this id is waiting for a response from the sibling.
+        UnknownRequest,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+            Self::expire_requests(remaining_weight)
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Serve `request`, sent by a sibling, and send the response back.
+        #[pallet::weight(T::HandlerWeight::get())]
+        pub fn handle_request(origin: OriginFor<T>, request: VersionedRequest) -> DispatchResult {
+            let para = ensure_sibling_para(<T as Config>::Origin::from(origin))?;
+            let VersionedRequest::V1 {
+                id,
+                handler,
+                payload,
+            } = request;
+
+            let result = T::Handlers::handle_request(handler, para, &payload)
+                .unwrap_or(Err(RpcError::UnknownHandler));
+            Self::deposit_event(Event::RequestHandled(
+                para,
+                id,
+                handler,
+                result.clone().err(),
+            ));
+
+            let response = VersionedResponse::V1 { id, result };
+            let call = Call::<T>::handle_response { response };
+            if let Err(e) = Self::send_call(para, call) {
+                Self::deposit_event(Event::ResponseNotSent(para, id, e));
+            }
+            Ok(())
+        }
+
+        /// Deliver `response`, sent by a sibling, to the handler of the request.
+        #[pallet::weight(T::HandlerWeight::get())]
+        pub fn handle_response(
+            origin: OriginFor<T>,
+            response: VersionedResponse,
+        ) -> DispatchResult {
+            let para = ensure_sibling_para(<T as Config>::Origin::from(origin))?;
+            let VersionedResponse::V1 { id, result } = response;
+
+            // Only the sibling the request was sent to can respond.
+            let (dest, handler, _) = PendingRequests::<T>::get(id)
+                .filter(|(dest, _, _)| *dest == para)
+                .ok_or(Error::<T>::UnknownRequest)?;
+            PendingRequests::<T>::remove(id);
+            T::Handlers::on_response(handler, id, dest, result);
+            Self::deposit_event(Event::ResponseReceived(dest, id));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Send `request` to the sibling `dest`, to be served by its handler `H`. The response is
+    /// delivered to `H::on_response` with the returned request id.
+    pub fn send_request<H: RpcHandler>(
+        dest: ParaId,
+        request: H::Request,
+    ) -> Result<RequestId, SendError> {
+        let id = NextRequestId::<T>::get();
+        let request = VersionedRequest::V1 {
+            id,
+            handler: H::ID,
+            payload: request.encode(),
+        };
+        if let Err(e) = Self::send_call(dest, Call::<T>::handle_request { request }) {
+            Self::deposit_event(Event::RequestNotSent(dest, H::ID, e.clone()));
+            return Err(e);
+        }
+
+        NextRequestId::<T>::put(id.wrapping_add(1));
+        let timeout = frame_system::Pallet::<T>::block_number() + T::RequestTimeout::get();
+        PendingRequests::<T>::insert(id, (dest, H::ID, timeout));
+        Self::deposit_event(Event::RequestSent(id, dest, H::ID));
+        Ok(id)
+    }
+
+    /// Time out the pending requests past their timeout, as far as `weight_limit` allows.
+    fn expire_requests(weight_limit: Weight) -> Weight {
+        let step = T::HandlerWeight::get().saturating_add(T::DbWeight::get().reads_writes(1, 1));
+        let mut weight_used = T::DbWeight::get().reads_writes(2, 1);
+        if weight_limit < weight_used {
+            return 0;
+        }
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let next_id = NextRequestId::<T>::get();
+        let mut cursor = ExpiryCursor::<T>::get();
+        let start = cursor;
+        while cursor != next_id && weight_used.saturating_add(step) <= weight_limit {
+            weight_used = weight_used.saturating_add(step);
+            match PendingRequests::<T>::get(cursor) {
+                // Requests get ids in the order they are sent, so the following ones time out
+                // no earlier while `RequestTimeout` is unchanged.
+                Some((_, _, timeout)) if timeout > now => break,
+                Some((dest, handler, _)) => {
+                    PendingRequests::<T>::remove(cursor);
+                    T::Handlers::on_response(handler, cursor, dest, Err(RpcError::Timeout));
+                    Self::deposit_event(Event::RequestTimedOut(dest, cursor));
+                }
+                // Already answered.
+                None => {}
+            }
+            cursor = cursor.wrapping_add(1);
+        }
+        if cursor != start {
+            ExpiryCursor::<T>::put(cursor);
+        }
+        weight_used
+    }
+
+    /// Dispatch `call` on the sibling `dest` with its parachain origin.
+    fn send_call(dest: ParaId, call: Call<T>) -> Result<(), SendError> {
+        T::XcmSender::send_xcm(
+            (1, Junction::Parachain(dest.into())),
+            Xcm(vec![Transact {
+                origin_type: OriginKind::Native,
+                require_weight_at_most: T::HandlerWeight::get(),
+                call: <T as Config>::Call::from(call).encode().into(),
+            }]),
+        )
+    }
+}
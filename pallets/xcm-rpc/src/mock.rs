@@ -0,0 +1,139 @@
+use crate::{self as pallet_xcm_rpc, RequestId, RpcError, RpcHandler};
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64, Everything},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    DispatchError,
+};
+use sp_std::cell::RefCell;
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin},
+        XcmRpc: pallet_xcm_rpc::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl cumulus_pallet_xcm::Config for Test {
+    type Event = Event;
+    type XcmExecutor = ();
+}
+
+/// A sibling messages can't be routed to.
+pub const UNREACHABLE_SIBLING: u32 = 9999;
+
+thread_local! {
+    pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+    pub static RESPONSES: RefCell<Vec<(RequestId, ParaId, Result<u64, RpcError>)>> =
+        RefCell::new(Vec::new());
+}
+
+/// The messages sent so far.
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+    SENT.with(|s| s.borrow().clone())
+}
+
+/// The responses delivered to `Double` so far.
+pub fn responses() -> Vec<(RequestId, ParaId, Result<u64, RpcError>)> {
+    RESPONSES.with(|r| r.borrow().clone())
+}
+
+/// Records messages instead of sending them.
+pub struct RecordingRouter;
+impl SendXcm for RecordingRouter {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        let dest = dest.into();
+        if dest == MultiLocation::new(1, X1(Parachain(UNREACHABLE_SIBLING))) {
+            return Err(SendError::Unroutable);
+        }
+        SENT.with(|s| s.borrow_mut().push((dest, message)));
+        Ok(())
+    }
+}
+
+/// Doubles numbers, refusing to double zero.
+pub struct Double;
+impl RpcHandler for Double {
+    const ID: [u8; 8] = *b"double__";
+    type Request = u32;
+    type Response = u64;
+
+    fn handle_request(_origin: ParaId, request: u32) -> Result<u64, DispatchError> {
+        match request {
+            0 => Err(DispatchError::Other("zero")),
+            n => Ok(n as u64 * 2),
+        }
+    }
+
+    fn on_response(id: RequestId, dest: ParaId, response: Result<u64, RpcError>) {
+        RESPONSES.with(|r| r.borrow_mut().push((id, dest, response)));
+    }
+}
+
+impl pallet_xcm_rpc::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type Call = Call;
+    type XcmSender = RecordingRouter;
+    type Handlers = (Double,);
+    type HandlerWeight = ConstU64<1_000>;
+    type RequestTimeout = ConstU64<10>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,224 @@
+use crate::{
+    mock::*, Error, Event as PalletEvent, RpcError, RpcHandler, VersionedRequest, VersionedResponse,
+};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight};
+use sp_runtime::DispatchError;
+use xcm::latest::prelude::*;
+
+fn sibling(para: u32) -> Origin {
+    cumulus_pallet_xcm::Origin::SiblingParachain(para.into()).into()
+}
+
+/// The call of the single `Transact` of the last message sent, and its destination.
+fn last_call() -> (MultiLocation, Vec<u8>) {
+    let (dest, message) = sent().pop().unwrap();
+    match &message.0[..] {
+        [Transact {
+            origin_type: OriginKind::Native,
+            require_weight_at_most: 1_000,
+            call,
+        }] => (dest, call.clone().into_encoded()),
+        other => panic!("expected a single Transact, got {:?}", other),
+    }
+}
+
+fn request(id: u64, handler: [u8; 8], payload: Vec<u8>) -> VersionedRequest {
+    VersionedRequest::V1 {
+        id,
+        handler,
+        payload,
+    }
+}
+
+fn response(id: u64, result: Result<Vec<u8>, RpcError>) -> VersionedResponse {
+    VersionedResponse::V1 { id, result }
+}
+
+#[test]
+fn requests_are_sent_as_transact() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(XcmRpc::send_request::<Double>(2000.into(), 21), Ok(0));
+        assert_eq!(XcmRpc::send_request::<Double>(2000.into(), 22), Ok(1));
+
+        let expected = Call::XcmRpc(crate::Call::handle_request {
+            request: request(1, Double::ID, 22u32.encode()),
+        });
+        assert_eq!(
+            last_call(),
+            (
+                MultiLocation::new(1, X1(Parachain(2000))),
+                expected.encode()
+            )
+        );
+        assert_eq!(
+            XcmRpc::pending_request(1),
+            Some((2000.into(), Double::ID, 11))
+        );
+    });
+}
+
+#[test]
+fn unsent_requests_are_not_pending() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            XcmRpc::send_request::<Double>(UNREACHABLE_SIBLING.into(), 21),
+            Err(SendError::Unroutable)
+        );
+        System::assert_last_event(
+            PalletEvent::RequestNotSent(
+                UNREACHABLE_SIBLING.into(),
+                Double::ID,
+                SendError::Unroutable,
+            )
+            .into(),
+        );
+        assert_eq!(XcmRpc::pending_request(0), None);
+    });
+}
+
+#[test]
+fn requests_from_siblings_are_answered() {
+    new_test_ext().execute_with(|| {
+        let answer = |id, handler, payload| {
+            assert_ok!(XcmRpc::handle_request(
+                sibling(2000),
+                request(id, handler, payload)
+            ));
+            let (dest, call) = last_call();
+            assert_eq!(dest, MultiLocation::new(1, X1(Parachain(2000))));
+            call
+        };
+        let expected = |id, result| {
+            Call::XcmRpc(crate::Call::handle_response {
+                response: response(id, result),
+            })
+            .encode()
+        };
+
+        assert_eq!(
+            answer(3, Double::ID, 21u32.encode()),
+            expected(3, Ok(42u64.encode()))
+        );
+        assert_eq!(
+            answer(4, Double::ID, 0u32.encode()),
+            expected(4, Err(RpcError::Failed(DispatchError::Other("zero"))))
+        );
+        assert_eq!(
+            answer(5, Double::ID, vec![1]),
+            expected(5, Err(RpcError::BadRequest))
+        );
+        assert_eq!(
+            answer(6, *b"unknown_", 21u32.encode()),
+            expected(6, Err(RpcError::UnknownHandler))
+        );
+
+        // Only siblings can send requests.
+        assert_noop!(
+            XcmRpc::handle_request(Origin::signed(1), request(7, Double::ID, 21u32.encode())),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn responses_are_delivered_to_their_handler() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XcmRpc::send_request::<Double>(2000.into(), 21));
+        assert_ok!(XcmRpc::send_request::<Double>(2000.into(), 1));
+
+        // Only the sibling the request was sent to can respond.
+        assert_noop!(
+            XcmRpc::handle_response(sibling(3000), response(0, Ok(42u64.encode()))),
+            Error::<Test>::UnknownRequest
+        );
+        assert_ok!(XcmRpc::handle_response(
+            sibling(2000),
+            response(0, Ok(42u64.encode()))
+        ));
+        assert_ok!(XcmRpc::handle_response(
+            sibling(2000),
+            response(1, Ok(vec![1]))
+        ));
+
+        assert_eq!(
+            responses(),
+            vec![
+                (0, 2000.into(), Ok(42)),
+                (1, 2000.into(), Err(RpcError::BadResponse))
+            ]
+        );
+        // Each request gets a single response.
+        assert_noop!(
+            XcmRpc::handle_response(sibling(2000), response(0, Ok(42u64.encode()))),
+            Error::<Test>::UnknownRequest
+        );
+    });
+}
+
+#[test]
+fn requests_time_out_without_a_response() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(XcmRpc::send_request::<Double>(2000.into(), 21), Ok(0));
+        System::set_block_number(5);
+        assert_eq!(XcmRpc::send_request::<Double>(3000.into(), 22), Ok(1));
+        assert_eq!(XcmRpc::send_request::<Double>(2000.into(), 23), Ok(2));
+        assert_ok!(XcmRpc::handle_response(
+            sibling(3000),
+            response(1, Ok(44u64.encode()))
+        ));
+
+        System::set_block_number(10);
+        XcmRpc::on_idle(10, Weight::MAX);
+        assert_eq!(
+            XcmRpc::pending_request(0),
+            Some((2000.into(), Double::ID, 11))
+        );
+
+        System::set_block_number(11);
+        XcmRpc::on_idle(11, Weight::MAX);
+        assert_eq!(XcmRpc::pending_request(0), None);
+        System::assert_last_event(PalletEvent::RequestTimedOut(2000.into(), 0).into());
+        assert_eq!(
+            responses(),
+            vec![
+                (1, 3000.into(), Ok(44)),
+                (0, 2000.into(), Err(RpcError::Timeout))
+            ]
+        );
+        // The request sent later is still waiting.
+        assert!(XcmRpc::pending_request(2).is_some());
+
+        // A late response is refused.
+        assert_noop!(
+            XcmRpc::handle_response(sibling(2000), response(0, Ok(42u64.encode()))),
+            Error::<Test>::UnknownRequest
+        );
+
+        System::set_block_number(15);
+        XcmRpc::on_idle(15, Weight::MAX);
+        assert_eq!(XcmRpc::pending_request(2), None);
+        assert_eq!(
+            responses().pop(),
+            Some((2, 2000.into(), Err(RpcError::Timeout)))
+        );
+    });
+}
+
+#[test]
+fn expiry_stays_within_the_weight_given() {
+    new_test_ext().execute_with(|| {
+        for request in 1..=3 {
+            assert_ok!(XcmRpc::send_request::<Double>(2000.into(), request));
+        }
+
+        System::set_block_number(11);
+        // Enough for two requests of handler weight 1_000.
+        assert_eq!(XcmRpc::on_idle(11, 2_500), 2_000);
+        assert_eq!(responses().len(), 2);
+        assert!(XcmRpc::pending_request(2).is_some());
+
+        XcmRpc::on_idle(11, 2_500);
+        assert_eq!(XcmRpc::pending_request(2), None);
+    });
+}
@@ -0,0 +1,623 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Subscriptions of sibling parachains to storage values.
+//!
+//! A sibling subscribes to the value under a storage key, such as `pallet_counter::Counter` or
+//! the account of a user, by sending a [`Subscribe`] request through `XcmRpc`. It must be one of
+//! the `SubscribableKeys`, with a value no longer than `MaxValueLen`, and `SubscriptionDeposit` is
+//! reserved from the sovereign account of the sibling until it unsubscribes. A sibling subscribes
+//! to at most `MaxKeysPerSubscriber` values. At the end of every block, each subscriber of a value
+//! that changed is sent a `Transact` of `notify` with the new value, with the weight limit it
+//! asked for. The notification costs `NotificationBaseFee` plus `NotificationByteFee` per byte of
+//! the call, paid from its sovereign account, and is skipped if that is more than the subscriber
+//! accepts to pay.
+//!
+//! The same pallet on the subscriber side sends the requests with `subscribe` and `unsubscribe`,
+//! and delivers notifications to `OnNotification`.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use cumulus_pallet_xcm::ensure_sibling_para;
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    pallet_prelude::*,
+    storage::unhashed,
+    traits::{Currency, ExistenceRequirement, OnUnbalanced, ReservableCurrency, WithdrawReasons},
+    weights::Weight,
+};
+use pallet_xcm_rpc::{HandlerId, RequestId, RpcError, RpcHandler};
+use scale_info::TypeInfo;
+use sp_runtime::traits::{Hash as HashT, Saturating};
+use sp_std::{marker::PhantomData, vec, vec::Vec};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::Convert;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+    <T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// A storage key siblings can subscribe to.
+pub type StorageKeyOf<T> = BoundedVec<u8, <T as Config>::MaxKeyLen>;
+
+/// A request to be notified of the changes of a storage value.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct SubscribeRequest<Balance> {
+    /// The storage key of the value.
+    pub key: Vec<u8>,
+    /// The weight limit of the `Transact` of the notifications.
+    pub max_weight: Weight,
+    /// The most the subscriber accepts to pay for a notification.
+    pub max_fee: Balance,
+}
+
+/// A sibling subscribed to a storage value.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct Subscriber<Balance> {
+    /// The weight limit of the `Transact` of the notifications.
+    pub max_weight: Weight,
+    /// The most the subscriber accepts to pay for a notification.
+    pub max_fee: Balance,
+    /// The deposit reserved from the sovereign account of the subscriber.
+    pub deposit: Balance,
+}
+
+/// A storage value with subscribers.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct Topic<Hash> {
+    /// The hash of the value last notified, or of the value when it got its first subscriber.
+    pub value_hash: Hash,
+    /// The number of subscribers.
+    pub subscribers: u32,
+}
+
+/// Why a subscriber was not notified of a change.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum NotSentReason {
+    /// The fee is more than the subscriber accepts to pay.
+    FeeLimit,
+    /// The sovereign account of the subscriber can't pay the fee.
+    CannotPay,
+    /// The notification could not be sent.
+    Send(SendError),
+}
+
+/// The storage values siblings can subscribe to.
+pub trait SubscribableValues {
+    /// The maximum encoded length of the value under `key`, `None` if it can't be subscribed to.
+    fn max_value_len(key: &[u8]) -> Option<u32>;
+}
+
+/// Receives the notifications of the storage values this chain subscribed to.
+pub trait OnNotification {
+    /// The value under `key` on the sibling `publisher` changed to `value`, `None` if it was
+    /// removed.
+    fn on_notification(publisher: ParaId, key: &[u8], value: Option<Vec<u8>>);
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl OnNotification for Tuple {
+    fn on_notification(publisher: ParaId, key: &[u8], value: Option<Vec<u8>>) {
+        for_tuples!( #( Tuple::on_notification(publisher, key, value.clone()); )* );
+    }
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_xcm_rpc::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        type Call: From<Call<Self>> + Encode;
+
+        /// The currency deposits and fees are paid in.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Converts the location of a sibling to its sovereign account.
+        type SovereignAccountOf: Convert<MultiLocation, Self::AccountId>;
+
+        /// Origin allowed to subscribe to the values of siblings, and to drop subscribers.
+        type SubscriptionOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+
+        /// The storage keys siblings can subscribe to, with the maximum length of their values.
+        type SubscribableKeys: SubscribableValues;
+
+        /// Receives the notifications of the values this chain subscribed to.
+        type OnNotification: OnNotification;
+
+        /// Where notification fees go.
+        type FeeDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+        /// The deposit reserved from the sovereign account of a subscriber.
+        #[pallet::constant]
+        type SubscriptionDeposit: Get<BalanceOf<Self>>;
+
+        /// The fee of a notification.
+        #[pallet::constant]
+        type NotificationBaseFee: Get<BalanceOf<Self>>;
+
+        /// The fee of a notification per byte of its call.
+        #[pallet::constant]
+        type NotificationByteFee: Get<BalanceOf<Self>>;
+
+        /// The weight allowed to handle a notification, which `OnNotification` must stay under.
+        /// Notifications are asked for with this weight limit.
+        #[pallet::constant]
+        type NotificationWeight: Get<Weight>;
+
+        /// The weight of sending a notification with `XcmSender`.
+        #[pallet::constant]
+        type SendWeight: Get<Weight>;
+
+        /// The weight of a byte of a value, read and hashed at the end of every block and sent
+        /// to each subscriber when it changes.
+        #[pallet::constant]
+        type ValueByteWeight: Get<Weight>;
+
+        /// The highest weight limit subscribers can ask for.
+        #[pallet::constant]
+        type MaxNotificationWeight: Get<Weight>;
+
+        /// The maximum length of a storage key.
+        #[pallet::constant]
+        type MaxKeyLen: Get<u32>;
+
+        /// The maximum length of a value siblings can subscribe to.
+        #[pallet::constant]
+        type MaxValueLen: Get<u32>;
+
+        /// The maximum number of subscribers, over all values.
+        #[pallet::constant]
+        type MaxSubscribers: Get<u32>;
+
+        /// The maximum number of values a sibling can subscribe to.
+        #[pallet::constant]
+        type MaxKeysPerSubscriber: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The storage values siblings subscribed to.
+    #[pallet::storage]
+    #[pallet::getter(fn topic)]
+    pub type Topics<T: Config> = StorageMap<_, Blake2_128Concat, StorageKeyOf<T>, Topic<T::Hash>>;
+
+    /// The siblings subscribed to each storage value.
+    #[pallet::storage]
+    #[pallet::getter(fn subscriber)]
+    pub type Subscribers<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        StorageKeyOf<T>,
+        Twox64Concat,
+        ParaId,
+        Subscriber<BalanceOf<T>>,
+    >;
+
+    /// The number of subscribers, over all values.
+    #[pallet::storage]
+    #[pallet::getter(fn subscriber_count)]
+    pub type SubscriberCount<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// The number of values with subscribers.
+    #[pallet::storage]
+    #[pallet::getter(fn topic_count)]
+    pub type TopicCount<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// The number of values each sibling is subscribed to.
+    #[pallet::storage]
+    #[pallet::getter(fn key_count)]
+    pub type KeyCount<T> = StorageMap<_, Twox64Concat, ParaId, u32, ValueQuery>;
+
+    /// The storage values of siblings this chain subscribed to.
+    #[pallet::storage]
+    pub type Subscriptions<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, ParaId, Blake2_128Concat, StorageKeyOf<T>, ()>;
+
+    /// The storage keys of the subscription requests waiting for a response.
+    #[pallet::storage]
+    pub type PendingSubscriptions<T: Config> =
+        StorageMap<_, Twox64Concat, RequestId, StorageKeyOf<T>>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A sibling subscribed to a value. [subscriber, key]
+        SubscriberAdded(ParaId, StorageKeyOf<T>),
+        /// A sibling unsubscribed from a value, or was dropped. [subscriber, key]
+        SubscriberRemoved(ParaId, StorageKeyOf<T>),
+        /// A subscriber was notified of a change. [subscriber, key, fee]
+        NotificationSent(ParaId, StorageKeyOf<T>, BalanceOf<T>),
+        /// A subscriber could not be notified of a change. [subscriber, key, reason]
+        NotificationNotSent(ParaId, StorageKeyOf<T>, NotSentReason),
+        /// This chain subscribed to a value of a sibling. [publisher, key]
+        Subscribed(ParaId, StorageKeyOf<T>),
+        /// A sibling refused the subscription of this chain. [publisher, key, error]
+        SubscriptionRejected(ParaId, StorageKeyOf<T>, RpcError),
+        /// This chain unsubscribed from a value of a sibling. [publisher, key]
+        Unsubscribed(ParaId, StorageKeyOf<T>),
+        /// A value this chain subscribed to changed. [publisher, key, value]
+        Notified(ParaId, StorageKeyOf<T>, Option<Vec<u8>>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The storage key is too long.
+        KeyTooLong,
+        /// The storage key can't be subscribed to.
+        NotSubscribable,
+        /// The value under the storage key can be longer than `MaxValueLen`.
+        ValueTooLong,
+        /// The weight limit of the notifications is too high.
+        WeightLimitTooHigh,
+        /// The sibling is already subscribed to the value.
+        AlreadySubscribed,
+        /// There are already `MaxSubscribers` subscribers.
+        TooManySubscribers,
+        /// The sibling is already subscribed to `MaxKeysPerSubscriber` values.
+        TooManyKeys,
+        /// The sibling is not subscribed to the value.
+        NotSubscribed,
+        /// The sovereign account of the sibling is unknown.
+        UnknownSovereignAccount,
+        /// The request could not be sent to the sibling.
+        SendFailure,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            // Changes are notified in `on_finalize`, at most once per subscriber.
+            T::DbWeight::get()
+                .reads(2)
+                .saturating_add(Self::notification_weight(
+                    TopicCount::<T>::get(),
+                    SubscriberCount::<T>::get(),
+                ))
+        }
+
+        fn on_finalize(_n: T::BlockNumber) {
+            Self::notify_changes();
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Subscribe to the value under `key` on the sibling `dest`, accepting to pay up to
+        /// `max_fee` per notification from the sovereign account of this chain there.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        pub fn subscribe(
+            origin: OriginFor<T>,
+            dest: ParaId,
+            key: Vec<u8>,
+            max_fee: BalanceOf<T>,
+        ) -> DispatchResult {
+            T::SubscriptionOrigin::ensure_origin(origin)?;
+            let bounded_key = Self::bounded_key(key.clone())?;
+            ensure!(
+                !Subscriptions::<T>::contains_key(dest, &bounded_key),
+                Error::<T>::AlreadySubscribed
+            );
+
+            let request = SubscribeRequest {
+                key,
+                max_weight: T::NotificationWeight::get(),
+                max_fee,
+            };
+            let id = pallet_xcm_rpc::Pallet::<T>::send_request::<Subscribe<T>>(dest, request)
+                .map_err(|_| Error::<T>::SendFailure)?;
+            PendingSubscriptions::<T>::insert(id, bounded_key);
+            Ok(())
+        }
+
+        /// Unsubscribe from the value under `key` on the sibling `dest`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        pub fn unsubscribe(origin: OriginFor<T>, dest: ParaId, key: Vec<u8>) -> DispatchResult {
+            T::SubscriptionOrigin::ensure_origin(origin)?;
+            let bounded_key = Self::bounded_key(key.clone())?;
+            ensure!(
+                Subscriptions::<T>::contains_key(dest, &bounded_key),
+                Error::<T>::NotSubscribed
+            );
+
+            pallet_xcm_rpc::Pallet::<T>::send_request::<Unsubscribe<T>>(dest, key)
+                .map_err(|_| Error::<T>::SendFailure)?;
+            Subscriptions::<T>::remove(dest, &bounded_key);
+            Self::deposit_event(Event::Unsubscribed(dest, bounded_key));
+            Ok(())
+        }
+
+        /// Drop the subscription of the sibling `subscriber` to the value under `key`, returning
+        /// its deposit.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+        pub fn remove_subscriber(
+            origin: OriginFor<T>,
+            subscriber: ParaId,
+            key: Vec<u8>,
+        ) -> DispatchResult {
+            T::SubscriptionOrigin::ensure_origin(origin)?;
+            Self::remove_subscriber_of(subscriber, &Self::bounded_key(key)?)
+        }
+
+        /// Receive the new value under `key` on the sibling sending the notification.
+        #[pallet::weight(T::NotificationWeight::get())]
+        pub fn notify(
+            origin: OriginFor<T>,
+            key: Vec<u8>,
+            value: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            let para = ensure_sibling_para(<<T as pallet_xcm_rpc::Config>::Origin>::from(origin))?;
+            let key = Self::bounded_key(key).map_err(|_| Error::<T>::NotSubscribed)?;
+            ensure!(
+                Subscriptions::<T>::contains_key(para, &key),
+                Error::<T>::NotSubscribed
+            );
+
+            T::OnNotification::on_notification(para, &key, value.clone());
+            Self::deposit_event(Event::Notified(para, key, value));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    fn bounded_key(key: Vec<u8>) -> Result<StorageKeyOf<T>, Error<T>> {
+        key.try_into().map_err(|_| Error::<T>::KeyTooLong)
+    }
+
+    fn sovereign_account(para: ParaId) -> Result<T::AccountId, Error<T>> {
+        T::SovereignAccountOf::convert(MultiLocation::new(1, X1(Parachain(para.into()))))
+            .map_err(|_| Error::<T>::UnknownSovereignAccount)
+    }
+
+    fn value_hash(key: &[u8]) -> T::Hash {
+        T::Hashing::hash_of(&unhashed::get_raw(key))
+    }
+
+    fn add_subscriber(para: ParaId, request: SubscribeRequest<BalanceOf<T>>) -> DispatchResult {
+        let max_value_len =
+            T::SubscribableKeys::max_value_len(&request.key).ok_or(Error::<T>::NotSubscribable)?;
+        ensure!(
+            max_value_len <= T::MaxValueLen::get(),
+            Error::<T>::ValueTooLong
+        );
+        let key = Self::bounded_key(request.key)?;
+        ensure!(
+            request.max_weight <= T::MaxNotificationWeight::get(),
+            Error::<T>::WeightLimitTooHigh
+        );
+        ensure!(
+            !Subscribers::<T>::contains_key(&key, para),
+            Error::<T>::AlreadySubscribed
+        );
+        let count = SubscriberCount::<T>::get();
+        ensure!(
+            count < T::MaxSubscribers::get(),
+            Error::<T>::TooManySubscribers
+        );
+        let keys = KeyCount::<T>::get(para);
+        ensure!(
+            keys < T::MaxKeysPerSubscriber::get(),
+            Error::<T>::TooManyKeys
+        );
+        let deposit = T::SubscriptionDeposit::get();
+        T::Currency::reserve(&Self::sovereign_account(para)?, deposit)?;
+
+        Topics::<T>::mutate(&key, |topic| {
+            // Only the changes from now on are notified.
+            let topic = topic.get_or_insert_with(|| {
+                TopicCount::<T>::mutate(|count| *count += 1);
+                Topic {
+                    value_hash: Self::value_hash(&key),
+                    subscribers: 0,
+                }
+            });
+            topic.subscribers += 1;
+        });
+        Subscribers::<T>::insert(
+            &key,
+            para,
+            Subscriber {
+                max_weight: request.max_weight,
+                max_fee: request.max_fee,
+                deposit,
+            },
+        );
+        SubscriberCount::<T>::put(count + 1);
+        KeyCount::<T>::insert(para, keys + 1);
+        Self::deposit_event(Event::SubscriberAdded(para, key));
+        Ok(())
+    }
+
+    fn remove_subscriber_of(para: ParaId, key: &StorageKeyOf<T>) -> DispatchResult {
+        let subscriber = Subscribers::<T>::take(key, para).ok_or(Error::<T>::NotSubscribed)?;
+        if let Ok(account) = Self::sovereign_account(para) {
+            T::Currency::unreserve(&account, subscriber.deposit);
+        }
+        Topics::<T>::mutate_exists(key, |maybe_topic| {
+            if let Some(topic) = maybe_topic {
+                topic.subscribers = topic.subscribers.saturating_sub(1);
+                if topic.subscribers == 0 {
+                    *maybe_topic = None;
+                    TopicCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+                }
+            }
+        });
+        SubscriberCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+        KeyCount::<T>::mutate_exists(para, |count| {
+            *count = count
+                .map(|count| count.saturating_sub(1))
+                .filter(|count| *count > 0)
+        });
+        Self::deposit_event(Event::SubscriberRemoved(para, key.clone()));
+        Ok(())
+    }
+
+    /// The weight of checking `topics` values for changes and notifying `subscribers`
+    /// subscribers in `on_finalize`, with values as long as they can be.
+    fn notification_weight(topics: u32, subscribers: u32) -> Weight {
+        let value_weight = T::ValueByteWeight::get().saturating_mul(T::MaxValueLen::get().into());
+        // The topic and the value are read, and the topic written if the value changed.
+        let per_topic =
+            (10_000 + T::DbWeight::get().reads_writes(2, 1)).saturating_add(value_weight);
+        let per_subscriber = (10_000 + T::DbWeight::get().reads_writes(5, 3))
+            .saturating_add(T::SendWeight::get())
+            .saturating_add(value_weight);
+        per_topic
+            .saturating_mul(topics.into())
+            .saturating_add(per_subscriber.saturating_mul(subscribers.into()))
+    }
+
+    /// Notify the subscribers of the values that changed since they were last notified.
+    fn notify_changes() {
+        let changed: Vec<_> = Topics::<T>::iter()
+            .filter_map(|(key, topic)| {
+                let value = unhashed::get_raw(&key);
+                let value_hash = T::Hashing::hash_of(&value);
+                (value_hash != topic.value_hash).then(|| (key, value, value_hash))
+            })
+            .collect();
+
+        for (key, value, value_hash) in changed {
+            Topics::<T>::mutate(&key, |topic| {
+                if let Some(topic) = topic {
+                    topic.value_hash = value_hash;
+                }
+            });
+            let call = <T as Config>::Call::from(Call::<T>::notify {
+                key: key.to_vec(),
+                value,
+            })
+            .encode();
+            for (para, subscriber) in Subscribers::<T>::iter_prefix(&key) {
+                Self::notify_subscriber(para, &key, &call, subscriber);
+            }
+        }
+    }
+
+    fn notify_subscriber(
+        para: ParaId,
+        key: &StorageKeyOf<T>,
+        call: &[u8],
+        subscriber: Subscriber<BalanceOf<T>>,
+    ) {
+        let fee = T::NotificationByteFee::get()
+            .saturating_mul((call.len() as u32).into())
+            .saturating_add(T::NotificationBaseFee::get());
+        if fee > subscriber.max_fee {
+            Self::deposit_event(Event::NotificationNotSent(
+                para,
+                key.clone(),
+                NotSentReason::FeeLimit,
+            ));
+            return;
+        }
+        let withdrawn = Self::sovereign_account(para).ok().and_then(|account| {
+            T::Currency::withdraw(
+                &account,
+                fee,
+                WithdrawReasons::FEE,
+                ExistenceRequirement::KeepAlive,
+            )
+            .ok()
+            .map(|paid| (account, paid))
+        });
+        let (account, paid) = match withdrawn {
+            Some(withdrawn) => withdrawn,
+            None => {
+                Self::deposit_event(Event::NotificationNotSent(
+                    para,
+                    key.clone(),
+                    NotSentReason::CannotPay,
+                ));
+                return;
+            }
+        };
+
+        let message = Xcm(vec![Transact {
+            origin_type: OriginKind::Native,
+            require_weight_at_most: subscriber.max_weight,
+            call: call.to_vec().into(),
+        }]);
+        match <T as pallet_xcm_rpc::Config>::XcmSender::send_xcm(
+            (1, Junction::Parachain(para.into())),
+            message,
+        ) {
+            Ok(()) => {
+                T::FeeDestination::on_unbalanced(paid);
+                Self::deposit_event(Event::NotificationSent(para, key.clone(), fee));
+            }
+            Err(e) => {
+                T::Currency::resolve_creating(&account, paid);
+                Self::deposit_event(Event::NotificationNotSent(
+                    para,
+                    key.clone(),
+                    NotSentReason::Send(e),
+                ));
+            }
+        }
+    }
+}
+
+/// Subscribes the sibling sending the request to a storage value.
+pub struct Subscribe<T>(PhantomData<T>);
+
+impl<T: Config> RpcHandler for Subscribe<T> {
+    const ID: HandlerId = *b"sub/add_";
+    type Request = SubscribeRequest<BalanceOf<T>>;
+    type Response = ();
+
+    fn handle_request(
+        origin: ParaId,
+        request: SubscribeRequest<BalanceOf<T>>,
+    ) -> Result<(), DispatchError> {
+        Pallet::<T>::add_subscriber(origin, request)
+    }
+
+    // Also called with `RpcError::Timeout` when the sibling does not answer in time.
+    fn on_response(id: RequestId, dest: ParaId, response: Result<(), RpcError>) {
+        let key = match PendingSubscriptions::<T>::take(id) {
+            Some(key) => key,
+            None => return,
+        };
+        match response {
+            Ok(()) => {
+                Subscriptions::<T>::insert(dest, &key, ());
+                Pallet::<T>::deposit_event(Event::Subscribed(dest, key));
+            }
+            Err(error) => {
+                Pallet::<T>::deposit_event(Event::SubscriptionRejected(dest, key, error));
+            }
+        }
+    }
+}
+
+/// Unsubscribes the sibling sending the request from a storage value.
+pub struct Unsubscribe<T>(PhantomData<T>);
+
+impl<T: Config> RpcHandler for Unsubscribe<T> {
+    const ID: HandlerId = *b"sub/del_";
+    type Request = Vec<u8>;
+    type Response = ();
+
+    fn handle_request(origin: ParaId, key: Vec<u8>) -> Result<(), DispatchError> {
+        let key = Pallet::<T>::bounded_key(key).map_err(|_| Error::<T>::NotSubscribed)?;
+        Pallet::<T>::remove_subscriber_of(origin, &key)
+    }
+
+    // The subscription was dropped when the request was sent.
+    fn on_response(_id: RequestId, _dest: ParaId, _response: Result<(), RpcError>) {}
+}
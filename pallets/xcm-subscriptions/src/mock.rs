@@ -0,0 +1,207 @@
+use crate::{
+    self as pallet_xcm_subscriptions, OnNotification, SubscribableValues, Subscribe, Unsubscribe,
+};
+use cumulus_primitives_core::ParaId;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64, Everything},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::{borrow::Borrow, cell::RefCell};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::Convert;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin},
+        XcmRpc: pallet_xcm_rpc::{Pallet, Call, Storage, Event<T>},
+        XcmSubscriptions: pallet_xcm_subscriptions::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = Event;
+    type ExistentialDeposit = ConstU64<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+}
+
+impl cumulus_pallet_xcm::Config for Test {
+    type Event = Event;
+    type XcmExecutor = ();
+}
+
+thread_local! {
+    pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+    pub static NOTIFIED: RefCell<Vec<(ParaId, Vec<u8>, Option<Vec<u8>>)>> =
+        RefCell::new(Vec::new());
+}
+
+/// The messages sent so far.
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+    SENT.with(|s| s.borrow().clone())
+}
+
+/// The notifications received so far.
+pub fn notified() -> Vec<(ParaId, Vec<u8>, Option<Vec<u8>>)> {
+    NOTIFIED.with(|n| n.borrow().clone())
+}
+
+/// Records messages instead of sending them.
+pub struct RecordingRouter;
+impl SendXcm for RecordingRouter {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        SENT.with(|s| s.borrow_mut().push((dest.into(), message)));
+        Ok(())
+    }
+}
+
+impl pallet_xcm_rpc::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type Call = Call;
+    type XcmSender = RecordingRouter;
+    type Handlers = (Subscribe<Test>, Unsubscribe<Test>);
+    type HandlerWeight = ConstU64<1_000>;
+    type RequestTimeout = ConstU64<10>;
+}
+
+/// The sovereign account of a sibling is its id.
+pub struct SiblingAccount;
+impl Convert<MultiLocation, u64> for SiblingAccount {
+    fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<u64, ()> {
+        match location.borrow() {
+            MultiLocation {
+                parents: 1,
+                interior: X1(Parachain(id)),
+            } => Ok(*id as u64),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The storage keys starting with `watched`.
+pub const WATCHED_KEY: &[u8] = b"watched";
+
+/// A watched key with a value that can be longer than `MAX_VALUE_LEN`.
+pub const LONG_VALUE_KEY: &[u8] = b"watched_long";
+
+/// The maximum length of a value siblings can subscribe to.
+pub const MAX_VALUE_LEN: u32 = 8;
+
+pub struct WatchedKeys;
+impl SubscribableValues for WatchedKeys {
+    fn max_value_len(key: &[u8]) -> Option<u32> {
+        if key == LONG_VALUE_KEY {
+            Some(MAX_VALUE_LEN + 1)
+        } else if key.starts_with(WATCHED_KEY) {
+            Some(MAX_VALUE_LEN)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct RecordNotifications;
+impl OnNotification for RecordNotifications {
+    fn on_notification(publisher: ParaId, key: &[u8], value: Option<Vec<u8>>) {
+        NOTIFIED.with(|n| n.borrow_mut().push((publisher, key.to_vec(), value)));
+    }
+}
+
+/// The deposit of a subscription.
+pub const DEPOSIT: u64 = 10;
+
+/// The weight limit asked for notifications.
+pub const NOTIFICATION_WEIGHT: u64 = 1_000;
+
+impl pallet_xcm_subscriptions::Config for Test {
+    type Event = Event;
+    type Call = Call;
+    type Currency = Balances;
+    type SovereignAccountOf = SiblingAccount;
+    type SubscriptionOrigin = system::EnsureRoot<u64>;
+    type SubscribableKeys = WatchedKeys;
+    type OnNotification = RecordNotifications;
+    type FeeDestination = ();
+    type SubscriptionDeposit = ConstU64<DEPOSIT>;
+    type NotificationBaseFee = ConstU64<10>;
+    type NotificationByteFee = ConstU64<1>;
+    type NotificationWeight = ConstU64<NOTIFICATION_WEIGHT>;
+    type SendWeight = ConstU64<100>;
+    type ValueByteWeight = ConstU64<1>;
+    type MaxNotificationWeight = ConstU64<10_000>;
+    type MaxKeyLen = ConstU32<16>;
+    type MaxValueLen = ConstU32<MAX_VALUE_LEN>;
+    type MaxSubscribers = ConstU32<3>;
+    type MaxKeysPerSubscriber = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        // Sovereign accounts of siblings, the last one only able to pay the deposit.
+        balances: vec![(2000, 1_000), (3000, 1_000), (4000, DEPOSIT + 2)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
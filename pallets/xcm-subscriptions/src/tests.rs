@@ -0,0 +1,376 @@
+use crate::{
+    mock::*, Error, Event as PalletEvent, NotSentReason, PendingSubscriptions, Subscribe,
+    SubscribeRequest, Unsubscribe,
+};
+use codec::Encode;
+use cumulus_primitives_core::ParaId;
+use frame_support::{assert_noop, assert_ok, storage::unhashed, traits::Hooks, weights::Weight};
+use pallet_xcm_rpc::{RpcError, RpcHandler, VersionedRequest, VersionedResponse};
+use sp_runtime::DispatchError;
+use xcm::latest::prelude::*;
+
+fn sibling(para: u32) -> Origin {
+    cumulus_pallet_xcm::Origin::SiblingParachain(para.into()).into()
+}
+
+fn key() -> Vec<u8> {
+    WATCHED_KEY.to_vec()
+}
+
+fn add_subscriber(para: u32, max_weight: u64, max_fee: u64) -> Result<(), DispatchError> {
+    Subscribe::<Test>::handle_request(
+        para.into(),
+        SubscribeRequest {
+            key: key(),
+            max_weight,
+            max_fee,
+        },
+    )
+}
+
+fn add_subscriber_to(para: u32, key: &[u8]) -> Result<(), DispatchError> {
+    Subscribe::<Test>::handle_request(
+        para.into(),
+        SubscribeRequest {
+            key: key.to_vec(),
+            max_weight: 1_000,
+            max_fee: 100,
+        },
+    )
+}
+
+fn end_block() {
+    XcmSubscriptions::on_finalize(System::block_number());
+}
+
+/// The destination, weight limit and call of the single `Transact` of the last message sent.
+fn last_transact() -> (MultiLocation, u64, Vec<u8>) {
+    let (dest, message) = sent().pop().unwrap();
+    match &message.0[..] {
+        [Transact {
+            origin_type: OriginKind::Native,
+            require_weight_at_most,
+            call,
+        }] => (dest, *require_weight_at_most, call.clone().into_encoded()),
+        other => panic!("expected a single Transact, got {:?}", other),
+    }
+}
+
+fn notify_call(value: Option<Vec<u8>>) -> Vec<u8> {
+    Call::XcmSubscriptions(crate::Call::notify { key: key(), value }).encode()
+}
+
+fn bounded_key() -> crate::StorageKeyOf<Test> {
+    key().try_into().unwrap()
+}
+
+#[test]
+fn siblings_subscribe_with_a_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(add_subscriber(2000, 1_000, 100));
+        assert_eq!(Balances::reserved_balance(2000), DEPOSIT);
+        assert_eq!(XcmSubscriptions::subscriber_count(), 1);
+        assert_eq!(
+            XcmSubscriptions::topic(bounded_key()).map(|topic| topic.subscribers),
+            Some(1)
+        );
+        System::assert_last_event(PalletEvent::SubscriberAdded(2000.into(), bounded_key()).into());
+
+        assert_eq!(
+            add_subscriber(2000, 1_000, 100),
+            Err(Error::<Test>::AlreadySubscribed.into())
+        );
+        assert_eq!(
+            add_subscriber(3000, 10_001, 100),
+            Err(Error::<Test>::WeightLimitTooHigh.into())
+        );
+        assert_eq!(
+            Subscribe::<Test>::handle_request(
+                3000.into(),
+                SubscribeRequest {
+                    key: b"other".to_vec(),
+                    max_weight: 1_000,
+                    max_fee: 100,
+                },
+            ),
+            Err(Error::<Test>::NotSubscribable.into())
+        );
+        assert_eq!(
+            Subscribe::<Test>::handle_request(
+                3000.into(),
+                SubscribeRequest {
+                    key: b"watched and too long".to_vec(),
+                    max_weight: 1_000,
+                    max_fee: 100,
+                },
+            ),
+            Err(Error::<Test>::KeyTooLong.into())
+        );
+        // The sibling can't pay the deposit.
+        assert!(add_subscriber(5000, 1_000, 100).is_err());
+
+        assert_ok!(add_subscriber(3000, 1_000, 100));
+        assert_ok!(add_subscriber(4000, 1_000, 100));
+        assert_eq!(
+            add_subscriber(5000, 1_000, 100),
+            Err(Error::<Test>::TooManySubscribers.into())
+        );
+    });
+}
+
+#[test]
+fn subscribers_are_notified_of_changes() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(add_subscriber(2000, 2_000, 100));
+        // Accepts to pay nothing.
+        assert_ok!(add_subscriber(3000, 1_000, 0));
+        // Can't pay anything once the deposit is reserved.
+        assert_ok!(add_subscriber(4000, 1_000, 100));
+
+        end_block();
+        assert!(sent().is_empty());
+
+        unhashed::put_raw(WATCHED_KEY, &[1, 2, 3]);
+        end_block();
+        let call = notify_call(Some(vec![1, 2, 3]));
+        let fee = 10 + call.len() as u64;
+        assert_eq!(sent().len(), 1);
+        assert_eq!(
+            last_transact(),
+            (MultiLocation::new(1, X1(Parachain(2000))), 2_000, call)
+        );
+        assert_eq!(Balances::free_balance(2000), 1_000 - DEPOSIT - fee);
+        System::assert_has_event(
+            PalletEvent::NotificationSent(2000.into(), bounded_key(), fee).into(),
+        );
+        System::assert_has_event(
+            PalletEvent::NotificationNotSent(3000.into(), bounded_key(), NotSentReason::FeeLimit)
+                .into(),
+        );
+        System::assert_has_event(
+            PalletEvent::NotificationNotSent(4000.into(), bounded_key(), NotSentReason::CannotPay)
+                .into(),
+        );
+
+        // Changes are notified once.
+        end_block();
+        assert_eq!(sent().len(), 1);
+
+        unhashed::kill(WATCHED_KEY);
+        end_block();
+        assert_eq!(sent().len(), 2);
+        assert_eq!(last_transact().2, notify_call(None));
+    });
+}
+
+#[test]
+fn unsubscribing_returns_the_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(add_subscriber(2000, 1_000, 100));
+        assert_ok!(add_subscriber(3000, 1_000, 100));
+
+        assert_ok!(Unsubscribe::<Test>::handle_request(2000.into(), key()));
+        assert_eq!(Balances::reserved_balance(2000), 0);
+        assert_eq!(
+            XcmSubscriptions::subscriber(bounded_key(), ParaId::from(2000)),
+            None
+        );
+        assert_eq!(
+            Unsubscribe::<Test>::handle_request(2000.into(), key()),
+            Err(Error::<Test>::NotSubscribed.into())
+        );
+
+        assert_noop!(
+            XcmSubscriptions::remove_subscriber(Origin::signed(1), 3000.into(), key()),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(XcmSubscriptions::remove_subscriber(
+            Origin::root(),
+            3000.into(),
+            key()
+        ));
+        assert_eq!(Balances::reserved_balance(3000), 0);
+        assert_eq!(XcmSubscriptions::subscriber_count(), 0);
+        assert_eq!(XcmSubscriptions::topic(bounded_key()), None);
+    });
+}
+
+#[test]
+fn subscriptions_go_through_xcm_rpc() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XcmSubscriptions::subscribe(
+            Origin::root(),
+            2000.into(),
+            key(),
+            50
+        ));
+        let request = VersionedRequest::V1 {
+            id: 0,
+            handler: Subscribe::<Test>::ID,
+            payload: SubscribeRequest {
+                key: key(),
+                max_weight: NOTIFICATION_WEIGHT,
+                max_fee: 50u64,
+            }
+            .encode(),
+        };
+        let (dest, _, call) = last_transact();
+        assert_eq!(dest, MultiLocation::new(1, X1(Parachain(2000))));
+        assert_eq!(
+            call,
+            Call::XcmRpc(pallet_xcm_rpc::Call::handle_request { request }).encode()
+        );
+
+        // Notifications are only accepted once the subscription is confirmed.
+        assert_noop!(
+            XcmSubscriptions::notify(sibling(2000), key(), Some(vec![1])),
+            Error::<Test>::NotSubscribed
+        );
+        assert_ok!(XcmRpc::handle_response(
+            sibling(2000),
+            VersionedResponse::V1 {
+                id: 0,
+                result: Ok(().encode()),
+            }
+        ));
+        System::assert_has_event(PalletEvent::Subscribed(2000.into(), bounded_key()).into());
+
+        assert_ok!(XcmSubscriptions::notify(
+            sibling(2000),
+            key(),
+            Some(vec![1])
+        ));
+        assert_eq!(notified(), vec![(2000.into(), key(), Some(vec![1]))]);
+        assert_noop!(
+            XcmSubscriptions::notify(sibling(3000), key(), Some(vec![1])),
+            Error::<Test>::NotSubscribed
+        );
+
+        // A rejected subscription.
+        assert_ok!(XcmSubscriptions::subscribe(
+            Origin::root(),
+            3000.into(),
+            key(),
+            50
+        ));
+        let error = RpcError::Failed(Error::<Test>::NotSubscribable.into());
+        assert_ok!(XcmRpc::handle_response(
+            sibling(3000),
+            VersionedResponse::V1 {
+                id: 1,
+                result: Err(error.clone()),
+            }
+        ));
+        System::assert_has_event(
+            PalletEvent::SubscriptionRejected(3000.into(), bounded_key(), error).into(),
+        );
+
+        assert_ok!(XcmSubscriptions::unsubscribe(
+            Origin::root(),
+            2000.into(),
+            key()
+        ));
+        let (_, _, call) = last_transact();
+        let request = VersionedRequest::V1 {
+            id: 2,
+            handler: Unsubscribe::<Test>::ID,
+            payload: key().encode(),
+        };
+        assert_eq!(
+            call,
+            Call::XcmRpc(pallet_xcm_rpc::Call::handle_request { request }).encode()
+        );
+        assert_noop!(
+            XcmSubscriptions::notify(sibling(2000), key(), Some(vec![2])),
+            Error::<Test>::NotSubscribed
+        );
+    });
+}
+
+#[test]
+fn subscriptions_are_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            add_subscriber_to(2000, LONG_VALUE_KEY),
+            Err(Error::<Test>::ValueTooLong.into())
+        );
+
+        assert_ok!(add_subscriber_to(2000, b"watched1"));
+        assert_ok!(add_subscriber_to(2000, b"watched2"));
+        assert_eq!(XcmSubscriptions::key_count(ParaId::from(2000)), 2);
+        assert_eq!(
+            add_subscriber_to(2000, b"watched3"),
+            Err(Error::<Test>::TooManyKeys.into())
+        );
+        // Other siblings have their own limit.
+        assert_ok!(add_subscriber_to(3000, b"watched3"));
+
+        assert_ok!(Unsubscribe::<Test>::handle_request(
+            2000.into(),
+            b"watched1".to_vec()
+        ));
+        assert_eq!(XcmSubscriptions::key_count(ParaId::from(2000)), 1);
+        assert_ok!(add_subscriber_to(2000, b"watched3"));
+    });
+}
+
+#[test]
+fn notifications_are_weighed_by_topics_and_subscribers() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(XcmSubscriptions::on_initialize(1), 0);
+
+        assert_ok!(add_subscriber_to(2000, b"watched1"));
+        assert_ok!(add_subscriber_to(3000, b"watched1"));
+        assert_ok!(add_subscriber_to(2000, b"watched2"));
+        assert_eq!(XcmSubscriptions::topic_count(), 2);
+
+        // Values of `MAX_VALUE_LEN` bytes, and a send weight of 100.
+        let value_weight = MAX_VALUE_LEN as Weight;
+        let per_topic = 10_000 + value_weight;
+        let per_subscriber = 10_000 + 100 + value_weight;
+        assert_eq!(
+            XcmSubscriptions::on_initialize(1),
+            2 * per_topic + 3 * per_subscriber
+        );
+
+        assert_ok!(XcmSubscriptions::remove_subscriber(
+            Origin::root(),
+            2000.into(),
+            b"watched2".to_vec()
+        ));
+        assert_eq!(XcmSubscriptions::topic_count(), 1);
+        assert_eq!(
+            XcmSubscriptions::on_initialize(1),
+            per_topic + 2 * per_subscriber
+        );
+    });
+}
+
+#[test]
+fn pending_subscriptions_are_dropped_on_timeout() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XcmSubscriptions::subscribe(
+            Origin::root(),
+            2000.into(),
+            key(),
+            50
+        ));
+        assert!(PendingSubscriptions::<Test>::contains_key(0));
+
+        // The request times out 10 blocks after it was sent.
+        System::set_block_number(11);
+        XcmRpc::on_idle(11, Weight::MAX);
+        assert!(!PendingSubscriptions::<Test>::contains_key(0));
+        System::assert_has_event(
+            PalletEvent::SubscriptionRejected(2000.into(), bounded_key(), RpcError::Timeout).into(),
+        );
+
+        // It can be asked for again.
+        assert_ok!(XcmSubscriptions::subscribe(
+            Origin::root(),
+            2000.into(),
+            key(),
+            50
+        ));
+    });
+}
@@ -0,0 +1,236 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Sells XCM execution for assets at governance-maintained rates.
+//!
+//! The rate of every asset accepted as payment, in units of the asset per second of weight, is
+//! kept in another pallet such as an asset registry and read through `Rates`.
+//! [`AssetRateTrader`], to be used as (part of) `XcmConfig::Trader`, charges messages at those
+//! rates, refunds the weight they didn't use and deposits what it kept into the fee beneficiary:
+//! the account set with `set_fee_beneficiary`, or `DefaultFeeBeneficiary`.
+
+use frame_support::{
+    pallet_prelude::*,
+    weights::{constants::WEIGHT_PER_SECOND, Weight},
+};
+use sp_runtime::traits::Convert;
+use sp_std::marker::PhantomData;
+use xcm::latest::prelude::*;
+use xcm_executor::{
+    traits::{TransactAsset, WeightTrader},
+    Assets,
+};
+
+pub use pallet::*;
+
+/// The rates assets are accepted at.
+pub trait UnitsPerSecondProvider {
+    /// Units of the asset at `asset` charged per second of weight, if it is accepted.
+    fn units_per_second(asset: &MultiLocation) -> Option<u128>;
+}
+
+impl UnitsPerSecondProvider for () {
+    fn units_per_second(_: &MultiLocation) -> Option<u128> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to change the fee beneficiary.
+        type FeeBeneficiaryOrigin: EnsureOrigin<Self::Origin>;
+
+        /// Deposits the fees into the fee beneficiary.
+        type AssetTransactor: TransactAsset;
+
+        /// Converts the fee beneficiary into the location fees are deposited to.
+        type AccountIdToMultiLocation: Convert<Self::AccountId, MultiLocation>;
+
+        /// The account receiving fees when no beneficiary is set, e.g. the block author. Fees are
+        /// burnt if there is none.
+        type DefaultFeeBeneficiary: Get<Option<Self::AccountId>>;
+
+        /// The rates of the assets accepted as payment.
+        type Rates: UnitsPerSecondProvider;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// The account receiving fees, overriding `DefaultFeeBeneficiary`.
+    #[pallet::storage]
+    #[pallet::getter(fn fee_beneficiary)]
+    pub type FeeBeneficiary<T: Config> = StorageValue<_, T::AccountId>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// The fee beneficiary was changed, `None` meaning `DefaultFeeBeneficiary`.
+        /// [beneficiary]
+        FeeBeneficiarySet(Option<T::AccountId>),
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Send fees to `beneficiary`, or to `DefaultFeeBeneficiary` if it is `None`.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        pub fn set_fee_beneficiary(
+            origin: OriginFor<T>,
+            beneficiary: Option<T::AccountId>,
+        ) -> DispatchResult {
+            T::FeeBeneficiaryOrigin::ensure_origin(origin)?;
+
+            FeeBeneficiary::<T>::set(beneficiary.clone());
+            Self::deposit_event(Event::FeeBeneficiarySet(beneficiary));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// The account fees currently go to.
+    pub fn current_fee_beneficiary() -> Option<T::AccountId> {
+        FeeBeneficiary::<T>::get().or_else(T::DefaultFeeBeneficiary::get)
+    }
+
+    /// Units of the asset at `asset` charged per second of weight, if it is accepted.
+    pub fn rate(asset: &MultiLocation) -> Option<u128> {
+        T::Rates::units_per_second(asset)
+    }
+
+    /// Deposit `fees` into the fee beneficiary. They are burnt if there is none or the deposit
+    /// fails.
+    fn take_revenue(fees: MultiAsset) {
+        if let Some(beneficiary) = Self::current_fee_beneficiary() {
+            let location = T::AccountIdToMultiLocation::convert(beneficiary);
+            if let Err(error) = T::AssetTransactor::deposit_asset(&fees, &location) {
+                log::warn!(
+                    target: "xcm::trader",
+                    "Failed to deposit {:?} into the fee beneficiary: {:?}",
+                    fees,
+                    error,
+                );
+            }
+        }
+    }
+}
+
+/// The price of `weight` at `units_per_second`, rounded up so that no weight is ever free.
+fn cost(units_per_second: u128, weight: Weight) -> u128 {
+    let weight_per_second = WEIGHT_PER_SECOND as u128;
+    units_per_second
+        .saturating_mul(weight as u128)
+        .saturating_add(weight_per_second - 1)
+        / weight_per_second
+}
+
+/// An asset being paid with, and the rate it was accepted at.
+struct Payment {
+    asset: MultiLocation,
+    units_per_second: u128,
+    amount: u128,
+}
+
+/// Buys weight with any asset that has a rate in `Rates`.
+///
+/// All weight bought for a message is paid with the same asset, at the rate of the first
+/// purchase. What was paid always equals the price of the weight still held, so refunds never
+/// give back more than was charged.
+pub struct AssetRateTrader<T: Config> {
+    weight: Weight,
+    payment: Option<Payment>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Config> AssetRateTrader<T> {
+    /// The first asset of `payment` that is accepted, and its rate.
+    fn accepted_asset(payment: &Assets) -> Option<(MultiLocation, u128)> {
+        payment.fungible.keys().find_map(|id| match id {
+            Concrete(location) => Pallet::<T>::rate(location)
+                .map(|units_per_second| (location.clone(), units_per_second)),
+            Abstract(_) => None,
+        })
+    }
+}
+
+impl<T: Config> WeightTrader for AssetRateTrader<T> {
+    fn new() -> Self {
+        Self {
+            weight: 0,
+            payment: None,
+            _marker: PhantomData,
+        }
+    }
+
+    fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, XcmError> {
+        let (asset, units_per_second, paid) = match &self.payment {
+            Some(p) => (p.asset.clone(), p.units_per_second, p.amount),
+            None => {
+                let (asset, units_per_second) =
+                    Self::accepted_asset(&payment).ok_or(XcmError::TooExpensive)?;
+                (asset, units_per_second, 0)
+            }
+        };
+
+        let total_weight = self.weight.saturating_add(weight);
+        let total_cost = cost(units_per_second, total_weight);
+        let amount = total_cost.saturating_sub(paid);
+        let unused = if amount == 0 {
+            payment
+        } else {
+            payment
+                .checked_sub((asset.clone(), amount).into())
+                .map_err(|_| XcmError::TooExpensive)?
+        };
+
+        self.weight = total_weight;
+        self.payment = Some(Payment {
+            asset,
+            units_per_second,
+            amount: total_cost,
+        });
+        Ok(unused)
+    }
+
+    fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
+        let payment = self.payment.as_mut()?;
+        let weight = weight.min(self.weight);
+        self.weight -= weight;
+        let still_owed = cost(payment.units_per_second, self.weight);
+        let refund = payment.amount.saturating_sub(still_owed);
+        payment.amount -= refund;
+
+        if refund > 0 {
+            Some((payment.asset.clone(), refund).into())
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Config> Drop for AssetRateTrader<T> {
+    fn drop(&mut self) {
+        if let Some(payment) = self.payment.take() {
+            if payment.amount > 0 {
+                Pallet::<T>::take_revenue((payment.asset, payment.amount).into());
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate as pallet_xcm_trader;
+use frame_support::{parameter_types, traits::Everything};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, Convert, IdentityLookup},
+};
+use sp_std::cell::RefCell;
+use xcm::latest::prelude::*;
+use xcm_executor::traits::TransactAsset;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        XcmTrader: pallet_xcm_trader::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub const BlockAuthor: Option<u64> = Some(AUTHOR);
+}
+
+/// The default fee beneficiary.
+pub const AUTHOR: u64 = 100;
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+thread_local! {
+    pub static DEPOSITS: RefCell<Vec<(MultiAsset, MultiLocation)>> = RefCell::new(Vec::new());
+}
+
+/// The fees deposited so far, with their beneficiary.
+pub fn deposits() -> Vec<(MultiAsset, MultiLocation)> {
+    DEPOSITS.with(|d| d.borrow().clone())
+}
+
+/// Records deposits instead of making them.
+pub struct RecordingTransactor;
+impl TransactAsset for RecordingTransactor {
+    fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> XcmResult {
+        DEPOSITS.with(|d| d.borrow_mut().push((what.clone(), who.clone())));
+        Ok(())
+    }
+}
+
+pub struct AccountIndexLocation;
+impl Convert<u64, MultiLocation> for AccountIndexLocation {
+    fn convert(index: u64) -> MultiLocation {
+        AccountIndex64 {
+            network: Any,
+            index,
+        }
+        .into()
+    }
+}
+
+thread_local! {
+    pub static RATES: RefCell<Vec<(MultiLocation, u128)>> = RefCell::new(Vec::new());
+}
+
+/// Accept `asset` at `units_per_second`.
+pub fn set_units_per_second(asset: MultiLocation, units_per_second: u128) {
+    RATES.with(|r| r.borrow_mut().push((asset, units_per_second)));
+}
+
+/// The rates set with `set_units_per_second`.
+pub struct MockRates;
+impl pallet_xcm_trader::UnitsPerSecondProvider for MockRates {
+    fn units_per_second(asset: &MultiLocation) -> Option<u128> {
+        RATES.with(|r| {
+            r.borrow()
+                .iter()
+                .find(|(location, _)| location == asset)
+                .map(|(_, units_per_second)| *units_per_second)
+        })
+    }
+}
+
+impl pallet_xcm_trader::Config for Test {
+    type Event = Event;
+    type FeeBeneficiaryOrigin = frame_system::EnsureRoot<u64>;
+    type AssetTransactor = RecordingTransactor;
+    type AccountIdToMultiLocation = AccountIndexLocation;
+    type DefaultFeeBeneficiary = BlockAuthor;
+    type Rates = MockRates;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
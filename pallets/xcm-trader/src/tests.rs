@@ -0,0 +1,110 @@
+use crate::{mock::*, AssetRateTrader};
+use frame_support::{assert_noop, assert_ok, weights::constants::WEIGHT_PER_SECOND};
+use xcm::latest::prelude::*;
+use xcm_executor::{traits::WeightTrader, Assets};
+
+fn sibling_token() -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(2000), GeneralIndex(0)))
+}
+
+fn payment(amount: u128) -> Assets {
+    MultiAsset::from((sibling_token(), amount)).into()
+}
+
+fn account(index: u64) -> MultiLocation {
+    AccountIndex64 {
+        network: Any,
+        index,
+    }
+    .into()
+}
+
+fn set_rate(units_per_second: u128) {
+    set_units_per_second(sibling_token(), units_per_second);
+}
+
+#[test]
+fn unused_weight_is_refunded_and_fees_go_to_beneficiary() {
+    new_test_ext().execute_with(|| {
+        // One unit per unit of weight.
+        set_rate(WEIGHT_PER_SECOND as u128);
+
+        let mut trader = AssetRateTrader::<Test>::new();
+        assert_eq!(trader.buy_weight(1_000, payment(5_000)), Ok(payment(4_000)));
+        assert_eq!(
+            trader.refund_weight(400),
+            Some((sibling_token(), 400).into())
+        );
+        drop(trader);
+
+        assert_eq!(
+            deposits(),
+            vec![((sibling_token(), 600).into(), account(AUTHOR))]
+        );
+    });
+}
+
+#[test]
+fn refunds_never_exceed_what_was_paid() {
+    new_test_ext().execute_with(|| {
+        // One unit per two units of weight.
+        set_rate(WEIGHT_PER_SECOND as u128 / 2);
+
+        let mut trader = AssetRateTrader::<Test>::new();
+        // Prices are rounded up: 3 weight costs 2, 6 weight costs 3.
+        assert_eq!(trader.buy_weight(3, payment(10)), Ok(payment(8)));
+        assert_eq!(trader.buy_weight(3, payment(10)), Ok(payment(9)));
+        // 5 weight still costs 3.
+        assert_eq!(trader.refund_weight(1), None);
+        // 4 weight costs 2.
+        assert_eq!(trader.refund_weight(1), Some((sibling_token(), 1).into()));
+        // Refunding more than was bought refunds everything left.
+        assert_eq!(trader.refund_weight(100), Some((sibling_token(), 2).into()));
+        drop(trader);
+
+        assert!(deposits().is_empty());
+    });
+}
+
+#[test]
+fn only_accepted_assets_can_pay() {
+    new_test_ext().execute_with(|| {
+        let mut trader = AssetRateTrader::<Test>::new();
+        assert_eq!(
+            trader.buy_weight(1_000, payment(5_000)),
+            Err(XcmError::TooExpensive)
+        );
+
+        set_rate(WEIGHT_PER_SECOND as u128);
+        assert_eq!(
+            trader.buy_weight(1_000, payment(999)),
+            Err(XcmError::TooExpensive)
+        );
+        let native: Assets = MultiAsset::from((MultiLocation::here(), 5_000)).into();
+        assert_eq!(
+            trader.buy_weight(1_000, native),
+            Err(XcmError::TooExpensive)
+        );
+    });
+}
+
+#[test]
+fn fee_beneficiary_can_be_changed() {
+    new_test_ext().execute_with(|| {
+        set_rate(WEIGHT_PER_SECOND as u128);
+        assert_noop!(
+            XcmTrader::set_fee_beneficiary(Origin::signed(1), Some(7)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(XcmTrader::set_fee_beneficiary(Origin::root(), Some(7)));
+
+        let mut trader = AssetRateTrader::<Test>::new();
+        assert_ok!(trader.buy_weight(1_000, payment(1_000)));
+        drop(trader);
+
+        assert_eq!(
+            deposits(),
+            vec![((sibling_token(), 1_000).into(), account(7))]
+        );
+    });
+}
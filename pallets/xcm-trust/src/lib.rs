@@ -0,0 +1,414 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Governance-managed XCM trust configuration.
+//!
+//! Keeps the trust relationships of this chain with other consensus systems in storage, so they
+//! can be changed without a runtime upgrade, and provides the `xcm_executor` adapters that read
+//! them:
+//!
+//! - [`IsTrustedReserve`], to be used as (part of) `XcmConfig::IsReserve`, accepts reserve-backed
+//!   assets only from the reserve locations registered for them.
+//! - [`IsTrustedTeleporter`], to be used as `XcmConfig::IsTeleporter`, accepts teleported assets
+//!   only from the locations registered as teleporters for them.
+//! - [`TeleportableAssets`], to be used as `pallet_xcm::Config::XcmTeleportFilter`, lets only
+//!   assets with a trusted teleporter be teleported.
+//! - [`TeleportsToTrustedOnly`], to be used in the base call filter, lets `pallet_xcm` teleport
+//!   assets only to the locations that would accept them back.
+//! - [`AllowUnpaidExecutionFromAllowlist`], a barrier letting the locations registered for it
+//!   execute without paying, up to a weight limit per block.
+//! - [`PaidExecutionAllowlist`], to be used with `xcm_builder::AllowTopLevelPaidExecutionFrom`,
+//!   lets the locations registered for it buy execution.
+
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Contains, IsSubType},
+    weights::Weight,
+};
+use sp_std::{boxed::Box, marker::PhantomData, vec::Vec};
+use xcm::{latest::prelude::*, VersionedMultiLocation};
+use xcm_executor::traits::{FilterAssetLocation, ShouldExecute};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Origin allowed to change the trust configuration.
+        type TrustOrigin: EnsureOrigin<Self::Origin>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::without_storage_info]
+    pub struct Pallet<T>(_);
+
+    /// Reserve locations trusted for an asset, keyed by asset location then reserve location.
+    #[pallet::storage]
+    #[pallet::getter(fn trusted_reserves)]
+    pub type TrustedReserves<T> =
+        StorageDoubleMap<_, Blake2_128Concat, MultiLocation, Blake2_128Concat, MultiLocation, ()>;
+
+    /// Locations trusted to teleport an asset, keyed by asset location then teleporter location.
+    #[pallet::storage]
+    #[pallet::getter(fn trusted_teleporters)]
+    pub type TrustedTeleporters<T> =
+        StorageDoubleMap<_, Blake2_128Concat, MultiLocation, Blake2_128Concat, MultiLocation, ()>;
+
+    /// Locations allowed to execute without paying, with the weight they may use per block.
+    #[pallet::storage]
+    #[pallet::getter(fn unpaid_execution_limit)]
+    pub type UnpaidExecutionLimits<T> = StorageMap<_, Blake2_128Concat, MultiLocation, Weight>;
+
+    /// Unpaid weight used by a location, and the block it was used in.
+    #[pallet::storage]
+    #[pallet::getter(fn unpaid_weight_used)]
+    pub type UnpaidWeightUsed<T: Config> =
+        StorageMap<_, Blake2_128Concat, MultiLocation, (T::BlockNumber, Weight)>;
+
+    /// Locations allowed to buy execution.
+    #[pallet::storage]
+    #[pallet::getter(fn paid_execution_allowed)]
+    pub type PaidExecutionAllowed<T> = StorageMap<_, Blake2_128Concat, MultiLocation, ()>;
+
+    #[pallet::genesis_config]
+    #[derive(Default)]
+    pub struct GenesisConfig {
+        /// Siblings, by parachain id, allowed to execute without paying, with the weight they may
+        /// use per block.
+        pub unpaid_execution_siblings: Vec<(u32, Weight)>,
+        /// Siblings, by parachain id, allowed to buy execution.
+        pub paid_execution_siblings: Vec<u32>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            for (para_id, limit) in &self.unpaid_execution_siblings {
+                UnpaidExecutionLimits::<T>::insert(sibling(*para_id), limit);
+            }
+            for para_id in &self.paid_execution_siblings {
+                PaidExecutionAllowed::<T>::insert(sibling(*para_id), ());
+            }
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Reserve-backed deposits of an asset are now accepted from a location.
+        /// [asset, reserve]
+        ReserveTrusted(MultiLocation, MultiLocation),
+        /// Reserve-backed deposits of an asset are no longer accepted from a location.
+        /// [asset, reserve]
+        ReserveUntrusted(MultiLocation, MultiLocation),
+        /// An asset can now be teleported to and from a location. [asset, teleporter]
+        TeleporterTrusted(MultiLocation, MultiLocation),
+        /// An asset can no longer be teleported to and from a location. [asset, teleporter]
+        TeleporterUntrusted(MultiLocation, MultiLocation),
+        /// The weight a location may execute without paying per block was changed, `None`
+        /// meaning it must pay. [location, limit]
+        UnpaidExecutionLimitSet(MultiLocation, Option<Weight>),
+        /// A location can now buy execution. [location]
+        PaidExecutionAllowed(MultiLocation),
+        /// A location can no longer buy execution. [location]
+        PaidExecutionDisallowed(MultiLocation),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// A location could not be converted to the latest XCM version.
+        BadVersion,
+        /// The reserve is already trusted for this asset.
+        AlreadyTrusted,
+        /// The reserve or teleporter is not trusted for this asset.
+        NotTrusted,
+        /// The location is already allowed to buy execution.
+        AlreadyAllowed,
+        /// The location is not allowed to buy execution.
+        NotAllowed,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Accept reserve-backed deposits of the asset at `asset` coming from `reserve`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn trust_reserve(
+            origin: OriginFor<T>,
+            asset: Box<VersionedMultiLocation>,
+            reserve: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let asset = Self::latest(*asset)?;
+            let reserve = Self::latest(*reserve)?;
+            ensure!(
+                !TrustedReserves::<T>::contains_key(&asset, &reserve),
+                Error::<T>::AlreadyTrusted
+            );
+
+            TrustedReserves::<T>::insert(&asset, &reserve, ());
+            Self::deposit_event(Event::ReserveTrusted(asset, reserve));
+            Ok(())
+        }
+
+        /// Stop accepting reserve-backed deposits of the asset at `asset` from `reserve`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn untrust_reserve(
+            origin: OriginFor<T>,
+            asset: Box<VersionedMultiLocation>,
+            reserve: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let asset = Self::latest(*asset)?;
+            let reserve = Self::latest(*reserve)?;
+            ensure!(
+                TrustedReserves::<T>::take(&asset, &reserve).is_some(),
+                Error::<T>::NotTrusted
+            );
+
+            Self::deposit_event(Event::ReserveUntrusted(asset, reserve));
+            Ok(())
+        }
+
+        /// Allow the asset at `asset` to be teleported to and from `teleporter`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn trust_teleporter(
+            origin: OriginFor<T>,
+            asset: Box<VersionedMultiLocation>,
+            teleporter: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let asset = Self::latest(*asset)?;
+            let teleporter = Self::latest(*teleporter)?;
+            ensure!(
+                !TrustedTeleporters::<T>::contains_key(&asset, &teleporter),
+                Error::<T>::AlreadyTrusted
+            );
+
+            TrustedTeleporters::<T>::insert(&asset, &teleporter, ());
+            Self::deposit_event(Event::TeleporterTrusted(asset, teleporter));
+            Ok(())
+        }
+
+        /// Stop allowing the asset at `asset` to be teleported to and from `teleporter`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn untrust_teleporter(
+            origin: OriginFor<T>,
+            asset: Box<VersionedMultiLocation>,
+            teleporter: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let asset = Self::latest(*asset)?;
+            let teleporter = Self::latest(*teleporter)?;
+            ensure!(
+                TrustedTeleporters::<T>::take(&asset, &teleporter).is_some(),
+                Error::<T>::NotTrusted
+            );
+
+            Self::deposit_event(Event::TeleporterUntrusted(asset, teleporter));
+            Ok(())
+        }
+
+        /// Let `location` execute messages without paying for up to `limit` weight per block, or
+        /// make it pay again if `limit` is `None`.
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
+        pub fn set_unpaid_execution_limit(
+            origin: OriginFor<T>,
+            location: Box<VersionedMultiLocation>,
+            limit: Option<Weight>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let location = Self::latest(*location)?;
+
+            UnpaidExecutionLimits::<T>::set(&location, limit);
+            UnpaidWeightUsed::<T>::remove(&location);
+            Self::deposit_event(Event::UnpaidExecutionLimitSet(location, limit));
+            Ok(())
+        }
+
+        /// Let `location` buy execution.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn allow_paid_execution(
+            origin: OriginFor<T>,
+            location: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let location = Self::latest(*location)?;
+            ensure!(
+                !PaidExecutionAllowed::<T>::contains_key(&location),
+                Error::<T>::AlreadyAllowed
+            );
+
+            PaidExecutionAllowed::<T>::insert(&location, ());
+            Self::deposit_event(Event::PaidExecutionAllowed(location));
+            Ok(())
+        }
+
+        /// Stop letting `location` buy execution.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn disallow_paid_execution(
+            origin: OriginFor<T>,
+            location: Box<VersionedMultiLocation>,
+        ) -> DispatchResult {
+            T::TrustOrigin::ensure_origin(origin)?;
+            let location = Self::latest(*location)?;
+            ensure!(
+                PaidExecutionAllowed::<T>::take(&location).is_some(),
+                Error::<T>::NotAllowed
+            );
+
+            Self::deposit_event(Event::PaidExecutionDisallowed(location));
+            Ok(())
+        }
+    }
+}
+
+fn sibling(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+impl<T: Config> Pallet<T> {
+    fn latest(location: VersionedMultiLocation) -> Result<MultiLocation, DispatchError> {
+        MultiLocation::try_from(location).map_err(|()| Error::<T>::BadVersion.into())
+    }
+
+    /// Whether `reserve` is trusted as a reserve of the asset at `asset`.
+    pub fn is_trusted_reserve(asset: &MultiLocation, reserve: &MultiLocation) -> bool {
+        TrustedReserves::<T>::contains_key(asset, reserve)
+    }
+
+    /// Account for `weight` of unpaid execution by `origin` in the current block, failing if it
+    /// isn't allowed to execute unpaid or would go over its limit.
+    fn use_unpaid_weight(origin: &MultiLocation, weight: Weight) -> Result<(), ()> {
+        let limit = UnpaidExecutionLimits::<T>::get(origin).ok_or(())?;
+        let now = frame_system::Pallet::<T>::block_number();
+        let used = match UnpaidWeightUsed::<T>::get(origin) {
+            Some((block, used)) if block == now => used,
+            _ => 0,
+        };
+        let used = used
+            .checked_add(weight)
+            .filter(|used| *used <= limit)
+            .ok_or(())?;
+        UnpaidWeightUsed::<T>::insert(origin, (now, used));
+        Ok(())
+    }
+
+    /// Whether the asset at `asset` can be teleported to and from `teleporter`.
+    pub fn is_trusted_teleporter(asset: &MultiLocation, teleporter: &MultiLocation) -> bool {
+        TrustedTeleporters::<T>::contains_key(asset, teleporter)
+    }
+
+    /// Whether every asset of `assets` can be teleported to and from `dest`.
+    pub fn can_teleport_to(dest: &MultiLocation, assets: &[MultiAsset]) -> bool {
+        assets
+            .iter()
+            .all(|asset| IsTrustedTeleporter::<T>::filter_asset_location(asset, dest))
+    }
+}
+
+/// Accepts a reserve-backed asset if its origin is registered as a trusted reserve for it.
+pub struct IsTrustedReserve<T>(PhantomData<T>);
+
+impl<T: Config> FilterAssetLocation for IsTrustedReserve<T> {
+    fn filter_asset_location(asset: &MultiAsset, origin: &MultiLocation) -> bool {
+        match &asset.id {
+            Concrete(location) => Pallet::<T>::is_trusted_reserve(location, origin),
+            Abstract(_) => false,
+        }
+    }
+}
+
+/// Accepts a teleported asset if its origin is registered as a trusted teleporter for it.
+pub struct IsTrustedTeleporter<T>(PhantomData<T>);
+
+impl<T: Config> FilterAssetLocation for IsTrustedTeleporter<T> {
+    fn filter_asset_location(asset: &MultiAsset, origin: &MultiLocation) -> bool {
+        match &asset.id {
+            Concrete(location) => Pallet::<T>::is_trusted_teleporter(location, origin),
+            Abstract(_) => false,
+        }
+    }
+}
+
+/// Allows an outgoing teleport only of assets with a trusted teleporter. `pallet_xcm` gives it
+/// the origin of the teleport rather than its destination, which [`TeleportsToTrustedOnly`]
+/// checks.
+pub struct TeleportableAssets<T>(PhantomData<T>);
+
+impl<T: Config> Contains<(MultiLocation, Vec<MultiAsset>)> for TeleportableAssets<T> {
+    fn contains((_origin, assets): &(MultiLocation, Vec<MultiAsset>)) -> bool {
+        assets.iter().all(|asset| match &asset.id {
+            Concrete(location) => TrustedTeleporters::<T>::iter_prefix(location)
+                .next()
+                .is_some(),
+            Abstract(_) => false,
+        })
+    }
+}
+
+/// Allows the teleport calls of `pallet_xcm` only if the destination is a trusted teleporter for
+/// every asset, so that it can be teleported back. Other calls are allowed.
+pub struct TeleportsToTrustedOnly<T>(PhantomData<T>);
+
+impl<T> Contains<<T as frame_system::Config>::Call> for TeleportsToTrustedOnly<T>
+where
+    T: Config + pallet_xcm::Config,
+    <T as frame_system::Config>::Call: IsSubType<pallet_xcm::Call<T>>,
+{
+    fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+        let (dest, assets) = match call.is_sub_type() {
+            Some(pallet_xcm::Call::teleport_assets { dest, assets, .. })
+            | Some(pallet_xcm::Call::limited_teleport_assets { dest, assets, .. }) => {
+                (dest, assets)
+            }
+            _ => return true,
+        };
+        match (
+            MultiLocation::try_from((**dest).clone()),
+            MultiAssets::try_from((**assets).clone()),
+        ) {
+            (Ok(dest), Ok(assets)) => Pallet::<T>::can_teleport_to(&dest, assets.inner()),
+            // `pallet_xcm` rejects them.
+            _ => true,
+        }
+    }
+}
+
+/// Allows execution without payment from the locations in `UnpaidExecutionLimits`, as long as
+/// they stay within their weight limit for the block.
+pub struct AllowUnpaidExecutionFromAllowlist<T>(PhantomData<T>);
+
+impl<T: Config> ShouldExecute for AllowUnpaidExecutionFromAllowlist<T> {
+    fn should_execute<Call>(
+        origin: &MultiLocation,
+        _message: &mut Xcm<Call>,
+        max_weight: Weight,
+        _weight_credit: &mut Weight,
+    ) -> Result<(), ()> {
+        Pallet::<T>::use_unpaid_weight(origin, max_weight)
+    }
+}
+
+/// Contains the locations in `PaidExecutionAllowed`.
+pub struct PaidExecutionAllowlist<T>(PhantomData<T>);
+
+impl<T: Config> Contains<MultiLocation> for PaidExecutionAllowlist<T> {
+    fn contains(location: &MultiLocation) -> bool {
+        PaidExecutionAllowed::<T>::contains_key(location)
+    }
+}
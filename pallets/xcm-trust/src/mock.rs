@@ -0,0 +1,193 @@
+use crate::{
+    self as pallet_xcm_trust, IsTrustedTeleporter, TeleportableAssets, TeleportsToTrustedOnly,
+};
+use frame_support::{
+    parameter_types,
+    traits::{ConstU64, GenesisBuild, Nothing},
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::{borrow::Borrow, cell::RefCell};
+use xcm::latest::prelude::*;
+use xcm_builder::{
+    CurrencyAdapter, EnsureXcmOrigin, FixedWeightBounds, IsConcrete, LocationInverter,
+    TakeWeightCredit,
+};
+use xcm_executor::{traits::Convert, XcmExecutor};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        PolkadotXcm: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
+        XcmTrust: pallet_xcm_trust::{Pallet, Call, Storage, Event<T>, Config},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub Ancestry: MultiLocation = Parachain(100).into();
+    pub const HereLocation: MultiLocation = MultiLocation::here();
+    pub const UnitWeightCost: Weight = 10;
+    pub const MaxInstructions: u32 = 100;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = TeleportsToTrustedOnly<Test>;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type Balance = u64;
+    type DustRemoval = ();
+    type Event = Event;
+    type ExistentialDeposit = ConstU64<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+}
+
+thread_local! {
+    pub static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages sent so far, with their destination.
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+    SENT.with(|s| s.borrow().clone())
+}
+
+/// Records the messages instead of sending them.
+pub struct RecordingSender;
+impl SendXcm for RecordingSender {
+    fn send_xcm(dest: impl Into<MultiLocation>, message: Xcm<()>) -> SendResult {
+        SENT.with(|s| s.borrow_mut().push((dest.into(), message)));
+        Ok(())
+    }
+}
+
+/// Local accounts are `AccountIndex64` locations.
+pub struct AccountIndexLocation;
+impl Convert<MultiLocation, u64> for AccountIndexLocation {
+    fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<u64, ()> {
+        match location.borrow() {
+            MultiLocation {
+                parents: 0,
+                interior: X1(AccountIndex64 { index, .. }),
+            } => Ok(*index),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Convert<Origin, MultiLocation> for AccountIndexLocation {
+    fn convert(origin: Origin) -> Result<MultiLocation, Origin> {
+        let raw: Result<system::RawOrigin<u64>, Origin> = origin.clone().into();
+        match raw {
+            Ok(system::RawOrigin::Signed(index)) => Ok(AccountIndex64 {
+                network: Any,
+                index,
+            }
+            .into()),
+            _ => Err(origin),
+        }
+    }
+}
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+    type Call = Call;
+    type XcmSender = RecordingSender;
+    type AssetTransactor =
+        CurrencyAdapter<Balances, IsConcrete<HereLocation>, AccountIndexLocation, u64, ()>;
+    type OriginConverter = ();
+    type IsReserve = ();
+    type IsTeleporter = IsTrustedTeleporter<Test>;
+    type LocationInverter = LocationInverter<Ancestry>;
+    type Barrier = TakeWeightCredit;
+    type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+    type Trader = ();
+    type ResponseHandler = ();
+    type AssetTrap = ();
+    type AssetClaims = ();
+    type SubscriptionService = ();
+}
+
+impl pallet_xcm::Config for Test {
+    type Event = Event;
+    type SendXcmOrigin = EnsureXcmOrigin<Origin, AccountIndexLocation>;
+    type XcmRouter = RecordingSender;
+    type ExecuteXcmOrigin = EnsureXcmOrigin<Origin, AccountIndexLocation>;
+    type XcmExecuteFilter = Nothing;
+    type XcmExecutor = XcmExecutor<XcmConfig>;
+    type XcmTeleportFilter = TeleportableAssets<Test>;
+    type XcmReserveTransferFilter = Nothing;
+    type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+    type LocationInverter = LocationInverter<Ancestry>;
+    type Origin = Origin;
+    type Call = Call;
+
+    const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+    type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+}
+
+impl pallet_xcm_trust::Config for Test {
+    type Event = Event;
+    type TrustOrigin = frame_system::EnsureRoot<u64>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    new_test_ext_with(Default::default())
+}
+
+// Build genesis storage with the given trust configuration.
+pub fn new_test_ext_with(trust: pallet_xcm_trust::GenesisConfig) -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 1_000)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    GenesisBuild::<Test>::assimilate_storage(&trust, &mut storage).unwrap();
+    storage.into()
+}
@@ -0,0 +1,262 @@
+use crate::{
+    mock::*, AllowUnpaidExecutionFromAllowlist, Error, IsTrustedReserve, IsTrustedTeleporter,
+    PaidExecutionAllowlist, TeleportableAssets,
+};
+use frame_support::{assert_noop, assert_ok, dispatch::Dispatchable, traits::Contains};
+use xcm::{latest::prelude::*, VersionedMultiLocation};
+use xcm_executor::traits::{FilterAssetLocation, ShouldExecute};
+
+fn sibling(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+fn sibling_token(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(para_id), GeneralIndex(0)))
+}
+
+fn versioned(location: MultiLocation) -> Box<VersionedMultiLocation> {
+    Box::new(location.into())
+}
+
+#[test]
+fn trusted_reserve_is_accepted_only_from_its_reserve() {
+    new_test_ext().execute_with(|| {
+        let asset: MultiAsset = (sibling_token(2000), 100).into();
+        assert!(!IsTrustedReserve::<Test>::filter_asset_location(
+            &asset,
+            &sibling(2000)
+        ));
+
+        assert_ok!(XcmTrust::trust_reserve(
+            Origin::root(),
+            versioned(sibling_token(2000)),
+            versioned(sibling(2000))
+        ));
+        assert!(IsTrustedReserve::<Test>::filter_asset_location(
+            &asset,
+            &sibling(2000)
+        ));
+        assert!(!IsTrustedReserve::<Test>::filter_asset_location(
+            &asset,
+            &sibling(3000)
+        ));
+
+        assert_ok!(XcmTrust::untrust_reserve(
+            Origin::root(),
+            versioned(sibling_token(2000)),
+            versioned(sibling(2000))
+        ));
+        assert!(!IsTrustedReserve::<Test>::filter_asset_location(
+            &asset,
+            &sibling(2000)
+        ));
+    });
+}
+
+#[test]
+fn trust_reserve_checks_origin_and_duplicates() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            XcmTrust::trust_reserve(
+                Origin::signed(1),
+                versioned(sibling_token(2000)),
+                versioned(sibling(2000))
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(XcmTrust::trust_reserve(
+            Origin::root(),
+            versioned(sibling_token(2000)),
+            versioned(sibling(2000))
+        ));
+        assert_noop!(
+            XcmTrust::trust_reserve(
+                Origin::root(),
+                versioned(sibling_token(2000)),
+                versioned(sibling(2000))
+            ),
+            Error::<Test>::AlreadyTrusted
+        );
+        assert_noop!(
+            XcmTrust::untrust_reserve(
+                Origin::root(),
+                versioned(sibling_token(2000)),
+                versioned(sibling(3000))
+            ),
+            Error::<Test>::NotTrusted
+        );
+    });
+}
+
+#[test]
+fn teleports_are_allowed_only_with_trusted_teleporters() {
+    new_test_ext().execute_with(|| {
+        let native: MultiAsset = (MultiLocation::here(), 100).into();
+        let asset_hub = sibling(1000);
+        let account: MultiLocation = AccountIndex64 {
+            network: Any,
+            index: 1,
+        }
+        .into();
+        assert!(!IsTrustedTeleporter::<Test>::filter_asset_location(
+            &native, &asset_hub
+        ));
+        assert!(!TeleportableAssets::<Test>::contains(&(
+            account.clone(),
+            vec![native.clone()]
+        )));
+
+        assert_ok!(XcmTrust::trust_teleporter(
+            Origin::root(),
+            versioned(MultiLocation::here()),
+            versioned(asset_hub.clone())
+        ));
+        assert!(IsTrustedTeleporter::<Test>::filter_asset_location(
+            &native, &asset_hub
+        ));
+        assert!(TeleportableAssets::<Test>::contains(&(
+            account.clone(),
+            vec![native.clone()]
+        )));
+        // Every asset must have a trusted teleporter.
+        assert!(!TeleportableAssets::<Test>::contains(&(
+            account,
+            vec![native.clone(), (sibling_token(2000), 100).into()]
+        )));
+
+        assert_ok!(XcmTrust::untrust_teleporter(
+            Origin::root(),
+            versioned(MultiLocation::here()),
+            versioned(asset_hub.clone())
+        ));
+        assert!(!IsTrustedTeleporter::<Test>::filter_asset_location(
+            &native, &asset_hub
+        ));
+    });
+}
+
+#[test]
+fn pallet_xcm_teleports_only_to_trusted_teleporters() {
+    new_test_ext().execute_with(|| {
+        let teleport = |dest: MultiLocation| {
+            Call::PolkadotXcm(pallet_xcm::Call::limited_teleport_assets {
+                dest: versioned(dest),
+                beneficiary: versioned(
+                    AccountIndex64 {
+                        network: Any,
+                        index: 2,
+                    }
+                    .into(),
+                ),
+                assets: Box::new(
+                    MultiAssets::from(vec![(MultiLocation::here(), 100).into()]).into(),
+                ),
+                fee_asset_item: 0,
+                weight_limit: Unlimited,
+            })
+            .dispatch(Origin::signed(1))
+        };
+        let asset_hub = sibling(1000);
+        assert_noop!(
+            teleport(asset_hub.clone()),
+            frame_system::Error::<Test>::CallFiltered
+        );
+
+        assert_ok!(XcmTrust::trust_teleporter(
+            Origin::root(),
+            versioned(MultiLocation::here()),
+            versioned(asset_hub.clone())
+        ));
+        assert_noop!(
+            teleport(sibling(2000)),
+            frame_system::Error::<Test>::CallFiltered
+        );
+        assert_ok!(teleport(asset_hub.clone()));
+        assert_eq!(Balances::free_balance(1), 900);
+        let (dest, message) = sent().pop().unwrap();
+        assert_eq!(dest, asset_hub);
+        assert!(matches!(message.0[..], [ReceiveTeleportedAsset(_), ..]));
+    });
+}
+
+fn should_execute_unpaid(origin: &MultiLocation, weight: u64) -> bool {
+    AllowUnpaidExecutionFromAllowlist::<Test>::should_execute(
+        origin,
+        &mut Xcm::<()>(vec![ClearOrigin]),
+        weight,
+        &mut 0,
+    )
+    .is_ok()
+}
+
+#[test]
+fn unpaid_execution_is_limited_per_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert!(!should_execute_unpaid(&sibling(2000), 1));
+
+        assert_ok!(XcmTrust::set_unpaid_execution_limit(
+            Origin::root(),
+            versioned(sibling(2000)),
+            Some(100)
+        ));
+        assert!(should_execute_unpaid(&sibling(2000), 60));
+        assert!(!should_execute_unpaid(&sibling(2000), 60));
+        assert!(should_execute_unpaid(&sibling(2000), 40));
+        assert!(!should_execute_unpaid(&sibling(3000), 1));
+
+        // The limit applies again in the next block.
+        System::set_block_number(2);
+        assert!(should_execute_unpaid(&sibling(2000), 100));
+
+        assert_ok!(XcmTrust::set_unpaid_execution_limit(
+            Origin::root(),
+            versioned(sibling(2000)),
+            None
+        ));
+        System::set_block_number(3);
+        assert!(!should_execute_unpaid(&sibling(2000), 1));
+    });
+}
+
+#[test]
+fn paid_execution_is_allowed_only_from_allowlist() {
+    new_test_ext().execute_with(|| {
+        assert!(!PaidExecutionAllowlist::<Test>::contains(&sibling(2000)));
+
+        assert_ok!(XcmTrust::allow_paid_execution(
+            Origin::root(),
+            versioned(sibling(2000))
+        ));
+        assert!(PaidExecutionAllowlist::<Test>::contains(&sibling(2000)));
+        assert!(!PaidExecutionAllowlist::<Test>::contains(&sibling(3000)));
+        assert_noop!(
+            XcmTrust::allow_paid_execution(Origin::root(), versioned(sibling(2000))),
+            Error::<Test>::AlreadyAllowed
+        );
+
+        assert_ok!(XcmTrust::disallow_paid_execution(
+            Origin::root(),
+            versioned(sibling(2000))
+        ));
+        assert!(!PaidExecutionAllowlist::<Test>::contains(&sibling(2000)));
+        assert_noop!(
+            XcmTrust::disallow_paid_execution(Origin::root(), versioned(sibling(2000))),
+            Error::<Test>::NotAllowed
+        );
+    });
+}
+
+#[test]
+fn genesis_allows_siblings() {
+    new_test_ext_with(crate::GenesisConfig {
+        unpaid_execution_siblings: vec![(2000, 100)],
+        paid_execution_siblings: vec![3000],
+    })
+    .execute_with(|| {
+        assert!(should_execute_unpaid(&sibling(2000), 100));
+        assert!(!should_execute_unpaid(&sibling(3000), 1));
+        assert!(PaidExecutionAllowlist::<Test>::contains(&sibling(3000)));
+        assert!(!PaidExecutionAllowlist::<Test>::contains(&sibling(2000)));
+    });
+}
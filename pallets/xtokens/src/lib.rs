@@ -0,0 +1,437 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Cross-chain transfers of fungible assets.
+//!
+//! `transfer` takes a currency, an amount and the full location of the beneficiary on another
+//! chain, builds the local XCM program moving the asset there and executes it on behalf of the
+//! sender:
+//!
+//! - assets the destination is a trusted teleporter of are teleported there with
+//!   `InitiateTeleport`;
+//! - assets this chain is the reserve of are moved into the sovereign account of the
+//!   destination with `TransferReserveAsset`;
+//! - assets whose reserve is the destination are burnt here and withdrawn from our sovereign
+//!   account on the destination with `InitiateReserveWithdraw`;
+//! - any other asset goes through its reserve, which forwards it to the destination.
+//!
+//! `transfer_with_fee` sends another asset along to pay for execution, and `transfer_multiasset`
+//! takes the asset as a raw `MultiAsset`. The `Transferred` event carries the hash of the message
+//! sent, to trace it on the destination.
+
+use frame_support::{pallet_prelude::*, weights::Weight};
+use sp_runtime::traits::{AtLeast32BitUnsigned, Convert};
+use sp_std::{boxed::Box, vec, vec::Vec};
+use xcm::{latest::prelude::*, VersionedMultiAsset, VersionedMultiLocation};
+use xcm_executor::traits::{FilterAssetLocation, InvertLocation, WeightBounds};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type CallOf<T> = <T as frame_system::Config>::Call;
+
+/// How an asset gets to its destination.
+#[derive(Clone, PartialEq, Eq)]
+enum Route {
+    /// Teleported, the destination being trusted to teleport it.
+    Teleport,
+    /// Moved into the sovereign account of the destination, this chain being its reserve.
+    LocalReserve,
+    /// Burnt here and withdrawn from our sovereign account on the destination, its reserve.
+    DestReserve,
+    /// Through its reserve, which forwards it to the destination.
+    RemoteReserve(MultiLocation),
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// The balance of a currency.
+        type Balance: Parameter + Member + AtLeast32BitUnsigned + Copy + Into<u128>;
+
+        /// Identifies the currencies that can be transferred.
+        type CurrencyId: Parameter + Member;
+
+        /// Gives the location of a currency, if it can be transferred.
+        type CurrencyIdConvert: Convert<Self::CurrencyId, Option<MultiLocation>>;
+
+        /// Converts a local account into the XCM location transfers are executed from.
+        type AccountIdToMultiLocation: Convert<Self::AccountId, MultiLocation>;
+
+        /// Tells whether a destination is trusted to teleport an asset, in which case the asset
+        /// is teleported there rather than going through its reserve.
+        type IsTeleporter: FilterAssetLocation;
+
+        /// Executes the local part of a transfer.
+        type XcmExecutor: ExecuteXcm<CallOf<Self>>;
+
+        /// Weighs the local part of a transfer.
+        type Weigher: WeightBounds<CallOf<Self>>;
+
+        /// Gives the location of this chain, used to re-anchor assets and locations as seen from
+        /// other chains.
+        type LocationInverter: InvertLocation;
+
+        /// Gives the hash of the last message sent, reported in the event of a transfer.
+        type SentMessageHash: Get<Option<[u8; 32]>>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Assets were sent to another chain, in the message with the given hash.
+        /// [sender, assets, dest, message_hash]
+        Transferred(T::AccountId, MultiAssets, MultiLocation, Option<[u8; 32]>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// An asset or location could not be converted to the latest XCM version.
+        BadVersion,
+        /// The currency has no known location.
+        UnknownCurrency,
+        /// The asset is not a non-zero amount of a fungible asset.
+        InvalidAsset,
+        /// The fee asset is not a non-zero amount of a fungible asset other than the one
+        /// transferred.
+        InvalidFee,
+        /// The fee asset would take another route to the destination than the one transferred.
+        FeeRouteMismatch,
+        /// The destination is not an account on another chain.
+        InvalidDest,
+        /// The reserve of the asset cannot be determined.
+        AssetHasNoReserve,
+        /// An asset or location cannot be expressed as seen from the destination or reserve.
+        CannotReanchor,
+        /// The local XCM program of the transfer could not be weighed.
+        UnweighableMessage,
+        /// The local XCM program of the transfer failed.
+        XcmExecutionFailed,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Send `amount` of `currency_id` to the account `dest` on another chain, e.g.
+        /// `(1, X2(Parachain(2000), AccountId32 { .. }))` for an account on sibling 2000.
+        ///
+        /// `weight_limit` is the weight bought on the destination, and on the reserve if the
+        /// asset goes through it, to deposit the asset. It is paid with the transferred asset.
+        #[pallet::weight(Pallet::<T>::weight_of_transfer(
+            Pallet::<T>::currency_asset(currency_id.clone(), *amount).ok(),
+            None,
+            dest,
+            weight_limit,
+        ))]
+        pub fn transfer(
+            origin: OriginFor<T>,
+            currency_id: T::CurrencyId,
+            amount: T::Balance,
+            dest: Box<VersionedMultiLocation>,
+            weight_limit: WeightLimit,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let asset = Self::currency_asset(currency_id, amount)?;
+            let dest = Self::latest(*dest)?;
+
+            Self::do_transfer(who, asset, None, dest, weight_limit)
+        }
+
+        /// Send `amount` of `currency_id` to the account `dest` on another chain like
+        /// `transfer`, along with `fee` of `fee_currency_id` to pay for execution.
+        ///
+        /// Both assets take the same route, so they must be teleported to the destination or
+        /// share their reserve.
+        #[pallet::weight(Pallet::<T>::weight_of_transfer(
+            Pallet::<T>::currency_asset(currency_id.clone(), *amount).ok(),
+            Pallet::<T>::currency_asset(fee_currency_id.clone(), *fee).ok(),
+            dest,
+            weight_limit,
+        ))]
+        pub fn transfer_with_fee(
+            origin: OriginFor<T>,
+            currency_id: T::CurrencyId,
+            amount: T::Balance,
+            fee_currency_id: T::CurrencyId,
+            fee: T::Balance,
+            dest: Box<VersionedMultiLocation>,
+            weight_limit: WeightLimit,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let asset = Self::currency_asset(currency_id, amount)?;
+            let fee = Self::currency_asset(fee_currency_id, fee)?;
+            let dest = Self::latest(*dest)?;
+
+            Self::do_transfer(who, asset, Some(fee), dest, weight_limit)
+        }
+
+        /// Send `asset` to the account `dest` on another chain like `transfer`.
+        #[pallet::weight(Pallet::<T>::weight_of_transfer(
+            MultiAsset::try_from((**asset).clone()).ok(),
+            None,
+            dest,
+            weight_limit,
+        ))]
+        pub fn transfer_multiasset(
+            origin: OriginFor<T>,
+            asset: Box<VersionedMultiAsset>,
+            dest: Box<VersionedMultiLocation>,
+            weight_limit: WeightLimit,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let asset = MultiAsset::try_from(*asset).map_err(|()| Error::<T>::BadVersion)?;
+            let dest = Self::latest(*dest)?;
+
+            Self::do_transfer(who, asset, None, dest, weight_limit)
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    fn latest(location: VersionedMultiLocation) -> Result<MultiLocation, DispatchError> {
+        MultiLocation::try_from(location).map_err(|()| Error::<T>::BadVersion.into())
+    }
+
+    /// `amount` of the currency `currency_id` as an XCM asset.
+    fn currency_asset(
+        currency_id: T::CurrencyId,
+        amount: T::Balance,
+    ) -> Result<MultiAsset, DispatchError> {
+        let location =
+            T::CurrencyIdConvert::convert(currency_id).ok_or(Error::<T>::UnknownCurrency)?;
+        Ok((location, amount.into()).into())
+    }
+
+    /// The weight of the local XCM program of a transfer, or zero if it can't be built.
+    fn weight_of_transfer(
+        asset: Option<MultiAsset>,
+        fee: Option<MultiAsset>,
+        dest: &VersionedMultiLocation,
+        weight_limit: &WeightLimit,
+    ) -> Weight {
+        let dest = MultiLocation::try_from(dest.clone()).ok();
+        asset
+            .zip(dest)
+            .and_then(|(asset, dest)| {
+                Self::transfer_message(asset, fee, dest, weight_limit.clone()).ok()
+            })
+            .and_then(|mut message| T::Weigher::weight(&mut message).ok())
+            .unwrap_or(0)
+    }
+
+    /// Execute the local XCM program sending `asset`, and `fee` if any, to the account `dest`.
+    fn do_transfer(
+        who: T::AccountId,
+        asset: MultiAsset,
+        fee: Option<MultiAsset>,
+        dest: MultiLocation,
+        weight_limit: WeightLimit,
+    ) -> DispatchResult {
+        let mut message =
+            Self::transfer_message(asset.clone(), fee.clone(), dest.clone(), weight_limit)?;
+        let weight =
+            T::Weigher::weight(&mut message).map_err(|()| Error::<T>::UnweighableMessage)?;
+        let origin_location = T::AccountIdToMultiLocation::convert(who.clone());
+        T::XcmExecutor::execute_xcm_in_credit(origin_location, message, weight, weight)
+            .ensure_complete()
+            .map_err(|_| Error::<T>::XcmExecutionFailed)?;
+
+        let assets = MultiAssets::from(fee.into_iter().chain(Some(asset)).collect::<Vec<_>>());
+        Self::deposit_event(Event::Transferred(
+            who,
+            assets,
+            dest,
+            T::SentMessageHash::get(),
+        ));
+        Ok(())
+    }
+
+    /// Build the local XCM program sending `asset` to the account `dest`, paying for execution
+    /// with `fee`, or with `asset` itself if there is no fee asset.
+    fn transfer_message(
+        asset: MultiAsset,
+        fee: Option<MultiAsset>,
+        dest: MultiLocation,
+        weight_limit: WeightLimit,
+    ) -> Result<Xcm<CallOf<T>>, DispatchError> {
+        Self::amount_of(&asset).ok_or(Error::<T>::InvalidAsset)?;
+        let (chain, beneficiary) = Self::split_dest(dest)?;
+        let route = Self::route(&asset, &chain)?;
+
+        let (fee, assets) = match fee {
+            Some(fee) => {
+                ensure!(
+                    fee.id != asset.id && Self::amount_of(&fee).is_some(),
+                    Error::<T>::InvalidFee
+                );
+                ensure!(
+                    Self::route(&fee, &chain)? == route,
+                    Error::<T>::FeeRouteMismatch
+                );
+                (fee.clone(), MultiAssets::from(vec![asset, fee]))
+            }
+            None => (asset.clone(), MultiAssets::from(asset)),
+        };
+        let max_assets = assets.len() as u32;
+
+        let message = match route {
+            Route::Teleport => {
+                let fees = Self::reanchored(fee, &chain)?;
+                Xcm(vec![
+                    WithdrawAsset(assets),
+                    InitiateTeleport {
+                        assets: Wild(All),
+                        dest: chain,
+                        xcm: Self::deposit_message(fees, weight_limit, max_assets, beneficiary),
+                    },
+                ])
+            }
+            Route::LocalReserve => {
+                let fees = Self::reanchored(fee, &chain)?;
+                Xcm(vec![TransferReserveAsset {
+                    assets,
+                    dest: chain,
+                    xcm: Self::deposit_message(fees, weight_limit, max_assets, beneficiary),
+                }])
+            }
+            Route::DestReserve => {
+                let fees = Self::reanchored(fee, &chain)?;
+                Xcm(vec![
+                    WithdrawAsset(assets),
+                    InitiateReserveWithdraw {
+                        assets: Wild(All),
+                        reserve: chain,
+                        xcm: Self::deposit_message(fees, weight_limit, max_assets, beneficiary),
+                    },
+                ])
+            }
+            Route::RemoteReserve(reserve) => {
+                // Half of the fee asset pays for execution on the reserve, the other half on the
+                // destination. Whatever isn't used is deposited with the rest.
+                let half = MultiAsset {
+                    id: fee.id.clone(),
+                    fun: Fungible(Self::amount_of(&fee).unwrap_or_default() / 2),
+                };
+                let reserve_fees = Self::reanchored(half.clone(), &reserve)?;
+                let dest_fees = Self::reanchored(half, &chain)?;
+                let ancestry = T::LocationInverter::ancestry();
+                let chain_from_reserve = chain
+                    .reanchored(&reserve, &ancestry)
+                    .map_err(|_| Error::<T>::CannotReanchor)?;
+                Xcm(vec![
+                    WithdrawAsset(assets),
+                    InitiateReserveWithdraw {
+                        assets: Wild(All),
+                        reserve,
+                        xcm: Xcm(vec![
+                            BuyExecution {
+                                fees: reserve_fees,
+                                weight_limit: weight_limit.clone(),
+                            },
+                            DepositReserveAsset {
+                                assets: Wild(All),
+                                max_assets,
+                                dest: chain_from_reserve,
+                                xcm: Self::deposit_message(
+                                    dest_fees,
+                                    weight_limit,
+                                    max_assets,
+                                    beneficiary,
+                                ),
+                            },
+                        ]),
+                    },
+                ])
+            }
+        };
+        Ok(message)
+    }
+
+    /// The program run on the destination: buy execution and deposit everything to the
+    /// beneficiary.
+    fn deposit_message(
+        fees: MultiAsset,
+        weight_limit: WeightLimit,
+        max_assets: u32,
+        beneficiary: MultiLocation,
+    ) -> Xcm<()> {
+        Xcm(vec![
+            BuyExecution { fees, weight_limit },
+            DepositAsset {
+                assets: Wild(All),
+                max_assets,
+                beneficiary,
+            },
+        ])
+    }
+
+    /// The amount of `asset`, if it is a non-zero amount of a fungible asset.
+    fn amount_of(asset: &MultiAsset) -> Option<u128> {
+        match asset.fun {
+            Fungible(amount) if amount > 0 => Some(amount),
+            _ => None,
+        }
+    }
+
+    /// Split the location of an account on another chain into the chain and the account as seen
+    /// from that chain.
+    fn split_dest(dest: MultiLocation) -> Result<(MultiLocation, MultiLocation), DispatchError> {
+        let (chain, account) = dest.split_last_interior();
+        let account = account.ok_or(Error::<T>::InvalidDest)?;
+        ensure!(chain != MultiLocation::here(), Error::<T>::InvalidDest);
+        Ok((chain, account.into()))
+    }
+
+    /// How `asset` gets to the chain `chain`.
+    fn route(asset: &MultiAsset, chain: &MultiLocation) -> Result<Route, DispatchError> {
+        if T::IsTeleporter::filter_asset_location(asset, chain) {
+            return Ok(Route::Teleport);
+        }
+        let reserve = Self::reserve_of(asset).ok_or(Error::<T>::AssetHasNoReserve)?;
+        Ok(if reserve == MultiLocation::here() {
+            Route::LocalReserve
+        } else if reserve == *chain {
+            Route::DestReserve
+        } else {
+            Route::RemoteReserve(reserve)
+        })
+    }
+
+    /// The chain holding the reserve of `asset`, as seen from this chain.
+    fn reserve_of(asset: &MultiAsset) -> Option<MultiLocation> {
+        let location = match &asset.id {
+            Concrete(location) => location,
+            Abstract(_) => return None,
+        };
+        match (location.parents, location.first_interior()) {
+            (0, _) => Some(MultiLocation::here()),
+            (1, Some(Parachain(id))) => Some(MultiLocation::new(1, X1(Parachain(*id)))),
+            (1, _) => Some(MultiLocation::parent()),
+            _ => None,
+        }
+    }
+
+    /// `asset` as seen from `target`.
+    fn reanchored(asset: MultiAsset, target: &MultiLocation) -> Result<MultiAsset, DispatchError> {
+        let ancestry = T::LocationInverter::ancestry();
+        asset
+            .reanchored(target, &ancestry)
+            .map_err(|()| Error::<T>::CannotReanchor.into())
+    }
+}
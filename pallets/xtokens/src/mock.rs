@@ -0,0 +1,155 @@
+use crate as pallet_xtokens;
+use frame_support::{parameter_types, traits::Everything, weights::Weight};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, Convert, IdentityLookup},
+};
+use sp_std::cell::RefCell;
+use xcm::latest::prelude::*;
+use xcm_builder::LocationInverter;
+use xcm_executor::traits::{FilterAssetLocation, WeightBounds};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        XTokens: pallet_xtokens::{Pallet, Call, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+    pub Ancestry: MultiLocation = Parachain(1000).into();
+    pub const SentMessageHash: Option<[u8; 32]> = Some([7; 32]);
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+thread_local! {
+    pub static EXECUTED: RefCell<Vec<(MultiLocation, Xcm<Call>)>> = RefCell::new(Vec::new());
+}
+
+/// The messages executed so far, with their origin.
+pub fn executed() -> Vec<(MultiLocation, Xcm<Call>)> {
+    EXECUTED.with(|e| e.borrow().clone())
+}
+
+/// Records the messages instead of executing them.
+pub struct RecordingExecutor;
+impl ExecuteXcm<Call> for RecordingExecutor {
+    fn execute_xcm_in_credit(
+        origin: impl Into<MultiLocation>,
+        message: Xcm<Call>,
+        weight_limit: Weight,
+        _weight_credit: Weight,
+    ) -> Outcome {
+        EXECUTED.with(|e| e.borrow_mut().push((origin.into(), message)));
+        Outcome::Complete(weight_limit)
+    }
+}
+
+/// Every instruction weighs 10.
+pub struct UnitWeigher;
+impl WeightBounds<Call> for UnitWeigher {
+    fn weight(message: &mut Xcm<Call>) -> Result<Weight, ()> {
+        Ok(message.0.len() as Weight * 10)
+    }
+
+    fn instr_weight(_instruction: &Instruction<Call>) -> Result<Weight, ()> {
+        Ok(10)
+    }
+}
+
+pub struct AccountIndexLocation;
+impl Convert<u64, MultiLocation> for AccountIndexLocation {
+    fn convert(index: u64) -> MultiLocation {
+        AccountIndex64 {
+            network: Any,
+            index,
+        }
+        .into()
+    }
+}
+
+/// The currency with id 0 is the native asset, 1 the relay token, 5 a local asset and any other
+/// id the token `GeneralIndex(0)` of the sibling with that id.
+pub struct CurrencyIdConvert;
+impl Convert<u32, Option<MultiLocation>> for CurrencyIdConvert {
+    fn convert(id: u32) -> Option<MultiLocation> {
+        match id {
+            0 => Some(MultiLocation::here()),
+            1 => Some(MultiLocation::parent()),
+            5 => Some(MultiLocation::new(
+                0,
+                X2(PalletInstance(50), GeneralIndex(5)),
+            )),
+            id if id >= 2000 => Some(MultiLocation::new(1, X2(Parachain(id), GeneralIndex(0)))),
+            _ => None,
+        }
+    }
+}
+
+/// The native asset can be teleported to sibling 3000.
+pub struct NativeTeleporter;
+impl FilterAssetLocation for NativeTeleporter {
+    fn filter_asset_location(asset: &MultiAsset, dest: &MultiLocation) -> bool {
+        asset.id == Concrete(MultiLocation::here())
+            && *dest == MultiLocation::new(1, X1(Parachain(3000)))
+    }
+}
+
+impl pallet_xtokens::Config for Test {
+    type Event = Event;
+    type Balance = u128;
+    type CurrencyId = u32;
+    type CurrencyIdConvert = CurrencyIdConvert;
+    type AccountIdToMultiLocation = AccountIndexLocation;
+    type IsTeleporter = NativeTeleporter;
+    type XcmExecutor = RecordingExecutor;
+    type Weigher = UnitWeigher;
+    type LocationInverter = LocationInverter<Ancestry>;
+    type SentMessageHash = SentMessageHash;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
@@ -0,0 +1,322 @@
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok};
+use xcm::{latest::prelude::*, VersionedMultiAsset, VersionedMultiLocation};
+
+const ALICE: u64 = 1;
+const DEST_WEIGHT: u64 = 1_000;
+
+fn beneficiary() -> Junction {
+    AccountId32 {
+        network: Any,
+        id: [1; 32],
+    }
+}
+
+fn sibling(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+fn sibling_token(para_id: u32) -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(para_id), GeneralIndex(0)))
+}
+
+fn versioned_asset(location: MultiLocation, amount: u128) -> Box<VersionedMultiAsset> {
+    Box::new(MultiAsset::from((location, amount)).into())
+}
+
+fn versioned(location: MultiLocation) -> Box<VersionedMultiLocation> {
+    Box::new(location.into())
+}
+
+fn deposit(fees: MultiAsset) -> Xcm<()> {
+    deposit_assets(fees, 1)
+}
+
+fn deposit_assets(fees: MultiAsset, max_assets: u32) -> Xcm<()> {
+    Xcm(vec![
+        BuyExecution {
+            fees,
+            weight_limit: Limited(DEST_WEIGHT),
+        },
+        DepositAsset {
+            assets: Wild(All),
+            max_assets,
+            beneficiary: beneficiary().into(),
+        },
+    ])
+}
+
+fn local_asset() -> MultiLocation {
+    MultiLocation::new(0, X2(PalletInstance(50), GeneralIndex(5)))
+}
+
+#[test]
+fn native_asset_is_transferred_to_the_sovereign_account_of_the_destination() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let dest = MultiLocation::new(1, X2(Parachain(2000), beneficiary()));
+        assert_ok!(XTokens::transfer_multiasset(
+            Origin::signed(ALICE),
+            versioned_asset(MultiLocation::here(), 100),
+            versioned(dest.clone()),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let origin: MultiLocation = AccountIndex64 {
+            network: Any,
+            index: ALICE,
+        }
+        .into();
+        assert_eq!(
+            executed(),
+            vec![(
+                origin,
+                Xcm(vec![TransferReserveAsset {
+                    assets: (MultiLocation::here(), 100).into(),
+                    dest: sibling(2000),
+                    xcm: deposit((sibling(1000), 100).into()),
+                }])
+            )]
+        );
+        System::assert_last_event(
+            crate::Event::Transferred(
+                ALICE,
+                (MultiLocation::here(), 100).into(),
+                dest,
+                Some([7; 32]),
+            )
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn asset_is_withdrawn_from_its_reserve_when_sent_there() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XTokens::transfer_multiasset(
+            Origin::signed(ALICE),
+            versioned_asset(MultiLocation::parent(), 100),
+            versioned(MultiLocation::new(1, X1(beneficiary()))),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let (_, message) = executed().pop().unwrap();
+        assert_eq!(
+            message,
+            Xcm(vec![
+                WithdrawAsset((MultiLocation::parent(), 100).into()),
+                InitiateReserveWithdraw {
+                    assets: Wild(All),
+                    reserve: MultiLocation::parent(),
+                    xcm: deposit((MultiLocation::here(), 100).into()),
+                },
+            ])
+        );
+    });
+}
+
+#[test]
+fn asset_goes_through_its_reserve_to_a_third_chain() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XTokens::transfer_multiasset(
+            Origin::signed(ALICE),
+            versioned_asset(sibling_token(2000), 100),
+            versioned(MultiLocation::new(1, X2(Parachain(3000), beneficiary()))),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let (_, message) = executed().pop().unwrap();
+        assert_eq!(
+            message,
+            Xcm(vec![
+                WithdrawAsset((sibling_token(2000), 100).into()),
+                InitiateReserveWithdraw {
+                    assets: Wild(All),
+                    reserve: sibling(2000),
+                    xcm: Xcm(vec![
+                        BuyExecution {
+                            fees: (MultiLocation::new(0, X1(GeneralIndex(0))), 50).into(),
+                            weight_limit: Limited(DEST_WEIGHT),
+                        },
+                        DepositReserveAsset {
+                            assets: Wild(All),
+                            max_assets: 1,
+                            dest: sibling(3000),
+                            xcm: deposit((sibling_token(2000), 50).into()),
+                        },
+                    ]),
+                },
+            ])
+        );
+    });
+}
+
+#[test]
+fn transfer_rejects_invalid_assets_and_destinations() {
+    new_test_ext().execute_with(|| {
+        let dest = MultiLocation::new(1, X2(Parachain(2000), beneficiary()));
+        assert_noop!(
+            XTokens::transfer_multiasset(
+                Origin::signed(ALICE),
+                versioned_asset(MultiLocation::here(), 0),
+                versioned(dest.clone()),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::InvalidAsset
+        );
+        assert_noop!(
+            XTokens::transfer_multiasset(
+                Origin::signed(ALICE),
+                versioned_asset(MultiLocation::here(), 100),
+                versioned(MultiLocation::new(0, X1(beneficiary()))),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::InvalidDest
+        );
+        assert_noop!(
+            XTokens::transfer_multiasset(
+                Origin::signed(ALICE),
+                versioned_asset(MultiLocation::grandparent(), 100),
+                versioned(dest),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::AssetHasNoReserve
+        );
+        assert!(executed().is_empty());
+    });
+}
+
+#[test]
+fn currency_is_transferred_by_id() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let dest = MultiLocation::new(1, X1(beneficiary()));
+        assert_ok!(XTokens::transfer(
+            Origin::signed(ALICE),
+            1,
+            100,
+            versioned(dest.clone()),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let (_, message) = executed().pop().unwrap();
+        assert_eq!(
+            message,
+            Xcm(vec![
+                WithdrawAsset((MultiLocation::parent(), 100).into()),
+                InitiateReserveWithdraw {
+                    assets: Wild(All),
+                    reserve: MultiLocation::parent(),
+                    xcm: deposit((MultiLocation::here(), 100).into()),
+                },
+            ])
+        );
+        System::assert_last_event(
+            crate::Event::Transferred(
+                ALICE,
+                (MultiLocation::parent(), 100).into(),
+                dest,
+                Some([7; 32]),
+            )
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn native_asset_is_teleported_to_trusted_teleporters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XTokens::transfer(
+            Origin::signed(ALICE),
+            0,
+            100,
+            versioned(MultiLocation::new(1, X2(Parachain(3000), beneficiary()))),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let (_, message) = executed().pop().unwrap();
+        assert_eq!(
+            message,
+            Xcm(vec![
+                WithdrawAsset((MultiLocation::here(), 100).into()),
+                InitiateTeleport {
+                    assets: Wild(All),
+                    dest: sibling(3000),
+                    xcm: deposit((sibling(1000), 100).into()),
+                },
+            ])
+        );
+    });
+}
+
+#[test]
+fn fee_asset_pays_for_execution() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(XTokens::transfer_with_fee(
+            Origin::signed(ALICE),
+            5,
+            100,
+            0,
+            10,
+            versioned(MultiLocation::new(1, X2(Parachain(2000), beneficiary()))),
+            Limited(DEST_WEIGHT)
+        ));
+
+        let (_, message) = executed().pop().unwrap();
+        let assets: Vec<MultiAsset> = vec![
+            (local_asset(), 100).into(),
+            (MultiLocation::here(), 10).into(),
+        ];
+        assert_eq!(
+            message,
+            Xcm(vec![TransferReserveAsset {
+                assets: assets.into(),
+                dest: sibling(2000),
+                xcm: deposit_assets((sibling(1000), 10).into(), 2),
+            }])
+        );
+    });
+}
+
+#[test]
+fn fee_asset_must_be_another_asset_taking_the_same_route() {
+    new_test_ext().execute_with(|| {
+        let dest = MultiLocation::new(1, X2(Parachain(2000), beneficiary()));
+        // The relay token goes through the relay chain, the native asset doesn't.
+        assert_noop!(
+            XTokens::transfer_with_fee(
+                Origin::signed(ALICE),
+                0,
+                100,
+                1,
+                10,
+                versioned(dest.clone()),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::FeeRouteMismatch
+        );
+        assert_noop!(
+            XTokens::transfer_with_fee(
+                Origin::signed(ALICE),
+                0,
+                100,
+                0,
+                10,
+                versioned(dest.clone()),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::InvalidFee
+        );
+        assert_noop!(
+            XTokens::transfer(
+                Origin::signed(ALICE),
+                2,
+                100,
+                versioned(dest),
+                Limited(DEST_WEIGHT)
+            ),
+            Error::<Test>::UnknownCurrency
+        );
+        assert!(executed().is_empty());
+    });
+}
@@ -0,0 +1,142 @@
+//! Runtime APIs exposed by this runtime on top of the ones provided by FRAME and Cumulus.
+
+use codec::{Codec, Decode, Encode};
+use frame_support::weights::Weight;
+use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{ApplyExtrinsicResult, RuntimeDebug};
+use sp_std::vec::Vec;
+use xcm::{latest::Outcome, prelude::XcmVersion, VersionedMultiLocation, VersionedXcm};
+
+pub use pallet_overweight_xcm::{OverweightMessage, Queue as OverweightXcmQueue};
+pub use pallet_xcm_log::{LogEntry as XcmLogEntry, LoggedMessage};
+
+/// XCM versions used to communicate with another chain.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct XcmVersionInfo {
+    /// The version the chain advertised, `None` until version discovery completes.
+    pub negotiated: Option<XcmVersion>,
+    /// The version messages to the chain are wrapped in, `None` if they can't be sent at all.
+    pub used: Option<XcmVersion>,
+}
+
+/// What dry-running an extrinsic did.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ExtrinsicDryRunEffects<Event, Balance> {
+    /// The result of applying the extrinsic.
+    pub result: ApplyExtrinsicResult,
+    /// The events emitted while applying it.
+    pub events: Vec<Event>,
+    /// The weight it used after refunds, zero if it was not applied.
+    pub actual_weight: Weight,
+    /// The fee paid in NB, not counting any tip, as reported by `TransactionFeePaid`. `None`
+    /// if it paid none, or paid in an asset, which `AssetTxPayment` reports no event for.
+    pub fee: Option<Balance>,
+    /// The XCM messages it sent, with their destination.
+    pub sent_xcm: Vec<(VersionedMultiLocation, VersionedXcm<()>)>,
+}
+
+/// What dry-running an XCM message did.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct XcmDryRunEffects<Event> {
+    /// The outcome of executing the message, with the weight it used.
+    pub outcome: Outcome,
+    /// The events emitted while executing it.
+    pub events: Vec<Event>,
+    /// The XCM messages it sent, with their destination.
+    pub sent_xcm: Vec<(VersionedMultiLocation, VersionedXcm<()>)>,
+}
+
+sp_api::decl_runtime_apis! {
+    /// Fee queries for transactions that pay their fees in an asset through
+    /// `pallet_asset_tx_payment::ChargeAssetTxPayment`.
+    pub trait AssetTxPaymentApi<Balance, AssetId> where
+        Balance: Codec,
+        AssetId: Codec,
+    {
+        /// Same as `TransactionPaymentApi::query_info`, with the partial fee expressed in
+        /// `asset_id`. Returns `None` if the asset cannot be used to pay fees.
+        fn query_info_in_asset(
+            uxt: Block::Extrinsic,
+            len: u32,
+            asset_id: AssetId,
+        ) -> Option<RuntimeDispatchInfo<Balance>>;
+
+        /// Same as `TransactionPaymentApi::query_fee_details`, with every component expressed in
+        /// `asset_id`. Returns `None` if the asset cannot be used to pay fees.
+        fn query_fee_details_in_asset(
+            uxt: Block::Extrinsic,
+            len: u32,
+            asset_id: AssetId,
+        ) -> Option<FeeDetails<Balance>>;
+    }
+
+    /// Local accounts of XCM locations.
+    pub trait LocationToAccountApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// The local account controlled by `location`, which is used to `Transact` from and
+        /// receives its asset deposits. Returns `None` if `location` has no local account or
+        /// can't be converted to the latest version.
+        fn location_to_account(location: VersionedMultiLocation) -> Option<AccountId>;
+    }
+
+    /// XCM version negotiation with other chains.
+    pub trait XcmVersionApi {
+        /// The versions used with `dest`, or `None` if it can't be converted to the latest
+        /// version.
+        fn xcm_version_for(dest: VersionedMultiLocation) -> Option<XcmVersionInfo>;
+    }
+
+    /// Weight and fee estimates for XCM messages executed by this chain.
+    pub trait XcmFeeApi<Balance> where
+        Balance: Codec,
+    {
+        /// The weight `XcmConfig::Weigher` gives `message` when executing it here. Returns
+        /// `None` if it can't be weighed or converted to the latest version.
+        fn query_xcm_weight(message: VersionedXcm<()>) -> Option<Weight>;
+
+        /// The fee `XcmConfig::Trader` charges here for `weight`, in the asset at `asset`.
+        /// Returns `None` if the asset is not accepted as payment or can't be converted to the
+        /// latest version.
+        fn query_xcm_fee(weight: Weight, asset: VersionedMultiLocation) -> Option<Balance>;
+    }
+
+    /// Dry runs of extrinsics and XCM messages. Like every runtime API call, they run on top of
+    /// the state of a block and nothing they change is kept.
+    pub trait DryRunApi<Event, Balance> where
+        Event: Codec,
+        Balance: Codec,
+    {
+        /// Apply `uxt` as if it was included in the next block.
+        fn dry_run_extrinsic(uxt: Block::Extrinsic) -> ExtrinsicDryRunEffects<Event, Balance>;
+
+        /// Execute `message` as if it was received from `origin` in the next block. Returns
+        /// `None` if either can't be converted to the latest version.
+        fn dry_run_xcm(
+            origin: VersionedMultiLocation,
+            message: VersionedXcm<()>,
+        ) -> Option<XcmDryRunEffects<Event>>;
+    }
+
+    /// The recent XCM messages logged by `XcmLog`.
+    pub trait XcmLogApi<BlockNumber> where
+        BlockNumber: Codec,
+    {
+        /// The last `count` messages sent or executed, most recent first.
+        fn recent_xcm_messages(count: u32) -> Vec<XcmLogEntry<BlockNumber>>;
+
+        /// The logged messages with the hash `hash`, oldest first.
+        fn xcm_messages_with_hash(hash: [u8; 32]) -> Vec<XcmLogEntry<BlockNumber>>;
+    }
+
+    /// The overweight XCM messages parked by `XcmpQueue` and `DmpQueue`.
+    pub trait OverweightXcmApi {
+        /// The messages still parked, decoded and weighed as the XCM executor would.
+        fn overweight_xcm_messages() -> Vec<OverweightMessage>;
+    }
+}
@@ -0,0 +1,149 @@
+//! Dry runs of extrinsics and XCM messages, backing `apis::DryRunApi`.
+
+use super::{
+    apis::{ExtrinsicDryRunEffects, XcmDryRunEffects},
+    xcm_config::InboundXcmExecutor,
+    Balance, Block, Call, Event, Executive, ParachainSystem, Runtime, RuntimeBlockWeights, System,
+    XcmpQueue,
+};
+use codec::Decode;
+use cumulus_primitives_core::{ParaId, UpwardMessage, XcmpMessageFormat};
+use frame_support::pallet_prelude::{Blake2_128Concat, Twox64Concat, ValueQuery};
+use sp_runtime::traits::Block as BlockT;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm};
+
+/// Apply `uxt` and report what it did.
+pub fn dry_run_extrinsic(
+    uxt: <Block as BlockT>::Extrinsic,
+) -> ExtrinsicDryRunEffects<Event, Balance> {
+    // The events of the block the state is taken from are still there.
+    System::reset_events();
+    let queued = QueuedXcm::snapshot();
+
+    let result = Executive::apply_extrinsic(uxt);
+
+    let events = emitted_events();
+    // The weight of the extrinsic after dispatch, as noted by `System`.
+    let actual_weight = events
+        .iter()
+        .find_map(|event| match event {
+            Event::System(frame_system::Event::ExtrinsicSuccess { dispatch_info })
+            | Event::System(frame_system::Event::ExtrinsicFailed { dispatch_info, .. }) => {
+                Some(dispatch_info.weight)
+            }
+            _ => None,
+        })
+        .unwrap_or(0);
+    // The fee actually withdrawn once the unused weight was refunded.
+    let fee = events.iter().find_map(|event| match event {
+        Event::TransactionPayment(pallet_transaction_payment::Event::TransactionFeePaid {
+            actual_fee,
+            tip,
+            ..
+        }) => Some(actual_fee.saturating_sub(*tip)),
+        _ => None,
+    });
+
+    ExtrinsicDryRunEffects {
+        result,
+        events,
+        actual_weight,
+        fee,
+        sent_xcm: queued.sent_since(),
+    }
+}
+
+/// Execute `message` as if it was received from `origin` and report what it did.
+pub fn dry_run_xcm(origin: MultiLocation, message: Xcm<Call>) -> XcmDryRunEffects<Event> {
+    System::reset_events();
+    let queued = QueuedXcm::snapshot();
+    let weight_limit = RuntimeBlockWeights::get().max_block;
+
+    let outcome = InboundXcmExecutor::execute_xcm(origin, message, weight_limit);
+
+    XcmDryRunEffects {
+        outcome,
+        events: emitted_events(),
+        sent_xcm: queued.sent_since(),
+    }
+}
+
+fn emitted_events() -> Vec<Event> {
+    System::events()
+        .into_iter()
+        .map(|record| record.event)
+        .collect()
+}
+
+/// The queues `XcmTransports` send through, whose storage is private to their pallets.
+mod outbound_queues {
+    use super::*;
+
+    /// The pages of messages queued for each sibling, each starting with the format of the
+    /// messages it holds.
+    #[frame_support::storage_alias]
+    pub type OutboundXcmpMessages = StorageDoubleMap<
+        XcmpQueue,
+        Blake2_128Concat,
+        ParaId,
+        Twox64Concat,
+        u16,
+        Vec<u8>,
+        ValueQuery,
+    >;
+
+    /// The messages queued for the relay chain.
+    #[frame_support::storage_alias]
+    pub type PendingUpwardMessages = StorageValue<ParachainSystem, Vec<UpwardMessage>, ValueQuery>;
+}
+
+/// How much the outbound queues held when a dry run started.
+struct QueuedXcm {
+    upward: usize,
+    pages: BTreeMap<(ParaId, u16), usize>,
+}
+
+impl QueuedXcm {
+    fn snapshot() -> Self {
+        Self {
+            upward: outbound_queues::PendingUpwardMessages::decode_len().unwrap_or(0),
+            pages: outbound_queues::OutboundXcmpMessages::iter()
+                .map(|(para, page, data)| ((para, page), data.len()))
+                .collect(),
+        }
+    }
+
+    /// The messages queued since, with their destination: those for the relay chain first, then
+    /// those for each sibling.
+    fn sent_since(self) -> Vec<(VersionedMultiLocation, VersionedXcm<()>)> {
+        let mut sent: Vec<_> = outbound_queues::PendingUpwardMessages::get()
+            .into_iter()
+            .skip(self.upward)
+            .filter_map(|message| VersionedXcm::<()>::decode(&mut &message[..]).ok())
+            .map(|message| (MultiLocation::parent().into(), message))
+            .collect();
+
+        for (para, page, data) in outbound_queues::OutboundXcmpMessages::iter() {
+            let mut added = match self.pages.get(&(para, page)) {
+                Some(&len) => data.get(len..).unwrap_or_default(),
+                None => {
+                    let mut data = &data[..];
+                    if !matches!(
+                        XcmpMessageFormat::decode(&mut data),
+                        Ok(XcmpMessageFormat::ConcatenatedVersionedXcm)
+                    ) {
+                        continue;
+                    }
+                    data
+                }
+            };
+            let dest: VersionedMultiLocation =
+                MultiLocation::new(1, X1(Parachain(para.into()))).into();
+            while let Ok(message) = VersionedXcm::<()>::decode(&mut added) {
+                sent.push((dest.clone(), message));
+            }
+        }
+        sent
+    }
+}
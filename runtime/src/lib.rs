@@ -6,16 +6,21 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+pub mod apis;
+mod dry_run;
+#[cfg(test)]
+mod tests;
 mod weights;
 pub mod xcm_config;
 
+use codec::MaxEncodedLen;
 use cumulus_pallet_parachain_system::RelayNumberStrictlyIncreases;
 use smallvec::smallvec;
 use sp_api::impl_runtime_apis;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
-    traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, Verify},
+    traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, Verify},
     transaction_validity::{TransactionSource, TransactionValidity},
     ApplyExtrinsicResult, MultiSignature,
 };
@@ -27,7 +32,12 @@ use sp_version::RuntimeVersion;
 
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::Everything,
+    storage::StoragePrefixedMap,
+    traits::{
+        fungibles::{Balanced, CreditOf},
+        tokens::BalanceConversion,
+        Contains, EitherOfDiverse, Get, ValidatorRegistration,
+    },
     weights::{
         constants::WEIGHT_PER_SECOND, ConstantMultiplier, DispatchClass, Weight,
         WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial,
@@ -40,7 +50,7 @@ use frame_system::{
 };
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 pub use sp_runtime::{MultiAddress, Perbill, Permill};
-use xcm_config::{XcmConfig, XcmOriginToTransactDispatchOrigin};
+use xcm_config::{RelayGovernanceOrigin, XcmOriginToTransactDispatchOrigin};
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -51,9 +61,8 @@ use polkadot_runtime_common::{BlockHashCount, SlowAdjustingFeeUpdate};
 use weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight};
 
 // XCM Imports
-use crate::xcm_config::XcmRouter;
+use crate::xcm_config::{InboundXcmExecutor, XcmRouter};
 use xcm::latest::prelude::BodyId;
-use xcm_executor::XcmExecutor;
 
 /// Import the template pallet.
 pub use pallet_template;
@@ -61,6 +70,9 @@ pub use pallet_template;
 /// Import counter pallet
 pub use pallet_counter;
 
+/// Import relay operations pallet, whose genesis depends on the relay chain.
+pub use pallet_relay_operations;
+
 /// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
 pub type Signature = MultiSignature;
 
@@ -71,6 +83,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset held in `pallet_assets`.
+pub type AssetId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -104,7 +119,7 @@ pub type SignedExtra = (
     frame_system::CheckEra<Runtime>,
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
-    pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -261,6 +276,47 @@ parameter_types! {
     pub const SS58Prefix: u16 = 42;
 }
 
+/// The runtime's base call filter: rejects calls that are never allowed, regardless of origin,
+/// and defers everything else to `MaintenanceMode`.
+pub struct BaseFilter;
+
+impl Contains<Call> for BaseFilter {
+    fn contains(call: &Call) -> bool {
+        // Ids of foreign assets are reserved for `AssetRegistry`. It adopts assets that already
+        // exist under the ids it registers, so otherwise anyone could create an asset under one
+        // and own what is later received under it. `pallet_assets` has no hook for this.
+        if matches!(
+            call,
+            Call::Assets(pallet_assets::Call::create { id, .. })
+                if <Runtime as pallet_asset_registry::Config>::ForeignAssetIds::contains(id)
+        ) {
+            return false;
+        }
+        // Assets can only be teleported to locations that would accept them back.
+        if !pallet_xcm_trust::TeleportsToTrustedOnly::<Runtime>::contains(call) {
+            return false;
+        }
+        MaintenanceMode::contains(call)
+    }
+}
+
+/// Calls that stay available in maintenance mode and can't be paused: inherents, system and
+/// sudo calls, and the maintenance switches themselves.
+pub struct MaintenanceWhitelist;
+
+impl Contains<Call> for MaintenanceWhitelist {
+    fn contains(call: &Call) -> bool {
+        matches!(
+            call,
+            Call::System(_)
+                | Call::ParachainSystem(_)
+                | Call::Timestamp(_)
+                | Call::Sudo(_)
+                | Call::MaintenanceMode(_)
+        )
+    }
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
@@ -299,7 +355,7 @@ impl frame_system::Config for Runtime {
     /// The weight of database operations that the runtime can invoke.
     type DbWeight = RocksDbWeight;
     /// The basic call filter to use in dispatchable.
-    type BaseCallFilter = Everything;
+    type BaseCallFilter = BaseFilter;
     /// Weight information for the extrinsics of this pallet.
     type SystemWeightInfo = ();
     /// Block & extrinsics weights: base values and limits.
@@ -371,6 +427,120 @@ impl pallet_transaction_payment::Config for Runtime {
     type OperationalFeeMultiplier = OperationalFeeMultiplier;
 }
 
+parameter_types! {
+    pub const AssetDeposit: Balance = 100 * UNIT;
+    pub const AssetAccountDeposit: Balance = 100 * MILLIUNIT;
+    pub const ApprovalDeposit: Balance = EXISTENTIAL_DEPOSIT;
+    pub const AssetsStringLimit: u32 = 50;
+    pub const MetadataDepositBase: Balance = 10 * UNIT;
+    pub const MetadataDepositPerByte: Balance = 100 * MILLIUNIT;
+}
+
+// We allow root only to force-create foreign assets and to manage asset status.
+pub type AssetsForceOrigin = EnsureRoot<AccountId>;
+
+impl pallet_assets::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type AssetId = AssetId;
+    type Currency = Balances;
+    type ForceOrigin = AssetsForceOrigin;
+    type AssetDeposit = AssetDeposit;
+    type AssetAccountDeposit = AssetAccountDeposit;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type ApprovalDeposit = ApprovalDeposit;
+    type StringLimit = AssetsStringLimit;
+    type Freezer = ();
+    type Extra = ();
+    type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const AssetRegistryPalletId: PalletId = PalletId(*b"nb/asreg");
+}
+
+impl pallet_asset_registry::Config for Runtime {
+    type Event = Event;
+    type RegistryOrigin = RootOrRelayGovernance;
+    type Assets = Assets;
+    type ForeignAssetIds = xcm_config::ForeignAssetIds;
+    type PalletId = AssetRegistryPalletId;
+    type StringLimit = AssetsStringLimit;
+}
+
+/// Converts native fees into an asset at the ratio of the asset's `min_balance` to the native
+/// existential deposit.
+type MinBalanceFeeConversion = pallet_assets::BalanceToAssetBalance<Balances, Runtime, ConvertInto>;
+
+/// Converts native fees into an asset. Assets registered with a rate in `AssetRegistry` are
+/// charged at the ratio of that rate to the rate of NB, as for XCM execution. Other assets are
+/// charged at the ratio of their `min_balance` to the native existential deposit, set by
+/// governance with `Assets::force_asset_status`; only sufficient ones can be used.
+pub struct AssetFeeConversion;
+
+impl BalanceConversion<Balance, AssetId, Balance> for AssetFeeConversion {
+    type Error = pallet_assets::ConversionError;
+
+    fn to_asset_balance(balance: Balance, asset_id: AssetId) -> Result<Balance, Self::Error> {
+        match AssetRegistry::units_per_second(asset_id) {
+            Some(units_per_second) => {
+                let native_units_per_second =
+                    <WeightToFee as frame_support::weights::WeightToFee>::weight_to_fee(
+                        &WEIGHT_PER_SECOND,
+                    );
+                Ok(sp_runtime::helpers_128bit::multiply_by_rational(
+                    balance,
+                    units_per_second,
+                    native_units_per_second,
+                )
+                .unwrap_or(Balance::MAX))
+            }
+            None => MinBalanceFeeConversion::to_asset_balance(balance, asset_id),
+        }
+    }
+}
+
+/// Gives the fees paid in `Assets` to the author of the block.
+pub struct AssetsToBlockAuthor;
+
+impl pallet_asset_tx_payment::HandleCredit<AccountId, Assets> for AssetsToBlockAuthor {
+    fn handle_credit(credit: CreditOf<AccountId, Assets>) {
+        if let Some(author) = Authorship::author() {
+            // In case of error the credit is dropped, which burns it.
+            let _ = Assets::resolve(&author, credit);
+        }
+    }
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+    type Fungibles = Assets;
+    type OnChargeAssetTransaction =
+        pallet_asset_tx_payment::FungiblesAdapter<AssetFeeConversion, AssetsToBlockAuthor>;
+}
+
+/// The fee `fee`, in native units, as charged in `asset_id` by `ChargeAssetTxPayment`.
+fn fee_in_asset(fee: Balance, asset_id: AssetId) -> Option<Balance> {
+    let converted = AssetFeeConversion::to_asset_balance(fee, asset_id).ok()?;
+    // Non-zero fees are never rounded down to nothing.
+    Some(if fee == 0 { 0 } else { converted.max(1) })
+}
+
+parameter_types! {
+    pub const MinVestedTransfer: Balance = 100 * MILLIUNIT;
+}
+
+impl pallet_vesting::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type BlockNumberToBalance = ConvertInto;
+    type MinVestedTransfer = MinVestedTransfer;
+    type WeightInfo = pallet_vesting::weights::SubstrateWeight<Runtime>;
+    // `VestingInfo` encode length is 36bytes. 28 schedules gets encoded as 1009 bytes, which is the
+    // highest number of schedules that encodes less than 2^10.
+    const MAX_VESTING_SCHEDULES: u32 = 28;
+}
+
 parameter_types! {
     pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
     pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT / 4;
@@ -394,18 +564,19 @@ impl cumulus_pallet_aura_ext::Config for Runtime {}
 
 impl cumulus_pallet_xcmp_queue::Config for Runtime {
     type Event = Event;
-    type XcmExecutor = XcmExecutor<XcmConfig>;
+    type XcmExecutor = InboundXcmExecutor;
     type ChannelInfo = ParachainSystem;
-    type VersionWrapper = ();
+    // Outbound messages are downgraded to the version negotiated with the sibling.
+    type VersionWrapper = PolkadotXcm;
     type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
-    type ControllerOrigin = EnsureRoot<AccountId>;
+    type ControllerOrigin = RootOrRelayGovernance;
     type ControllerOriginConverter = XcmOriginToTransactDispatchOrigin;
     type WeightInfo = ();
 }
 
 impl cumulus_pallet_dmp_queue::Config for Runtime {
     type Event = Event;
-    type XcmExecutor = XcmExecutor<XcmConfig>;
+    type XcmExecutor = InboundXcmExecutor;
     type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
 }
 
@@ -444,8 +615,43 @@ parameter_types! {
     pub const ExecutiveBody: BodyId = BodyId::Executive;
 }
 
-// We allow root only to execute privileged collator selection operations.
-pub type CollatorSelectionUpdateOrigin = EnsureRoot<AccountId>;
+parameter_types! {
+    /// Whether candidates must have an identity with a positive judgement before they can
+    /// register. Changing it takes a runtime upgrade.
+    pub const CollatorIdentityRequired: bool = false;
+}
+
+/// Candidacy check for `pallet_collator_selection`. A candidate must have registered session
+/// keys and, when `IdentityRequired` is set, an identity judged `Reasonable` or `KnownGood` by
+/// one of the registrars.
+pub struct CollatorRegistration<IdentityRequired>(sp_std::marker::PhantomData<IdentityRequired>);
+
+impl<IdentityRequired: Get<bool>> ValidatorRegistration<AccountId>
+    for CollatorRegistration<IdentityRequired>
+{
+    fn is_registered(id: &AccountId) -> bool {
+        if !Session::is_registered(id) {
+            return false;
+        }
+        if !IdentityRequired::get() {
+            return true;
+        }
+        Identity::identity(id).map_or(false, |registration| {
+            registration.judgements.iter().any(|(_, judgement)| {
+                matches!(
+                    judgement,
+                    pallet_identity::Judgement::Reasonable | pallet_identity::Judgement::KnownGood
+                )
+            })
+        })
+    }
+}
+
+/// Root, or relay chain governance dispatching through XCM.
+pub type RootOrRelayGovernance = EitherOfDiverse<EnsureRoot<AccountId>, RelayGovernanceOrigin>;
+
+// We allow root and relay chain governance to execute privileged collator selection operations.
+pub type CollatorSelectionUpdateOrigin = RootOrRelayGovernance;
 
 impl pallet_collator_selection::Config for Runtime {
     type Event = Event;
@@ -459,10 +665,36 @@ impl pallet_collator_selection::Config for Runtime {
     type KickThreshold = Period;
     type ValidatorId = <Self as frame_system::Config>::AccountId;
     type ValidatorIdOf = pallet_collator_selection::IdentityCollator;
-    type ValidatorRegistration = Session;
+    type ValidatorRegistration = CollatorRegistration<CollatorIdentityRequired>;
     type WeightInfo = ();
 }
 
+parameter_types! {
+    // Minimum 4 fields: display, legal, web and email.
+    pub const BasicDeposit: Balance = 10 * UNIT;
+    pub const FieldDeposit: Balance = 250 * MILLIUNIT;
+    pub const SubAccountDeposit: Balance = 2 * UNIT;
+    pub const MaxSubAccounts: u32 = 100;
+    pub const MaxAdditionalFields: u32 = 100;
+    pub const MaxRegistrars: u32 = 20;
+}
+
+impl pallet_identity::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type BasicDeposit = BasicDeposit;
+    type FieldDeposit = FieldDeposit;
+    type SubAccountDeposit = SubAccountDeposit;
+    type MaxSubAccounts = MaxSubAccounts;
+    type MaxAdditionalFields = MaxAdditionalFields;
+    type MaxRegistrars = MaxRegistrars;
+    // Slashed deposits are burned.
+    type Slashed = ();
+    type ForceOrigin = EnsureRoot<AccountId>;
+    type RegistrarOrigin = EnsureRoot<AccountId>;
+    type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
 /// Configure the pallet template in pallets/template.
 impl pallet_template::Config for Runtime {
     type Event = Event;
@@ -476,6 +708,100 @@ impl pallet_counter::Config for Runtime {
     type XcmSender = XcmRouter;
 }
 
+parameter_types! {
+    pub const XcmRpcHandlerWeight: Weight = 1_000_000_000;
+    pub const XcmRpcRequestTimeout: BlockNumber = 10 * MINUTES;
+}
+
+impl pallet_xcm_rpc::Config for Runtime {
+    type Event = Event;
+    type Origin = Origin;
+    type Call = Call;
+    type XcmSender = XcmRouter;
+    type Handlers = (
+        pallet_xcm_subscriptions::Subscribe<Runtime>,
+        pallet_xcm_subscriptions::Unsubscribe<Runtime>,
+    );
+    type HandlerWeight = XcmRpcHandlerWeight;
+    type RequestTimeout = XcmRpcRequestTimeout;
+}
+
+/// The storage siblings can subscribe to: the counter, and the accounts of users.
+pub struct SubscribableStorage;
+
+impl pallet_xcm_subscriptions::SubscribableValues for SubscribableStorage {
+    fn max_value_len(key: &[u8]) -> Option<u32> {
+        // The final prefix of `System::Account`, then the `Blake2_128Concat` of an account id.
+        const ACCOUNT_KEY_LEN: usize = 32 + 16 + 32;
+        type AccountInfo = frame_system::AccountInfo<Index, pallet_balances::AccountData<Balance>>;
+        if key == pallet_counter::Counter::<Runtime>::hashed_key() {
+            Some(u32::max_encoded_len() as u32)
+        } else if key.len() == ACCOUNT_KEY_LEN
+            && key.starts_with(&frame_system::Account::<Runtime>::final_prefix())
+        {
+            Some(AccountInfo::max_encoded_len() as u32)
+        } else {
+            None
+        }
+    }
+}
+
+parameter_types! {
+    pub const SubscriptionDeposit: Balance = 10 * UNIT;
+    pub const NotificationBaseFee: Balance = 10 * MILLIUNIT;
+    pub const NotificationByteFee: Balance = 10 * MICROUNIT;
+    pub const NotificationWeight: Weight = 1_000_000_000;
+    pub const MaxNotificationWeight: Weight = 5_000_000_000;
+    pub const MaxSubscribedKeyLen: u32 = 128;
+    pub const MaxSubscribedValueLen: u32 = 128;
+    pub const MaxSubscribers: u32 = 100;
+    pub const MaxKeysPerSubscriber: u32 = 10;
+    // The channel status, the outbound queue and its last page are read, and the last two
+    // written.
+    pub XcmSendWeight: Weight = 10_000 + RocksDbWeight::get().reads_writes(3, 2);
+    // Hashing and copying a byte, rounded up.
+    pub const ValueByteWeight: Weight = 1_000;
+}
+
+impl pallet_xcm_subscriptions::Config for Runtime {
+    type Event = Event;
+    type Call = Call;
+    type Currency = Balances;
+    type SovereignAccountOf = xcm_config::LocationToAccountId;
+    type SubscriptionOrigin = RootOrRelayGovernance;
+    type SubscribableKeys = SubscribableStorage;
+    type OnNotification = ();
+    // Burnt, like transaction fees.
+    type FeeDestination = ();
+    type SubscriptionDeposit = SubscriptionDeposit;
+    type NotificationBaseFee = NotificationBaseFee;
+    type NotificationByteFee = NotificationByteFee;
+    type NotificationWeight = NotificationWeight;
+    type SendWeight = XcmSendWeight;
+    type ValueByteWeight = ValueByteWeight;
+    type MaxNotificationWeight = MaxNotificationWeight;
+    type MaxKeyLen = MaxSubscribedKeyLen;
+    type MaxValueLen = MaxSubscribedValueLen;
+    type MaxSubscribers = MaxSubscribers;
+    type MaxKeysPerSubscriber = MaxKeysPerSubscriber;
+}
+
+parameter_types! {
+    pub const MaxCallNameLen: u32 = 64;
+}
+
+impl pallet_maintenance_mode::Config for Runtime {
+    type Event = Event;
+    type MaintenanceOrigin = EnsureRoot<AccountId>;
+    type WhitelistedCalls = MaintenanceWhitelist;
+    type MaxNameLen = MaxCallNameLen;
+}
+
+impl pallet_upgrade_authorization::Config for Runtime {
+    type Event = Event;
+    type AuthorizeOrigin = RelayGovernanceOrigin;
+}
+
 impl pallet_sudo::Config for Runtime {
     type Call = Call;
     type Event = Event;
@@ -500,6 +826,10 @@ construct_runtime!(
         // Monetary stuff.
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 10,
         TransactionPayment: pallet_transaction_payment::{Pallet, Storage, Event<T>} = 11,
+        Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>} = 12,
+        Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 13,
+        AssetTxPayment: pallet_asset_tx_payment::{Pallet} = 14,
+        AssetRegistry: pallet_asset_registry::{Pallet, Call, Storage, Event<T>, Config<T>} = 15,
 
         // Collator support. The order of these 4 are important and shall not change.
         Authorship: pallet_authorship::{Pallet, Call, Storage} = 20,
@@ -513,6 +843,22 @@ construct_runtime!(
         PolkadotXcm: pallet_xcm::{Pallet, Call, Event<T>, Origin, Config} = 31,
         CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin} = 32,
         DmpQueue: cumulus_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 33,
+        XcmTrust: pallet_xcm_trust::{Pallet, Call, Storage, Event<T>, Config} = 34,
+        XTokens: pallet_xtokens::{Pallet, Call, Event<T>} = 35,
+        XcmTrader: pallet_xcm_trader::{Pallet, Call, Storage, Event<T>} = 36,
+        HrmpChannels: pallet_hrmp_channels::{Pallet, Call, Storage, Event<T>} = 37,
+        RelayOperations: pallet_relay_operations::{Pallet, Call, Storage, Event<T>, Config} = 38,
+        XcmLog: pallet_xcm_log::{Pallet, Storage} = 39,
+        XcmCircuitBreaker: pallet_xcm_circuit_breaker::{Pallet, Call, Storage, Event<T>} = 42,
+        OverweightXcm: pallet_overweight_xcm::{Pallet, Call, Storage, Event<T>} = 43,
+        InterchainAccounts: pallet_interchain_accounts::{Pallet, Call, Storage, Event<T>} = 44,
+        XcmRpc: pallet_xcm_rpc::{Pallet, Call, Storage, Event<T>} = 45,
+        XcmSubscriptions: pallet_xcm_subscriptions::{Pallet, Call, Storage, Event<T>} = 46,
+
+        // Identity and operations.
+        Identity: pallet_identity::{Pallet, Call, Storage, Event<T>} = 50,
+        MaintenanceMode: pallet_maintenance_mode::{Pallet, Call, Storage, Event<T>} = 51,
+        UpgradeAuthorization: pallet_upgrade_authorization::{Pallet, Call, Event<T>} = 52,
 
         // Template
         TemplatePallet: pallet_template::{Pallet, Call, Storage, Event<T>}  = 40,
@@ -528,11 +874,16 @@ extern crate frame_benchmarking;
 mod benches {
     define_benchmarks!(
         [frame_system, SystemBench::<Runtime>]
+        [pallet_assets, Assets]
         [pallet_balances, Balances]
+        [pallet_identity, Identity]
         [pallet_session, SessionBench::<Runtime>]
         [pallet_timestamp, Timestamp]
+        [pallet_vesting, Vesting]
         [pallet_collator_selection, CollatorSelection]
         [cumulus_pallet_xcmp_queue, XcmpQueue]
+        [pallet_xcm_benchmarks::fungible, XcmBalances]
+        [pallet_xcm_benchmarks::generic, XcmGeneric]
     );
 }
 
@@ -637,6 +988,98 @@ impl_runtime_apis! {
         }
     }
 
+    impl apis::AssetTxPaymentApi<Block, Balance, AssetId> for Runtime {
+        fn query_info_in_asset(
+            uxt: <Block as BlockT>::Extrinsic,
+            len: u32,
+            asset_id: AssetId,
+        ) -> Option<pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance>> {
+            let info = TransactionPayment::query_info(uxt, len);
+            Some(pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo {
+                partial_fee: fee_in_asset(info.partial_fee, asset_id)?,
+                ..info
+            })
+        }
+        fn query_fee_details_in_asset(
+            uxt: <Block as BlockT>::Extrinsic,
+            len: u32,
+            asset_id: AssetId,
+        ) -> Option<pallet_transaction_payment::FeeDetails<Balance>> {
+            let details = TransactionPayment::query_fee_details(uxt, len);
+            let inclusion_fee = match details.inclusion_fee {
+                Some(fee) => Some(pallet_transaction_payment::InclusionFee {
+                    base_fee: fee_in_asset(fee.base_fee, asset_id)?,
+                    len_fee: fee_in_asset(fee.len_fee, asset_id)?,
+                    adjusted_weight_fee: fee_in_asset(fee.adjusted_weight_fee, asset_id)?,
+                }),
+                None => None,
+            };
+            Some(pallet_transaction_payment::FeeDetails {
+                inclusion_fee,
+                tip: fee_in_asset(details.tip, asset_id)?,
+            })
+        }
+    }
+
+    impl apis::LocationToAccountApi<Block, AccountId> for Runtime {
+        fn location_to_account(location: xcm::VersionedMultiLocation) -> Option<AccountId> {
+            let location = xcm::latest::MultiLocation::try_from(location).ok()?;
+            xcm_config::location_to_account(&location)
+        }
+    }
+
+    impl apis::XcmVersionApi<Block> for Runtime {
+        fn xcm_version_for(dest: xcm::VersionedMultiLocation) -> Option<apis::XcmVersionInfo> {
+            let dest = xcm::latest::MultiLocation::try_from(dest).ok()?;
+            Some(xcm_config::xcm_version_info(&dest))
+        }
+    }
+
+    impl apis::XcmFeeApi<Block, Balance> for Runtime {
+        fn query_xcm_weight(message: xcm::VersionedXcm<()>) -> Option<Weight> {
+            let message = xcm::latest::Xcm::try_from(message).ok()?;
+            xcm_config::xcm_weight(message)
+        }
+
+        fn query_xcm_fee(weight: Weight, asset: xcm::VersionedMultiLocation) -> Option<Balance> {
+            let asset = xcm::latest::MultiLocation::try_from(asset).ok()?;
+            xcm_config::xcm_fee(weight, asset)
+        }
+    }
+
+    impl apis::DryRunApi<Block, Event, Balance> for Runtime {
+        fn dry_run_extrinsic(
+            uxt: <Block as BlockT>::Extrinsic,
+        ) -> apis::ExtrinsicDryRunEffects<Event, Balance> {
+            dry_run::dry_run_extrinsic(uxt)
+        }
+
+        fn dry_run_xcm(
+            origin: xcm::VersionedMultiLocation,
+            message: xcm::VersionedXcm<()>,
+        ) -> Option<apis::XcmDryRunEffects<Event>> {
+            let origin = xcm::latest::MultiLocation::try_from(origin).ok()?;
+            let message = xcm::latest::Xcm::<()>::try_from(message).ok()?;
+            Some(dry_run::dry_run_xcm(origin, xcm::latest::Xcm::<Call>::from(message)))
+        }
+    }
+
+    impl apis::XcmLogApi<Block, BlockNumber> for Runtime {
+        fn recent_xcm_messages(count: u32) -> Vec<apis::XcmLogEntry<BlockNumber>> {
+            XcmLog::recent_entries(count)
+        }
+
+        fn xcm_messages_with_hash(hash: [u8; 32]) -> Vec<apis::XcmLogEntry<BlockNumber>> {
+            XcmLog::entries_with_hash(hash)
+        }
+    }
+
+    impl apis::OverweightXcmApi<Block> for Runtime {
+        fn overweight_xcm_messages() -> Vec<apis::OverweightMessage> {
+            OverweightXcm::overweight_messages()
+        }
+    }
+
     impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
         fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
             ParachainSystem::collect_collation_info(header)
@@ -667,6 +1110,10 @@ impl_runtime_apis! {
             use frame_system_benchmarking::Pallet as SystemBench;
             use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
 
+            // These are used by `define_benchmarks!`, and must have these names.
+            type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+            type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
+
             let mut list = Vec::<BenchmarkList>::new();
             list_benchmarks!(list, extra);
 
@@ -685,6 +1132,63 @@ impl_runtime_apis! {
             use cumulus_pallet_session_benchmarking::Pallet as SessionBench;
             impl cumulus_pallet_session_benchmarking::Config for Runtime {}
 
+            use frame_benchmarking::BenchmarkError;
+            use xcm::latest::prelude::*;
+
+            impl pallet_xcm_benchmarks::Config for Runtime {
+                type XcmConfig = xcm_config::XcmConfig;
+                type AccountIdConverter = xcm_config::LocationToAccountId;
+                fn valid_destination() -> Result<MultiLocation, BenchmarkError> {
+                    Ok(MultiLocation::parent())
+                }
+                fn worst_case_holding() -> MultiAssets {
+                    // NB is the only asset the fungible benchmarks act on.
+                    vec![MultiAsset {
+                        id: Concrete(xcm_config::NativeLocation::get()),
+                        fun: Fungible(u128::MAX),
+                    }].into()
+                }
+            }
+
+            parameter_types! {
+                // Teleporters are configured in `XcmTrust`, whose storage is empty here.
+                pub const TrustedTeleporter: Option<(MultiLocation, MultiAsset)> = None;
+            }
+
+            impl pallet_xcm_benchmarks::fungible::Config for Runtime {
+                type TransactAsset = Balances;
+                type CheckedAccount = xcm_config::NativeCheckingAccount;
+                type TrustedTeleporter = TrustedTeleporter;
+                fn get_multi_asset() -> MultiAsset {
+                    MultiAsset {
+                        id: Concrete(xcm_config::NativeLocation::get()),
+                        fun: Fungible(UNIT),
+                    }
+                }
+            }
+
+            impl pallet_xcm_benchmarks::generic::Config for Runtime {
+                type Call = Call;
+                fn worst_case_response() -> (u64, Response) {
+                    (0u64, Response::Version(Default::default()))
+                }
+                fn transact_origin() -> Result<MultiLocation, BenchmarkError> {
+                    Ok(MultiLocation::parent())
+                }
+                fn subscribe_origin() -> Result<MultiLocation, BenchmarkError> {
+                    Ok(MultiLocation::parent())
+                }
+                fn claimable_asset() -> Result<(MultiLocation, MultiLocation, MultiAssets), BenchmarkError> {
+                    let origin = MultiLocation::parent();
+                    let assets: MultiAssets = (Concrete(xcm_config::NativeLocation::get()), 1_000 * UNIT).into();
+                    let ticket = MultiLocation { parents: 0, interior: Here };
+                    Ok((origin, ticket, assets))
+                }
+            }
+
+            type XcmBalances = pallet_xcm_benchmarks::fungible::Pallet::<Runtime>;
+            type XcmGeneric = pallet_xcm_benchmarks::generic::Pallet::<Runtime>;
+
             let whitelist: Vec<TrackedStorageKey> = vec![
                 // Block Number
                 hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
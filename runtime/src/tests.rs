@@ -0,0 +1,394 @@
+use super::{
+    apis::DryRunApi,
+    opaque::SessionKeys,
+    xcm_config::{
+        AssetsPalletLocation, Barrier, FungiblesTransactor, XcmOriginToTransactDispatchOrigin,
+        RELAY_TOKEN_ASSET_ID,
+    },
+    AccountId, Assets, Balance, Balances, Block, Call, CollatorRegistration, Counter, DmpQueue,
+    Event, Identity, InterchainAccounts, Origin, ParachainSystem, PolkadotXcm, Runtime, Session,
+    SignedExtra, System, UncheckedExtrinsic, XcmCircuitBreaker, XcmpQueue, UNIT,
+};
+use codec::{Decode, Encode};
+use cumulus_pallet_parachain_system::relay_state_snapshot::MessagingStateSnapshot;
+use cumulus_primitives_core::{
+    AbridgedHrmpChannel, DmpMessageHandler, ParaId, XcmpMessageFormat, XcmpMessageSource,
+};
+use frame_support::{
+    pallet_prelude::OptionQuery,
+    traits::{ConstBool, GenesisBuild, Hooks, ValidatorRegistration},
+};
+use pallet_identity::Judgement;
+use pallet_interchain_accounts::CallFilter;
+use sp_core::{sr25519, Pair};
+use sp_runtime::{
+    generic::{Era, SignedPayload},
+    MultiAddress, MultiSignature,
+};
+use xcm::{latest::prelude::*, v1, VersionedMultiLocation, VersionedXcm};
+use xcm_executor::traits::{ConvertOrigin, ShouldExecute, TransactAsset};
+
+#[frame_support::storage_alias]
+type RelevantMessagingState = StorageValue<ParachainSystem, MessagingStateSnapshot, OptionQuery>;
+
+fn alice() -> sr25519::Pair {
+    sr25519::Pair::from_string("//Alice", None).unwrap()
+}
+
+fn new_test_ext(trust: pallet_xcm_trust::GenesisConfig) -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Runtime>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Runtime> {
+        balances: vec![(alice().public().into(), 1_000 * UNIT)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    GenesisBuild::<Runtime>::assimilate_storage(&trust, &mut storage).unwrap();
+    storage.into()
+}
+
+fn set_counter_value(value: u32) -> Xcm<Call> {
+    Xcm(vec![Transact {
+        origin_type: OriginKind::Native,
+        require_weight_at_most: 1_000,
+        call: Call::Counter(pallet_counter::Call::set_counter_value { value })
+            .encode()
+            .into(),
+    }])
+}
+
+/// Open channels to `siblings` in the relay chain state `ParachainSystem` knows of.
+fn open_channels_to(siblings: &[u32]) {
+    let channel = AbridgedHrmpChannel {
+        max_capacity: 10,
+        max_total_size: 10_000,
+        max_message_size: 1_000,
+        msg_count: 0,
+        total_size: 0,
+        mqc_head: None,
+    };
+    RelevantMessagingState::put(MessagingStateSnapshot {
+        dmq_mqc_head: Default::default(),
+        relay_dispatch_queue_size: (0, 0),
+        ingress_channels: vec![],
+        egress_channels: siblings
+            .iter()
+            .map(|id| (ParaId::from(*id), channel.clone()))
+            .collect(),
+    });
+}
+
+/// The messages `XcmpQueue` queued for each sibling, in the version they are sent in.
+fn queued_xcmp_messages() -> Vec<(ParaId, VersionedXcm<()>)> {
+    let mut messages = vec![];
+    for (para, page) in XcmpQueue::take_outbound_messages(usize::MAX) {
+        let mut data = &page[..];
+        assert!(matches!(
+            XcmpMessageFormat::decode(&mut data),
+            Ok(XcmpMessageFormat::ConcatenatedVersionedXcm)
+        ));
+        while !data.is_empty() {
+            messages.push((para, VersionedXcm::decode(&mut data).unwrap()));
+        }
+    }
+    messages
+}
+
+/// `call` signed by `signer` with the nonce `nonce`, paying its fee in NB.
+fn signed(call: Call, signer: &sr25519::Pair, nonce: u32) -> UncheckedExtrinsic {
+    let extra: SignedExtra = (
+        frame_system::CheckNonZeroSender::new(),
+        frame_system::CheckSpecVersion::new(),
+        frame_system::CheckTxVersion::new(),
+        frame_system::CheckGenesis::new(),
+        frame_system::CheckEra::from(Era::Immortal),
+        frame_system::CheckNonce::from(nonce),
+        frame_system::CheckWeight::new(),
+        pallet_asset_tx_payment::ChargeAssetTxPayment::from(0, None),
+    );
+    let payload = SignedPayload::new(call.clone(), extra.clone()).unwrap();
+    let signature = payload.using_encoded(|payload| signer.sign(payload));
+    UncheckedExtrinsic::new_signed(
+        call,
+        MultiAddress::Id(signer.public().into()),
+        MultiSignature::Sr25519(signature),
+        extra,
+    )
+}
+
+#[test]
+fn counter_message_from_genesis_sibling_passes_barrier() {
+    new_test_ext(pallet_xcm_trust::GenesisConfig {
+        unpaid_execution_siblings: vec![(2000, 1_000_000)],
+        paid_execution_siblings: vec![2000],
+    })
+    .execute_with(|| {
+        let sibling = MultiLocation::new(1, X1(Parachain(2000)));
+        assert!(
+            Barrier::should_execute(&sibling, &mut set_counter_value(1), 1_000, &mut 0).is_ok()
+        );
+
+        let stranger = MultiLocation::new(1, X1(Parachain(3000)));
+        assert!(
+            Barrier::should_execute(&stranger, &mut set_counter_value(1), 1_000, &mut 0).is_err()
+        );
+    });
+}
+
+#[test]
+fn only_opened_interchain_accounts_dispatch_from_within_siblings() {
+    new_test_ext(Default::default()).execute_with(|| {
+        let user = MultiLocation::new(
+            1,
+            X2(
+                Parachain(2000),
+                AccountId32 {
+                    network: Any,
+                    id: [1; 32],
+                },
+            ),
+        );
+        let pallet = MultiLocation::new(1, X2(Parachain(2000), PalletInstance(10)));
+        let pallet_user = MultiLocation::new(
+            1,
+            X3(
+                Parachain(2000),
+                PalletInstance(10),
+                AccountId32 {
+                    network: Any,
+                    id: [1; 32],
+                },
+            ),
+        );
+        for location in [&user, &pallet, &pallet_user] {
+            assert!(XcmOriginToTransactDispatchOrigin::convert_origin(
+                location.clone(),
+                OriginKind::SovereignAccount
+            )
+            .is_err());
+        }
+
+        // Only users can open an account.
+        for location in [&pallet, &pallet_user] {
+            assert!(InterchainAccounts::open_account(
+                pallet_xcm::Origin::Xcm(location.clone()).into(),
+                CallFilter::All
+            )
+            .is_err());
+        }
+        assert!(InterchainAccounts::open_account(
+            pallet_xcm::Origin::Xcm(user.clone()).into(),
+            CallFilter::All
+        )
+        .is_ok());
+        assert!(XcmOriginToTransactDispatchOrigin::convert_origin(
+            user,
+            OriginKind::SovereignAccount
+        )
+        .is_ok());
+
+        // The sibling itself is not restricted.
+        let sibling = MultiLocation::new(1, X1(Parachain(2000)));
+        assert!(XcmOriginToTransactDispatchOrigin::convert_origin(
+            sibling,
+            OriginKind::SovereignAccount
+        )
+        .is_ok());
+    });
+}
+
+#[test]
+fn dry_run_reports_the_fee_paid() {
+    new_test_ext(Default::default()).execute_with(|| {
+        let account: AccountId = alice().public().into();
+        let call = Call::System(frame_system::Call::remark_with_event {
+            remark: b"dry run".to_vec(),
+        });
+        let effects = <Runtime as DryRunApi<Block, Event, Balance>>::dry_run_extrinsic(signed(
+            call,
+            &alice(),
+            0,
+        ));
+
+        assert_eq!(effects.result, Ok(Ok(())));
+        assert!(effects.actual_weight > 0);
+        let fee = effects.fee.unwrap();
+        assert!(fee > 0);
+        assert_eq!(Balances::free_balance(&account), 1_000 * UNIT - fee);
+        assert!(effects.events.iter().any(|event| matches!(
+            event,
+            Event::System(frame_system::Event::Remarked { sender, .. }) if *sender == account
+        )));
+        assert!(effects.sent_xcm.is_empty());
+    });
+}
+
+#[test]
+fn dry_run_reports_messages_too_large_for_the_log() {
+    new_test_ext(Default::default()).execute_with(|| {
+        assert!(PolkadotXcm::force_default_xcm_version(Origin::root(), Some(2)).is_ok());
+        // Larger than `MaxXcmLogMessageSize`.
+        let message = Xcm(vec![Transact {
+            origin_type: OriginKind::SovereignAccount,
+            require_weight_at_most: 1_000,
+            call: vec![0; 2_000].into(),
+        }]);
+        let call = Call::PolkadotXcm(pallet_xcm::Call::send {
+            dest: Box::new(VersionedMultiLocation::from(MultiLocation::parent())),
+            message: Box::new(VersionedXcm::from(message)),
+        });
+        let effects = <Runtime as DryRunApi<Block, Event, Balance>>::dry_run_extrinsic(signed(
+            call,
+            &alice(),
+            0,
+        ));
+
+        assert_eq!(effects.result, Ok(Ok(())));
+        assert_eq!(effects.sent_xcm.len(), 1);
+        let (dest, sent) = &effects.sent_xcm[0];
+        assert_eq!(*dest, VersionedMultiLocation::from(MultiLocation::parent()));
+        assert!(sent.encode().len() > 2_000);
+    });
+}
+
+#[test]
+fn downward_messages_stay_queued_while_inbound_is_paused() {
+    new_test_ext(Default::default()).execute_with(|| {
+        System::set_block_number(1);
+        assert!(XcmCircuitBreaker::pause_inbound(Origin::root()).is_ok());
+
+        // Relay chain governance pausing outbound XCM.
+        let message = VersionedXcm::<Call>::from(Xcm(vec![Transact {
+            origin_type: OriginKind::Xcm,
+            require_weight_at_most: 1_000_000_000,
+            call: Call::XcmCircuitBreaker(pallet_xcm_circuit_breaker::Call::pause_outbound {})
+                .encode()
+                .into(),
+        }]));
+        DmpQueue::handle_dmp_messages(vec![(1, message.encode())].into_iter(), 100_000_000_000);
+        assert!(!XcmCircuitBreaker::outbound_paused());
+
+        // Still held while paused.
+        DmpQueue::on_idle(1, 100_000_000_000);
+        assert!(!XcmCircuitBreaker::outbound_paused());
+
+        assert!(XcmCircuitBreaker::resume_inbound(Origin::root()).is_ok());
+        DmpQueue::on_idle(1, 100_000_000_000);
+        assert!(XcmCircuitBreaker::outbound_paused());
+    });
+}
+
+#[test]
+fn foreign_assets_are_not_transacted_by_their_index() {
+    new_test_ext(Default::default()).execute_with(|| {
+        let owner: AccountId = alice().public().into();
+        let beneficiary = MultiLocation::new(
+            0,
+            X1(AccountId32 {
+                network: Any,
+                id: owner.clone().into(),
+            }),
+        );
+        let by_index = |id: u32| {
+            let mut location = AssetsPalletLocation::get();
+            location.push_interior(GeneralIndex(id.into())).unwrap();
+            MultiAsset::from((location, 10))
+        };
+
+        for id in [1, RELAY_TOKEN_ASSET_ID] {
+            assert!(
+                Assets::force_create(Origin::root(), id, owner.clone().into(), true, 1).is_ok()
+            );
+        }
+        assert!(FungiblesTransactor::deposit_asset(&by_index(1), &beneficiary).is_ok());
+        assert!(
+            FungiblesTransactor::deposit_asset(&by_index(RELAY_TOKEN_ASSET_ID), &beneficiary)
+                .is_err()
+        );
+        assert_eq!(Assets::balance(RELAY_TOKEN_ASSET_ID, &owner), 0);
+    });
+}
+
+#[test]
+fn counter_messages_are_sent_in_the_version_of_the_sibling() {
+    new_test_ext(Default::default()).execute_with(|| {
+        open_channels_to(&[2000, 3000]);
+        for (para_id, version) in [(2000, 1), (3000, 2)] {
+            assert!(PolkadotXcm::force_xcm_version(
+                Origin::root(),
+                Box::new(MultiLocation::new(1, X1(Parachain(para_id)))),
+                version
+            )
+            .is_ok());
+            assert!(Counter::set_counter(Origin::root(), para_id.into(), 42).is_ok());
+        }
+
+        let set_counter_value =
+            Call::Counter(pallet_counter::Call::set_counter_value { value: 42 }).encode();
+        let messages = queued_xcmp_messages();
+        assert_eq!(messages.len(), 2);
+        for (para, message) in messages {
+            match (u32::from(para), message) {
+                (2000, VersionedXcm::V1(v1::Xcm::Transact { call, .. })) => {
+                    assert_eq!(call.into_encoded(), set_counter_value)
+                }
+                (3000, VersionedXcm::V2(Xcm(instructions))) => match &instructions[..] {
+                    [Transact { call, .. }] => {
+                        assert_eq!(call.clone().into_encoded(), set_counter_value)
+                    }
+                    other => panic!("expected a single Transact, got {:?}", other),
+                },
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+    });
+}
+
+#[test]
+fn collator_candidates_need_a_judged_identity_if_required() {
+    type IdentityRequired = CollatorRegistration<ConstBool<true>>;
+    type IdentityNotRequired = CollatorRegistration<ConstBool<false>>;
+
+    new_test_ext(Default::default()).execute_with(|| {
+        let candidate: AccountId = alice().public().into();
+        let registrar: AccountId = sr25519::Pair::from_string("//Bob", None)
+            .unwrap()
+            .public()
+            .into();
+        assert!(!IdentityNotRequired::is_registered(&candidate));
+        assert!(Session::set_keys(
+            Origin::signed(candidate.clone()),
+            SessionKeys {
+                aura: alice().public().into()
+            },
+            vec![]
+        )
+        .is_ok());
+        assert!(IdentityNotRequired::is_registered(&candidate));
+        assert!(!IdentityRequired::is_registered(&candidate));
+
+        assert!(Identity::set_identity(
+            Origin::signed(candidate.clone()),
+            Box::new(Default::default())
+        )
+        .is_ok());
+        assert!(Identity::add_registrar(Origin::root(), registrar.clone()).is_ok());
+        let judge = |judgement| {
+            Identity::provide_judgement(
+                Origin::signed(registrar.clone()),
+                0,
+                candidate.clone().into(),
+                judgement,
+            )
+        };
+        // An identity without a positive judgement is not enough.
+        assert!(!IdentityRequired::is_registered(&candidate));
+        assert!(judge(Judgement::LowQuality).is_ok());
+        assert!(!IdentityRequired::is_registered(&candidate));
+
+        assert!(judge(Judgement::Reasonable).is_ok());
+        assert!(IdentityRequired::is_registered(&candidate));
+        assert!(IdentityNotRequired::is_registered(&candidate));
+    });
+}
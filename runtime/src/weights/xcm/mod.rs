@@ -0,0 +1,191 @@
+//! Per-instruction XCM weights, used through `xcm_builder::WeightInfoBounds`.
+
+mod pallet_xcm_benchmarks_fungible;
+mod pallet_xcm_benchmarks_generic;
+
+use crate::Runtime;
+use frame_support::weights::Weight;
+use xcm::{
+    latest::{prelude::*, XcmWeightInfo},
+    DoubleEncoded,
+};
+
+use pallet_xcm_benchmarks_fungible::WeightInfo as XcmFungibleWeight;
+use pallet_xcm_benchmarks_generic::WeightInfo as XcmGeneric;
+
+/// Number of assets a wildcard is assumed to match when it has no explicit limit.
+const MAX_ASSETS: u32 = 100;
+
+/// Scales the weight of an instruction on a single asset to the number of assets it acts on.
+trait WeighMultiAssets {
+    fn weigh_multi_assets(&self, weight: Weight) -> Weight;
+}
+
+impl WeighMultiAssets for MultiAssets {
+    fn weigh_multi_assets(&self, weight: Weight) -> Weight {
+        (self.len() as Weight).saturating_mul(weight)
+    }
+}
+
+impl WeighMultiAssets for MultiAssetFilter {
+    fn weigh_multi_assets(&self, weight: Weight) -> Weight {
+        match self {
+            Definite(assets) => assets.weigh_multi_assets(weight),
+            Wild(_) => (MAX_ASSETS as Weight).saturating_mul(weight),
+        }
+    }
+}
+
+/// The weight of a deposit of `assets`, which never acts on more than `max_assets` assets.
+fn weigh_deposit(assets: &MultiAssetFilter, max_assets: u32, weight: Weight) -> Weight {
+    let count = match assets {
+        Definite(assets) => (assets.len() as u32).min(max_assets),
+        Wild(_) => max_assets.min(MAX_ASSETS),
+    };
+    (count as Weight).saturating_mul(weight)
+}
+
+/// Weighs XCM instructions with the results of `pallet_xcm_benchmarks`.
+///
+/// The fungible benchmarks run against `Balances`. Instructions on assets held in `Assets` are
+/// charged the same, which is an approximation until the fungibles adapter is benchmarked too.
+pub struct NbchainXcmWeight<Call>(core::marker::PhantomData<Call>);
+
+impl<Call> XcmWeightInfo<Call> for NbchainXcmWeight<Call> {
+    fn withdraw_asset(assets: &MultiAssets) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::withdraw_asset())
+    }
+    fn reserve_asset_deposited(assets: &MultiAssets) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::reserve_asset_deposited())
+    }
+    fn receive_teleported_asset(assets: &MultiAssets) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::receive_teleported_asset())
+    }
+    fn query_response(_query_id: &u64, _response: &Response, _max_weight: &u64) -> Weight {
+        XcmGeneric::<Runtime>::query_response()
+    }
+    fn transfer_asset(assets: &MultiAssets, _dest: &MultiLocation) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::transfer_asset())
+    }
+    fn transfer_reserve_asset(
+        assets: &MultiAssets,
+        _dest: &MultiLocation,
+        _xcm: &Xcm<()>,
+    ) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::transfer_reserve_asset())
+    }
+    fn transact(
+        _origin_type: &OriginKind,
+        require_weight_at_most: &u64,
+        _call: &DoubleEncoded<Call>,
+    ) -> Weight {
+        // The benchmark only covers dispatching; the call itself may use up to
+        // `require_weight_at_most`, and what it doesn't use is refunded by the executor.
+        XcmGeneric::<Runtime>::transact().saturating_add(*require_weight_at_most)
+    }
+    fn hrmp_new_channel_open_request(
+        _sender: &u32,
+        _max_message_size: &u32,
+        _max_capacity: &u32,
+    ) -> Weight {
+        // Only the relay chain handles HRMP notifications.
+        Weight::MAX
+    }
+    fn hrmp_channel_accepted(_recipient: &u32) -> Weight {
+        // Only the relay chain handles HRMP notifications.
+        Weight::MAX
+    }
+    fn hrmp_channel_closing(_initiator: &u32, _sender: &u32, _recipient: &u32) -> Weight {
+        // Only the relay chain handles HRMP notifications.
+        Weight::MAX
+    }
+    fn clear_origin() -> Weight {
+        XcmGeneric::<Runtime>::clear_origin()
+    }
+    fn descend_origin(_who: &InteriorMultiLocation) -> Weight {
+        XcmGeneric::<Runtime>::descend_origin()
+    }
+    fn report_error(
+        _query_id: &QueryId,
+        _dest: &MultiLocation,
+        _max_response_weight: &u64,
+    ) -> Weight {
+        XcmGeneric::<Runtime>::report_error()
+    }
+    fn deposit_asset(
+        assets: &MultiAssetFilter,
+        max_assets: &u32,
+        _beneficiary: &MultiLocation,
+    ) -> Weight {
+        weigh_deposit(
+            assets,
+            *max_assets,
+            XcmFungibleWeight::<Runtime>::deposit_asset(),
+        )
+    }
+    fn deposit_reserve_asset(
+        assets: &MultiAssetFilter,
+        max_assets: &u32,
+        _dest: &MultiLocation,
+        _xcm: &Xcm<()>,
+    ) -> Weight {
+        weigh_deposit(
+            assets,
+            *max_assets,
+            XcmFungibleWeight::<Runtime>::deposit_reserve_asset(),
+        )
+    }
+    fn exchange_asset(_give: &MultiAssetFilter, _receive: &MultiAssets) -> Weight {
+        // Exchanging assets is not supported by the executor.
+        Weight::MAX
+    }
+    fn initiate_reserve_withdraw(
+        assets: &MultiAssetFilter,
+        _reserve: &MultiLocation,
+        _xcm: &Xcm<()>,
+    ) -> Weight {
+        assets.weigh_multi_assets(XcmGeneric::<Runtime>::initiate_reserve_withdraw())
+    }
+    fn initiate_teleport(
+        assets: &MultiAssetFilter,
+        _dest: &MultiLocation,
+        _xcm: &Xcm<()>,
+    ) -> Weight {
+        assets.weigh_multi_assets(XcmFungibleWeight::<Runtime>::initiate_teleport())
+    }
+    fn query_holding(
+        _query_id: &u64,
+        _dest: &MultiLocation,
+        _assets: &MultiAssetFilter,
+        _max_response_weight: &u64,
+    ) -> Weight {
+        XcmGeneric::<Runtime>::query_holding()
+    }
+    fn buy_execution(_fees: &MultiAsset, _weight_limit: &WeightLimit) -> Weight {
+        XcmGeneric::<Runtime>::buy_execution()
+    }
+    fn refund_surplus() -> Weight {
+        XcmGeneric::<Runtime>::refund_surplus()
+    }
+    fn set_error_handler(_xcm: &Xcm<Call>) -> Weight {
+        XcmGeneric::<Runtime>::set_error_handler()
+    }
+    fn set_appendix(_xcm: &Xcm<Call>) -> Weight {
+        XcmGeneric::<Runtime>::set_appendix()
+    }
+    fn clear_error() -> Weight {
+        XcmGeneric::<Runtime>::clear_error()
+    }
+    fn claim_asset(_assets: &MultiAssets, _ticket: &MultiLocation) -> Weight {
+        XcmGeneric::<Runtime>::claim_asset()
+    }
+    fn trap(_code: &u64) -> Weight {
+        XcmGeneric::<Runtime>::trap()
+    }
+    fn subscribe_version(_query_id: &QueryId, _max_response_weight: &u64) -> Weight {
+        XcmGeneric::<Runtime>::subscribe_version()
+    }
+    fn unsubscribe_version() -> Weight {
+        XcmGeneric::<Runtime>::unsubscribe_version()
+    }
+}
@@ -0,0 +1,56 @@
+//! Weights for `pallet_xcm_benchmarks::fungible`.
+//!
+//! THESE ARE NOT YET GENERATED FOR THIS RUNTIME. They are the Westend results of the same
+//! `pallet_xcm_benchmarks` release, kept so XCM is weighed per instruction rather than at a flat
+//! 1_000_000_000 each. `reserve_asset_deposited`, which Westend does not benchmark, is set to the
+//! cost of `withdraw_asset`. Overwrite this file by running `scripts/benchmark-xcm.sh` on the
+//! reference hardware.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weights for `pallet_xcm_benchmarks::fungible`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo<T> {
+    pub(crate) fn withdraw_asset() -> Weight {
+        (20_385_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    pub(crate) fn transfer_asset() -> Weight {
+        (32_756_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    pub(crate) fn transfer_reserve_asset() -> Weight {
+        (50_645_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+    pub(crate) fn reserve_asset_deposited() -> Weight {
+        (20_385_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    pub(crate) fn receive_teleported_asset() -> Weight {
+        (4_061_000 as Weight)
+    }
+    pub(crate) fn deposit_asset() -> Weight {
+        (22_425_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    pub(crate) fn deposit_reserve_asset() -> Weight {
+        (39_099_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    pub(crate) fn initiate_teleport() -> Weight {
+        (22_692_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+}
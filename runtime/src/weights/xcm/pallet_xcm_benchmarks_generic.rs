@@ -0,0 +1,75 @@
+//! Weights for `pallet_xcm_benchmarks::generic`.
+//!
+//! THESE ARE NOT YET GENERATED FOR THIS RUNTIME. They are the Westend results of the same
+//! `pallet_xcm_benchmarks` release, kept so XCM is weighed per instruction rather than at a flat
+//! 1_000_000_000 each. Overwrite this file by running `scripts/benchmark-xcm.sh` on the reference
+//! hardware.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weights for `pallet_xcm_benchmarks::generic`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo<T> {
+    pub(crate) fn query_holding() -> Weight {
+        (21_822_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    pub(crate) fn buy_execution() -> Weight {
+        (3_109_000 as Weight)
+    }
+    pub(crate) fn query_response() -> Weight {
+        (12_087_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    pub(crate) fn transact() -> Weight {
+        (12_398_000 as Weight)
+    }
+    pub(crate) fn refund_surplus() -> Weight {
+        (3_258_000 as Weight)
+    }
+    pub(crate) fn set_error_handler() -> Weight {
+        (3_144_000 as Weight)
+    }
+    pub(crate) fn set_appendix() -> Weight {
+        (3_166_000 as Weight)
+    }
+    pub(crate) fn clear_error() -> Weight {
+        (3_153_000 as Weight)
+    }
+    pub(crate) fn descend_origin() -> Weight {
+        (4_184_000 as Weight)
+    }
+    pub(crate) fn clear_origin() -> Weight {
+        (3_156_000 as Weight)
+    }
+    pub(crate) fn report_error() -> Weight {
+        (17_676_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    pub(crate) fn claim_asset() -> Weight {
+        (7_020_000 as Weight).saturating_add(T::DbWeight::get().reads(1 as Weight))
+    }
+    pub(crate) fn trap() -> Weight {
+        (3_127_000 as Weight)
+    }
+    pub(crate) fn subscribe_version() -> Weight {
+        (19_390_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    pub(crate) fn unsubscribe_version() -> Weight {
+        (4_831_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    pub(crate) fn initiate_reserve_withdraw() -> Weight {
+        (20_958_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+}
@@ -1,31 +1,67 @@
 use super::{
-    AccountId, Balances, Call, Event, Origin, ParachainInfo, ParachainSystem, PolkadotXcm, Runtime,
-    WeightToFee, XcmpQueue,
+    apis::XcmVersionInfo, weights::xcm::NbchainXcmWeight, AccountId, AssetId, AssetRegistry,
+    Assets, Authorship, Balance, Balances, BlockNumber, Call, DmpQueue, Event, Origin,
+    ParachainInfo, ParachainSystem, PolkadotXcm, RootOrRelayGovernance, Runtime, WeightToFee,
+    XcmpQueue, HOURS,
 };
+use codec::{Compact, Decode, Encode, MaxEncodedLen};
+use cumulus_pallet_parachain_system::{
+    relay_state_snapshot::MessagingStateSnapshot, RelaychainBlockNumberProvider,
+};
+use cumulus_primitives_core::ParaId;
 use frame_support::{
-    match_types, parameter_types,
-    traits::{Everything, Nothing},
-    weights::Weight,
+    match_types,
+    pallet_prelude::{Blake2_128Concat, DispatchError, OptionQuery, Twox64Concat, ValueQuery},
+    parameter_types,
+    traits::{ConstU32, Contains, Get, Nothing, PalletInfoAccess},
+    weights::{constants::WEIGHT_PER_SECOND, Weight},
+    RuntimeDebug,
 };
-use pallet_xcm::XcmPassthrough;
+use frame_system::{EnsureRoot, RawOrigin};
+use pallet_hrmp_channels::ChannelId;
+use pallet_overweight_xcm::{OverweightIndex, OverweightQueue, ParkedMessage, RelayBlockNumber};
+use pallet_xcm::{EnsureXcm, XcmPassthrough};
+use pallet_xcm_circuit_breaker::{PausableExecutor, PausableRouter};
+use pallet_xcm_log::{LoggingExecutor, LoggingRouter, LoggingWeigher};
 use polkadot_parachain::primitives::Sibling;
 use polkadot_runtime_common::impls::ToAuthor;
-use xcm::latest::prelude::*;
+use scale_info::TypeInfo;
+use sp_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
+use xcm::{
+    latest::prelude::*,
+    prelude::{VersionedMultiLocation, XcmVersion},
+};
 use xcm_builder::{
     AccountId32Aliases, AllowKnownQueryResponses, AllowSubscriptionsFrom,
-    AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, CurrencyAdapter, EnsureXcmOrigin,
-    FixedWeightBounds, IsConcrete, LocationInverter, NativeAsset, ParentIsPreset,
-    RelayChainAsNative, SiblingParachainAsNative, SiblingParachainConvertsVia,
-    SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
-    UsingComponents,
+    AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, AsPrefixedGeneralIndex, Case,
+    ConvertedConcreteAssetId, CurrencyAdapter, EnsureXcmOrigin, FungiblesAdapter, IsConcrete,
+    LocationInverter, ParentIsPreset, RelayChainAsNative, SiblingParachainAsNative,
+    SiblingParachainConvertsVia, SignedAccountId32AsNative, SignedToAccountId32,
+    SovereignSignedViaLocation, TakeWeightCredit, UsingComponents, WeightInfoBounds,
+};
+use xcm_executor::{
+    traits::{Convert, JustTry, WeightBounds, WeightTrader},
+    Assets, XcmExecutor,
 };
-use xcm_executor::XcmExecutor;
 
 parameter_types! {
-    pub const RelayLocation: MultiLocation = MultiLocation::parent();
     pub const RelayNetwork: NetworkId = NetworkId::Any;
     pub RelayChainOrigin: Origin = cumulus_pallet_xcm::Origin::Relay.into();
     pub Ancestry: MultiLocation = Parachain(ParachainInfo::parachain_id().into()).into();
+    /// The location of NB, the native currency, as seen from this chain.
+    pub const NativeLocation: MultiLocation = MultiLocation::here();
+    /// The location under which assets created locally in `Assets` are identified.
+    pub AssetsPalletLocation: MultiLocation =
+        PalletInstance(<Assets as PalletInfoAccess>::index() as u8).into();
+    pub CheckingAccount: AccountId = PolkadotXcm::check_account();
+    /// Teleports of NB are tracked in `CheckingAccount`, so no more can be teleported in than was
+    /// teleported out.
+    pub NativeCheckingAccount: Option<AccountId> = Some(CheckingAccount::get());
+    /// The relay token is always accepted from the relay chain.
+    pub RelayTokenFromRelay: (MultiAssetFilter, MultiLocation) = (
+        Wild(AllOf { id: Concrete(MultiLocation::parent()), fun: WildFungible }),
+        MultiLocation::parent(),
+    );
 }
 
 /// Type for specifying how a `MultiLocation` can be converted into an `AccountId`. This is used
@@ -38,22 +74,169 @@ pub type LocationToAccountId = (
     SiblingParachainConvertsVia<Sibling, AccountId>,
     // Straight up local `AccountId32` origins just alias directly to `AccountId`.
     AccountId32Aliases<RelayNetwork, AccountId>,
+    // Accounts and pallets on the relay chain and siblings get an account derived from a hash of
+    // their description.
+    HashedDescription<AccountId, DescribeFamily<DescribeTerminal>>,
 );
 
-/// Means for transacting assets on this chain.
-pub type LocalAssetTransactor = CurrencyAdapter<
+/// Describes a location independently of the chain it is expressed from, so that the same remote
+/// account always derives the same local account.
+pub trait DescribeLocation {
+    /// The description of `location`, or `None` if it can't be described.
+    fn describe_location(location: &MultiLocation) -> Option<Vec<u8>>;
+}
+
+/// Describes an account or a pallet, relative to the chain it is on.
+pub struct DescribeTerminal;
+
+impl DescribeLocation for DescribeTerminal {
+    fn describe_location(location: &MultiLocation) -> Option<Vec<u8>> {
+        match (location.parents, &location.interior) {
+            (0, X1(AccountId32 { id, .. })) => Some((b"AccountId32", id).encode()),
+            (0, X1(AccountKey20 { key, .. })) => Some((b"AccountKey20", key).encode()),
+            (0, X1(PalletInstance(index))) => Some((b"Pallet", index).encode()),
+            _ => None,
+        }
+    }
+}
+
+/// Describes something inside the relay chain or a sibling, using `Inner` to describe it relative
+/// to its chain. The chains themselves are not described, they have their own accounts.
+pub struct DescribeFamily<Inner>(PhantomData<Inner>);
+
+impl<Inner: DescribeLocation> DescribeLocation for DescribeFamily<Inner> {
+    fn describe_location(location: &MultiLocation) -> Option<Vec<u8>> {
+        if location.parents != 1 {
+            return None;
+        }
+        match location.first_interior() {
+            Some(Parachain(para_id)) => {
+                let (inner, _) = location.interior.clone().split_first();
+                if inner == Here {
+                    return None;
+                }
+                let inner = Inner::describe_location(&inner.into())?;
+                Some((b"SiblingChain", Compact(*para_id), inner).encode())
+            }
+            Some(_) => {
+                let inner = Inner::describe_location(&location.interior.clone().into())?;
+                Some((b"ParentChain", inner).encode())
+            }
+            None => None,
+        }
+    }
+}
+
+/// Converts any location described by `Describe` into the account whose id is the hash of its
+/// description.
+pub struct HashedDescription<AccountId, Describe>(PhantomData<(AccountId, Describe)>);
+
+impl<AccountId: From<[u8; 32]> + Clone, Describe: DescribeLocation>
+    Convert<MultiLocation, AccountId> for HashedDescription<AccountId, Describe>
+{
+    fn convert_ref(location: impl Borrow<MultiLocation>) -> Result<AccountId, ()> {
+        let description = Describe::describe_location(location.borrow()).ok_or(())?;
+        Ok(sp_io::hashing::blake2_256(&description).into())
+    }
+}
+
+/// The local account of `location`, as used for `Transact` and asset deposits.
+pub fn location_to_account(location: &MultiLocation) -> Option<AccountId> {
+    LocationToAccountId::convert_ref(location).ok()
+}
+
+/// Means for transacting the native currency on this chain.
+pub type CurrencyTransactor = CurrencyAdapter<
     // Use this currency:
     Balances,
     // Use this currency when it is a fungible asset matching the given location or name:
-    IsConcrete<RelayLocation>,
+    IsConcrete<NativeLocation>,
     // Do a simple punn to convert an AccountId32 MultiLocation into a native chain account ID:
     LocationToAccountId,
     // Our chain's account ID type (we can't get away without mentioning it explicitly):
     AccountId,
-    // We don't track any teleports.
-    (),
+    // Track teleports of NB in the checking account:
+    NativeCheckingAccount,
 >;
 
+/// Bit set on the ids foreign assets are registered under in `AssetRegistry`. Assets created
+/// locally never have it, since `create` calls for such ids are rejected by the runtime's base
+/// call filter.
+pub const FOREIGN_ASSET_ID_FLAG: AssetId = 1 << 31;
+
+/// The ids reserved for foreign assets, which `AssetRegistry` registers assets under.
+pub struct ForeignAssetIds;
+
+impl Contains<AssetId> for ForeignAssetIds {
+    fn contains(id: &AssetId) -> bool {
+        id & FOREIGN_ASSET_ID_FLAG != 0
+    }
+}
+
+/// The id the relay chain token is registered under at genesis.
+pub const RELAY_TOKEN_ASSET_ID: AssetId = FOREIGN_ASSET_ID_FLAG;
+
+/// The units of the relay token charged per second of weight until governance changes its rate:
+/// the rate of transaction fees, which execution was charged at in the relay token before
+/// `AssetRegistry` kept rates.
+pub fn relay_token_genesis_units_per_second() -> u128 {
+    <WeightToFee as frame_support::weights::WeightToFee>::weight_to_fee(&WEIGHT_PER_SECOND)
+}
+
+/// Converts general indices to the ids of assets created locally. The ids reserved for foreign
+/// assets are rejected, so that those assets are only known by their registered location.
+pub struct LocalAssetId;
+
+impl Convert<u128, AssetId> for LocalAssetId {
+    fn convert(index: u128) -> Result<AssetId, u128> {
+        match <JustTry as Convert<u128, AssetId>>::convert(index) {
+            Ok(id) if !ForeignAssetIds::contains(&id) => Ok(id),
+            _ => Err(index),
+        }
+    }
+
+    fn reverse(id: AssetId) -> Result<u128, AssetId> {
+        if ForeignAssetIds::contains(&id) {
+            return Err(id);
+        }
+        <JustTry as Convert<u128, AssetId>>::reverse(id)
+    }
+}
+
+/// Means for transacting assets held in `Assets`.
+pub type FungiblesTransactor = FungiblesAdapter<
+    // Use this fungibles implementation:
+    Assets,
+    // Use this currency when it is a fungible asset matching the given location or name:
+    (
+        // Assets created on this chain, identified by their index in `Assets`:
+        ConvertedConcreteAssetId<
+            AssetId,
+            Balance,
+            AsPrefixedGeneralIndex<AssetsPalletLocation, AssetId, LocalAssetId>,
+            JustTry,
+        >,
+        // Assets from the relay chain and siblings, registered in `AssetRegistry`:
+        ConvertedConcreteAssetId<
+            AssetId,
+            Balance,
+            pallet_asset_registry::LocationToAssetId<Runtime>,
+            JustTry,
+        >,
+    ),
+    // Convert an XCM MultiLocation into a local account id:
+    LocationToAccountId,
+    // Our chain's account ID type (we can't get away without mentioning it explicitly):
+    AccountId,
+    // We don't track any teleports of `Assets`.
+    Nothing,
+    // The account to use for tracking teleports.
+    CheckingAccount,
+>;
+
+/// Means for transacting assets on this chain.
+pub type AssetTransactors = (CurrencyTransactor, FungiblesTransactor);
+
 /// This is the type we use to convert an (incoming) XCM origin into a local `Origin` instance,
 /// ready for dispatching a transaction with Xcm's `Transact`. There is an `OriginKind` which can
 /// biases the kind of local `Origin` it will become.
@@ -61,7 +244,12 @@ pub type XcmOriginToTransactDispatchOrigin = (
     // Sovereign account converter; this attempts to derive an `AccountId` from the origin location
     // using `LocationToAccountId` and then turn that into the usual `Signed` origin. Useful for
     // foreign chains who want to have a local sovereign account on this chain which they control.
-    SovereignSignedViaLocation<LocationToAccountId, Origin>,
+    // Users of siblings only get the interchain account they opened in `InterchainAccounts`,
+    // restricted to the calls it may dispatch.
+    pallet_interchain_accounts::RestrictInterchainAccounts<
+        Runtime,
+        SovereignSignedViaLocation<LocationToAccountId, Origin>,
+    >,
     // Native converter for Relay-chain (Parent) location; will converts to a `Relay` origin when
     // recognized.
     RelayChainAsNative<RelayChainOrigin, Origin>,
@@ -76,11 +264,14 @@ pub type XcmOriginToTransactDispatchOrigin = (
 );
 
 parameter_types! {
-    // One XCM operation is 1_000_000_000 weight - almost certainly a conservative estimate.
-    pub UnitWeightCost: Weight = 1_000_000_000;
     pub const MaxInstructions: u32 = 100;
 }
 
+/// Weighs XCM messages instruction by instruction with the results of `pallet_xcm_benchmarks`,
+/// plus logging the messages they send in `XcmLog`.
+pub type XcmWeigher =
+    LoggingWeigher<Runtime, WeightInfoBounds<NbchainXcmWeight<Call>, Call, MaxInstructions>>;
+
 match_types! {
     pub type ParentOrParentsExecutivePlurality: impl Contains<MultiLocation> = {
         MultiLocation { parents: 1, interior: Here } |
@@ -88,6 +279,28 @@ match_types! {
     };
 }
 
+/// Relay chain governance: the relay chain itself or its executive body, dispatching a `Transact`
+/// with `OriginKind::Xcm`, which `XcmPassthrough` turns into a `pallet_xcm` origin.
+pub type RelayGovernanceOrigin = EnsureXcm<ParentOrParentsExecutivePlurality>;
+
+match_types! {
+    pub type SiblingUsers: impl Contains<MultiLocation> = {
+        MultiLocation { parents: 1, interior: X2(Parachain(_), AccountId32 { .. }) } |
+        MultiLocation { parents: 1, interior: X2(Parachain(_), AccountKey20 { .. }) }
+    };
+}
+
+/// Locations within siblings: their users, pallets, and anything else they descend into.
+pub struct DescendedFromSiblings;
+
+impl Contains<MultiLocation> for DescendedFromSiblings {
+    fn contains(location: &MultiLocation) -> bool {
+        location.parents == 1
+            && location.interior.len() > 1
+            && matches!(location.interior.first(), Some(Parachain(_)))
+    }
+}
+
 match_types! {
     pub type ParentOrSiblings: impl Contains<MultiLocation> = {
         MultiLocation { parents: 1, interior: Here } |
@@ -97,49 +310,113 @@ match_types! {
 
 pub type Barrier = (
     TakeWeightCredit,
-    AllowTopLevelPaidExecutionFrom<Everything>,
-    // Messages coming from system parachains need not pay for execution.
-    AllowUnpaidExecutionFrom<ParentOrSiblings>,
+    // Only locations allowed in `XcmTrust` can buy execution.
+    AllowTopLevelPaidExecutionFrom<pallet_xcm_trust::PaidExecutionAllowlist<Runtime>>,
+    // ..also for their users, paying from the users' own accounts.
+    pallet_interchain_accounts::AllowDescendedPaidExecutionFrom<
+        pallet_xcm_trust::PaidExecutionAllowlist<Runtime>,
+    >,
+    // The relay chain and its executive body need not pay for execution.
+    AllowUnpaidExecutionFrom<ParentOrParentsExecutivePlurality>,
+    // Neither do locations allowed in `XcmTrust`, within their weight limit per block.
+    pallet_xcm_trust::AllowUnpaidExecutionFromAllowlist<Runtime>,
     // Expected responses are OK.
     AllowKnownQueryResponses<PolkadotXcm>,
     // Subscriptions for version tracking are OK.
     AllowSubscriptionsFrom<ParentOrSiblings>,
 );
 
+/// Reserve-backed assets are accepted from the relay chain for the relay token, and from the
+/// reserves registered in `XcmTrust` for everything else.
+pub type TrustedReserves = (
+    Case<RelayTokenFromRelay>,
+    pallet_xcm_trust::IsTrustedReserve<Runtime>,
+);
+
 pub struct XcmConfig;
 
 impl xcm_executor::Config for XcmConfig {
     type Call = Call;
     type XcmSender = XcmRouter;
     // How to withdraw and deposit an asset.
-    type AssetTransactor = LocalAssetTransactor;
+    type AssetTransactor = AssetTransactors;
     type OriginConverter = XcmOriginToTransactDispatchOrigin;
-    type IsReserve = NativeAsset;
-    type IsTeleporter = ();
-    // Teleporting is disabled.
+    type IsReserve = TrustedReserves;
+    // Teleported assets are accepted from the teleporters registered in `XcmTrust`.
+    type IsTeleporter = pallet_xcm_trust::IsTrustedTeleporter<Runtime>;
     type LocationInverter = LocationInverter<Ancestry>;
     type Barrier = Barrier;
-    type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
-    type Trader =
-        UsingComponents<WeightToFee, RelayLocation, AccountId, Balances, ToAuthor<Runtime>>;
+    type Weigher = XcmWeigher;
+    type Trader = (
+        // Assets with a rate in `AssetRegistry`:
+        pallet_xcm_trader::AssetRateTrader<Runtime>,
+        // ..and NB at the rate of transaction fees. NB is what `Balances` holds, and the fee is
+        // issued in it for the block author, so this can't take the relay token as it used to.
+        UsingComponents<WeightToFee, NativeLocation, AccountId, Balances, ToAuthor<Runtime>>,
+    );
     type ResponseHandler = PolkadotXcm;
     type AssetTrap = PolkadotXcm;
     type AssetClaims = PolkadotXcm;
     type SubscriptionService = PolkadotXcm;
 }
 
+/// The weight `XcmWeigher` gives `message`, as charged when executing it here.
+pub fn xcm_weight(message: Xcm<()>) -> Option<Weight> {
+    XcmWeigher::weight(&mut Xcm::<Call>::from(message)).ok()
+}
+
+/// The fee the trader of `XcmConfig` charges for `weight` in the asset at `asset`, found by
+/// buying the weight with as much of the asset as there can be.
+pub fn xcm_fee(weight: Weight, asset: MultiLocation) -> Option<Balance> {
+    let available = Balance::MAX;
+    let payment: Assets = MultiAsset::from((asset.clone(), available)).into();
+    let mut trader = <XcmConfig as xcm_executor::Config>::Trader::new();
+    let unused = trader.buy_weight(weight, payment).ok()?;
+    let left = unused
+        .fungible
+        .get(&Concrete(asset))
+        .copied()
+        .unwrap_or_default();
+    Some(available - left)
+}
+
 /// No local origins on this chain are allowed to dispatch XCM sends/executions.
 pub type LocalOriginToLocation = SignedToAccountId32<Origin, AccountId, RelayNetwork>;
 
+/// Only NB can be sent to other chains with `reserve_transfer_assets`, this chain being its
+/// reserve.
+pub struct OnlyNativeReserveTransfers;
+
+impl Contains<(MultiLocation, Vec<MultiAsset>)> for OnlyNativeReserveTransfers {
+    fn contains((_, assets): &(MultiLocation, Vec<MultiAsset>)) -> bool {
+        assets.iter().all(|asset| {
+            matches!(
+                asset,
+                MultiAsset { id: Concrete(location), fun: Fungible(_) }
+                    if *location == NativeLocation::get()
+            )
+        })
+    }
+}
+
 /// The means for routing XCM messages which are not for local execution into the right message
 /// queues.
-pub type XcmRouter = (
+pub type XcmTransports = (
     // Two routers - use UMP to communicate with the relay chain:
-    cumulus_primitives_utility::ParentAsUmp<ParachainSystem, ()>,
+    cumulus_primitives_utility::ParentAsUmp<ParachainSystem, PolkadotXcm>,
     // ..and XCMP to communicate with the sibling chains.
     XcmpQueue,
 );
 
+/// Routes messages through [`XcmTransports`] unless `XcmCircuitBreaker` paused outbound XCM,
+/// logging them in `XcmLog`.
+pub type XcmRouter = LoggingRouter<Runtime, PausableRouter<Runtime, XcmTransports>>;
+
+/// Executes the messages received from other chains unless `XcmCircuitBreaker` paused inbound
+/// XCM, logging them in `XcmLog`.
+pub type InboundXcmExecutor =
+    LoggingExecutor<Runtime, PausableExecutor<Runtime, XcmExecutor<XcmConfig>>>;
+
 impl pallet_xcm::Config for Runtime {
     type Event = Event;
     type SendXcmOrigin = EnsureXcmOrigin<Origin, LocalOriginToLocation>;
@@ -149,9 +426,11 @@ impl pallet_xcm::Config for Runtime {
     // ^ Disable dispatchable execute on the XCM pallet.
     // Needs to be `Everything` for local testing.
     type XcmExecutor = XcmExecutor<XcmConfig>;
-    type XcmTeleportFilter = Everything;
-    type XcmReserveTransferFilter = Nothing;
-    type Weigher = FixedWeightBounds<UnitWeightCost, Call, MaxInstructions>;
+    // Only assets with a trusted teleporter can be teleported. `BaseFilter` checks that the
+    // destination is one of them.
+    type XcmTeleportFilter = pallet_xcm_trust::TeleportableAssets<Runtime>;
+    type XcmReserveTransferFilter = OnlyNativeReserveTransfers;
+    type Weigher = XcmWeigher;
     type LocationInverter = LocationInverter<Ancestry>;
     type Origin = Origin;
     type Call = Call;
@@ -161,7 +440,393 @@ impl pallet_xcm::Config for Runtime {
     type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
 }
 
+/// Versions advertised by other chains to `PolkadotXcm`, keyed by the XCM version of the runtime
+/// that stored them and the location of the chain.
+#[frame_support::storage_alias]
+type SupportedVersion = StorageDoubleMap<
+    PolkadotXcm,
+    Twox64Concat,
+    XcmVersion,
+    Blake2_128Concat,
+    VersionedMultiLocation,
+    XcmVersion,
+    OptionQuery,
+>;
+
+/// The version `PolkadotXcm` uses for chains whose version is not known yet.
+#[frame_support::storage_alias]
+type SafeXcmVersion = StorageValue<PolkadotXcm, XcmVersion, OptionQuery>;
+
+/// The version negotiated with `dest` and the version messages sent to it are wrapped in, which
+/// is the same as `PolkadotXcm` does when wrapping them.
+pub fn xcm_version_info(dest: &MultiLocation) -> XcmVersionInfo {
+    let negotiated = SupportedVersion::get(
+        xcm::latest::VERSION,
+        VersionedMultiLocation::from(dest.clone()),
+    );
+    let used = negotiated
+        .or_else(SafeXcmVersion::get)
+        .map(|version| version.min(xcm::latest::VERSION));
+    XcmVersionInfo { negotiated, used }
+}
+
 impl cumulus_pallet_xcm::Config for Runtime {
     type Event = Event;
+    type XcmExecutor = InboundXcmExecutor;
+}
+
+impl pallet_xcm_trust::Config for Runtime {
+    type Event = Event;
+    type TrustOrigin = EnsureRoot<AccountId>;
+}
+
+/// Converts a local account into the location it executes XCM from.
+pub struct AccountIdToMultiLocation;
+
+impl sp_runtime::traits::Convert<AccountId, MultiLocation> for AccountIdToMultiLocation {
+    fn convert(account: AccountId) -> MultiLocation {
+        X1(AccountId32 {
+            network: NetworkId::Any,
+            id: account.into(),
+        })
+        .into()
+    }
+}
+
+/// The author of the current block.
+pub struct BlockAuthor;
+
+impl Get<Option<AccountId>> for BlockAuthor {
+    fn get() -> Option<AccountId> {
+        Authorship::author()
+    }
+}
+
+impl pallet_xcm_trader::Config for Runtime {
+    type Event = Event;
+    type FeeBeneficiaryOrigin = EnsureRoot<AccountId>;
+    type AssetTransactor = AssetTransactors;
+    type AccountIdToMultiLocation = AccountIdToMultiLocation;
+    // Fees paid in assets go to the block author unless another account is set.
+    type DefaultFeeBeneficiary = BlockAuthor;
+    // Assets are charged at their rate in `AssetRegistry`.
+    type Rates = RegisteredAssetRates;
+}
+
+/// The rates of the assets registered in `AssetRegistry`.
+pub struct RegisteredAssetRates;
+
+impl pallet_xcm_trader::UnitsPerSecondProvider for RegisteredAssetRates {
+    fn units_per_second(asset: &MultiLocation) -> Option<u128> {
+        AssetRegistry::units_per_second_at(asset)
+    }
+}
+
+/// The currencies that can be sent to other chains with `XTokens`.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum CurrencyId {
+    /// NB, the native currency.
+    Native,
+    /// An asset held in `Assets`, created on this chain or received from another one.
+    Asset(AssetId),
+}
+
+/// Gives the location of a currency as seen from this chain.
+pub struct CurrencyIdToMultiLocation;
+
+impl sp_runtime::traits::Convert<CurrencyId, Option<MultiLocation>> for CurrencyIdToMultiLocation {
+    fn convert(currency_id: CurrencyId) -> Option<MultiLocation> {
+        match currency_id {
+            CurrencyId::Native => Some(NativeLocation::get()),
+            CurrencyId::Asset(id) if ForeignAssetIds::contains(&id) => AssetRegistry::location(id),
+            CurrencyId::Asset(id) => {
+                let mut location = AssetsPalletLocation::get();
+                location.push_interior(GeneralIndex(id.into())).ok()?;
+                Some(location)
+            }
+        }
+    }
+}
+
+impl pallet_xtokens::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type CurrencyId = CurrencyId;
+    type CurrencyIdConvert = CurrencyIdToMultiLocation;
+    type AccountIdToMultiLocation = AccountIdToMultiLocation;
+    // Assets are teleported to the teleporters registered for them in `XcmTrust`.
+    type IsTeleporter = pallet_xcm_trust::IsTrustedTeleporter<Runtime>;
     type XcmExecutor = XcmExecutor<XcmConfig>;
+    type Weigher = XcmWeigher;
+    type LocationInverter = LocationInverter<Ancestry>;
+    // Transfers are reported with the hash `XcmLog` gives their message.
+    type SentMessageHash = pallet_xcm_log::LastSentHash<Runtime>;
+}
+
+parameter_types! {
+    /// The index of `Hrmp` in the Polkadot, Kusama and Rococo runtimes.
+    pub const RelayHrmpPalletIndex: u8 = 60;
+    /// 0.1 of a relay chain token with 10 decimals, well above the cost of any `Hrmp` call.
+    pub const HrmpRelayFee: u128 = 1_000_000_000;
+    pub const HrmpRelayCallWeight: Weight = 1_000_000_000;
+}
+
+#[frame_support::storage_alias]
+type RelevantMessagingState = StorageValue<ParachainSystem, MessagingStateSnapshot, OptionQuery>;
+
+/// Reads the channels of this chain from the relay chain state proven to `ParachainSystem` in the
+/// current block.
+pub struct RelayChannelStatus;
+
+impl pallet_hrmp_channels::HrmpChannelStatus for RelayChannelStatus {
+    fn is_open(channel: &ChannelId) -> Option<bool> {
+        let state = RelevantMessagingState::get()?;
+        let self_id = ParachainInfo::parachain_id();
+        let open = if channel.sender == self_id {
+            state
+                .egress_channels
+                .iter()
+                .any(|(id, _)| *id == channel.recipient)
+        } else if channel.recipient == self_id {
+            state
+                .ingress_channels
+                .iter()
+                .any(|(id, _)| *id == channel.sender)
+        } else {
+            false
+        };
+        Some(open)
+    }
+}
+
+impl pallet_hrmp_channels::Config for Runtime {
+    type Event = Event;
+    type ChannelOrigin = RootOrRelayGovernance;
+    type SelfParaId = ParachainInfo;
+    type XcmSender = XcmRouter;
+    type ChannelStatus = RelayChannelStatus;
+    type RelayHrmpPalletIndex = RelayHrmpPalletIndex;
+    type RelayFee = HrmpRelayFee;
+    type RelayCallWeight = HrmpRelayCallWeight;
+    type MaxChannelChecks = ConstU32<10>;
+}
+
+parameter_types! {
+    pub const RelayOperationFee: u128 = 1_000_000_000;
+    pub const RelayOperationCallWeight: Weight = 4_000_000_000;
+    /// Outcomes of relay chain operations are expected within an hour.
+    pub const RelayOperationResultTimeout: BlockNumber = HOURS;
+}
+
+impl pallet_relay_operations::Config for Runtime {
+    type Event = Event;
+    type Origin = Origin;
+    type Call = Call;
+    type StakingOrigin = RootOrRelayGovernance;
+    type CrowdloanOrigin = RootOrRelayGovernance;
+    type TransferOrigin = EnsureRoot<AccountId>;
+    type SelfParaId = ParachainInfo;
+    // Goes to the relay chain over UMP through `ParentAsUmp`.
+    type XcmSender = XcmRouter;
+    type AccountIdToMultiLocation = AccountIdToMultiLocation;
+    type RelayFee = RelayOperationFee;
+    type RelayCallWeight = RelayOperationCallWeight;
+    type ResultTimeout = RelayOperationResultTimeout;
+}
+
+parameter_types! {
+    pub const MaxXcmLogEntries: u32 = 256;
+    /// Larger messages are logged by hash only.
+    pub const MaxXcmLogMessageSize: u32 = 1024;
+}
+
+impl pallet_xcm_log::Config for Runtime {
+    type MaxEntries = MaxXcmLogEntries;
+    type MaxMessageSize = MaxXcmLogMessageSize;
+    // `XcmTransports` send messages in the version `PolkadotXcm` wraps them in.
+    type VersionWrapper = PolkadotXcm;
+}
+
+parameter_types! {
+    pub const XcmPauseDuration: BlockNumber = 6 * HOURS;
+}
+
+impl pallet_xcm_circuit_breaker::Config for Runtime {
+    type Event = Event;
+    // While inbound XCM is paused, relay chain governance can only lift the pause by waiting for
+    // it to expire.
+    type PauseOrigin = RootOrRelayGovernance;
+    type PauseDuration = XcmPauseDuration;
+}
+
+/// The overweight messages parked by `XcmpQueue`.
+mod xcmp_queue_overweight {
+    use super::*;
+
+    /// The messages, with their sender and the relay chain block they were sent in.
+    #[frame_support::storage_alias]
+    pub type Overweight = StorageMap<
+        XcmpQueue,
+        Twox64Concat,
+        OverweightIndex,
+        (ParaId, RelayBlockNumber, Vec<u8>),
+        OptionQuery,
+    >;
+
+    /// The number of messages ever parked.
+    #[frame_support::storage_alias]
+    pub type OverweightCount = StorageValue<XcmpQueue, OverweightIndex, ValueQuery>;
+}
+
+/// The overweight messages parked by `DmpQueue`.
+mod dmp_queue_overweight {
+    use super::*;
+
+    /// The messages, with the relay chain block they were sent in.
+    #[frame_support::storage_alias]
+    pub type Overweight = StorageMap<
+        DmpQueue,
+        Blake2_128Concat,
+        OverweightIndex,
+        (RelayBlockNumber, Vec<u8>),
+        OptionQuery,
+    >;
+
+    /// The layout of `cumulus_pallet_dmp_queue::PageIndexData`, whose fields are private.
+    #[derive(Default, Decode, Encode)]
+    pub struct PageIndexData {
+        pub begin_used: u32,
+        pub end_used: u32,
+        pub overweight_count: OverweightIndex,
+    }
+
+    #[frame_support::storage_alias]
+    pub type PageIndex = StorageValue<DmpQueue, PageIndexData, ValueQuery>;
+}
+
+/// The overweight messages of `XcmpQueue`, serviced as root.
+pub struct XcmpOverweightQueue;
+
+impl OverweightQueue for XcmpOverweightQueue {
+    fn overweight_count() -> OverweightIndex {
+        xcmp_queue_overweight::OverweightCount::get()
+    }
+
+    fn overweight_message(index: OverweightIndex) -> Option<ParkedMessage> {
+        xcmp_queue_overweight::Overweight::get(index).map(Self::parked)
+    }
+
+    fn overweight_messages() -> Vec<(OverweightIndex, ParkedMessage)> {
+        xcmp_queue_overweight::Overweight::iter()
+            .map(|(index, message)| (index, Self::parked(message)))
+            .collect()
+    }
+
+    fn service_overweight(
+        index: OverweightIndex,
+        weight_limit: Weight,
+    ) -> Result<Weight, DispatchError> {
+        XcmpQueue::service_overweight(RawOrigin::Root.into(), index, weight_limit)
+            .map(|info| info.actual_weight.unwrap_or(weight_limit))
+            .map_err(|error| error.error)
+    }
+
+    fn remove_overweight(index: OverweightIndex) {
+        xcmp_queue_overweight::Overweight::remove(index);
+    }
+}
+
+impl XcmpOverweightQueue {
+    fn parked((sender, sent_at, data): (ParaId, RelayBlockNumber, Vec<u8>)) -> ParkedMessage {
+        ParkedMessage {
+            origin: MultiLocation::new(1, X1(Parachain(sender.into()))),
+            sent_at,
+            data,
+        }
+    }
+}
+
+/// The overweight messages of `DmpQueue`, serviced as root.
+pub struct DmpOverweightQueue;
+
+impl OverweightQueue for DmpOverweightQueue {
+    fn overweight_count() -> OverweightIndex {
+        dmp_queue_overweight::PageIndex::get().overweight_count
+    }
+
+    fn overweight_message(index: OverweightIndex) -> Option<ParkedMessage> {
+        dmp_queue_overweight::Overweight::get(index).map(Self::parked)
+    }
+
+    fn overweight_messages() -> Vec<(OverweightIndex, ParkedMessage)> {
+        dmp_queue_overweight::Overweight::iter()
+            .map(|(index, message)| (index, Self::parked(message)))
+            .collect()
+    }
+
+    fn service_overweight(
+        index: OverweightIndex,
+        weight_limit: Weight,
+    ) -> Result<Weight, DispatchError> {
+        DmpQueue::service_overweight(RawOrigin::Root.into(), index, weight_limit)
+            .map(|info| info.actual_weight.unwrap_or(weight_limit))
+            .map_err(|error| error.error)
+    }
+
+    fn remove_overweight(index: OverweightIndex) {
+        dmp_queue_overweight::Overweight::remove(index);
+    }
+}
+
+impl DmpOverweightQueue {
+    fn parked((sent_at, data): (RelayBlockNumber, Vec<u8>)) -> ParkedMessage {
+        ParkedMessage {
+            origin: MultiLocation::parent(),
+            sent_at,
+            data,
+        }
+    }
+}
+
+parameter_types! {
+    /// A week of relay chain blocks.
+    pub const OverweightXcmExpiryPeriod: RelayBlockNumber = 7 * 24 * 600;
+}
+
+impl pallet_overweight_xcm::Config for Runtime {
+    type Event = Event;
+    type ServiceOrigin = RootOrRelayGovernance;
+    type XcmpQueue = XcmpOverweightQueue;
+    type DmpQueue = DmpOverweightQueue;
+    type Weigher = XcmWeigher;
+    type RelayBlockNumberProvider = RelaychainBlockNumberProvider<Runtime>;
+    type ExpiryPeriod = OverweightXcmExpiryPeriod;
+    type MaxExpiredPerBlock = ConstU32<10>;
+    type MaxChecksPerBlock = ConstU32<50>;
+}
+
+/// The calls interchain accounts may dispatch, on top of their own call filter: anything but
+/// the calls reserved for inherents and root.
+pub struct InterchainAccountCalls;
+
+impl Contains<Call> for InterchainAccountCalls {
+    fn contains(call: &Call) -> bool {
+        !matches!(
+            call,
+            Call::ParachainSystem(_) | Call::Timestamp(_) | Call::Sudo(_)
+        )
+    }
+}
+
+impl pallet_interchain_accounts::Config for Runtime {
+    type Event = Event;
+    type Call = Call;
+    // Users open their account with a `Transact` using `OriginKind::Xcm`.
+    type ControllerOrigin = EnsureXcm<SiblingUsers>;
+    type ForceOrigin = RootOrRelayGovernance;
+    // Only users can open accounts, so anything else within a sibling is refused.
+    type IsController = DescendedFromSiblings;
+    type LocationToAccountId = LocationToAccountId;
+    type AllowedCalls = InterchainAccountCalls;
+    type MaxCallPatterns = ConstU32<16>;
 }
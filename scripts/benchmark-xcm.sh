@@ -0,0 +1,18 @@
+#!/bin/bash
+# Generates the per-instruction XCM weights. Run from the repository root on the reference hardware.
+
+cargo build --release --features runtime-benchmarks
+
+mkdir -p runtime/src/weights/xcm
+
+for pallet in fungible generic; do
+  ./target/release/nbchain-node benchmark pallet \
+    --chain=dev \
+    --steps=50 \
+    --repeat=20 \
+    --pallet=pallet_xcm_benchmarks::$pallet \
+    --extrinsic='*' \
+    --execution=wasm \
+    --wasm-execution=compiled \
+    --output=runtime/src/weights/xcm/pallet_xcm_benchmarks_$pallet.rs
+done